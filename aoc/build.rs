@@ -0,0 +1,34 @@
+use std::process::Command;
+
+fn output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+
+    if output.status.success() {
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        None
+    }
+}
+
+fn main() {
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let toolchain = output(&rustc, &["--version"]);
+
+    let revision = output("git", &["rev-parse", "HEAD"]).map(|revision| {
+        match output("git", &["status", "--porcelain", "--untracked-files=no"]) {
+            Some(status) if !status.is_empty() => format!("{}-dirty", revision),
+            _ => revision,
+        }
+    });
+
+    println!(
+        "cargo:rustc-env=AOC_TOOLCHAIN={}",
+        toolchain.as_deref().unwrap_or("unknown")
+    );
+    println!(
+        "cargo:rustc-env=AOC_REVISION={}",
+        revision.as_deref().unwrap_or("unknown")
+    );
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/index");
+}
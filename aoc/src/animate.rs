@@ -0,0 +1,148 @@
+use crate::days::Day;
+use std::io::Write;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
+
+/// What a line typed while animating does.
+enum Control {
+    /// An empty line pauses or resumes.
+    Pause,
+    /// `s` shows the next generation and pauses.
+    Step,
+    /// `q` stops.
+    Quit,
+}
+
+/// Reads the controls from stdin on a thread of its own, so that the
+/// animation goes on while nothing is typed.
+fn controls() -> Receiver<Control> {
+    let (sender, receiver) = mpsc::channel();
+
+    std::thread::spawn(move || {
+        let mut line = String::new();
+        while std::io::stdin().read_line(&mut line).is_ok_and(|n| n > 0) {
+            let control = match line.trim() {
+                "" => Control::Pause,
+                "s" => Control::Step,
+                "q" => Control::Quit,
+                _ => {
+                    line.clear();
+                    continue;
+                }
+            };
+            if sender.send(control).is_err() {
+                return;
+            }
+            line.clear();
+        }
+    });
+
+    receiver
+}
+
+/// Waits for the next control: as long as it takes while `paused`, else
+/// `delay` at most. Once stdin is closed, there are no more `controls`, and
+/// the animation plays on with `delay` between frames.
+fn next_control(
+    controls: &mut Option<Receiver<Control>>,
+    paused: &mut bool,
+    delay: Duration,
+) -> Option<Control> {
+    let received = controls.as_ref().map(|controls| {
+        if *paused {
+            controls.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        } else {
+            controls.recv_timeout(delay)
+        }
+    });
+
+    match received {
+        Some(Ok(control)) => Some(control),
+        Some(Err(RecvTimeoutError::Timeout)) => None,
+        Some(Err(RecvTimeoutError::Disconnected)) | None => {
+            *controls = None;
+            *paused = false;
+            std::thread::sleep(delay);
+            None
+        }
+    }
+}
+
+/// Draws the generations of the given parts of `day` in the terminal, `fps`
+/// a second, and returns an error if none of the parts has any.
+pub fn play(day: &Day, input: &str, parts: &[usize], fps: f64) -> Result<(), String> {
+    let mut controls = Some(controls());
+    let delay = Duration::from_secs_f64(1.0 / fps);
+    let (mut paused, mut quit, mut animated) = (false, false, false);
+
+    for &part in parts {
+        if quit {
+            break;
+        }
+
+        let mut show = |generation: usize, frame: &str| {
+            let mut stdout = std::io::stdout().lock();
+            let _ = write!(
+                stdout,
+                "\x1b[2J\x1b[Hday {} part {}, generation {}{}\n\n{}",
+                day.day,
+                part,
+                generation,
+                if paused {
+                    " (paused: enter resumes, s enter steps, q enter quits)"
+                } else {
+                    " (enter pauses, q enter quits)"
+                },
+                frame
+            );
+            let _ = stdout.flush();
+
+            match next_control(&mut controls, &mut paused, delay) {
+                Some(Control::Pause) => paused = !paused,
+                Some(Control::Step) => paused = true,
+                Some(Control::Quit) => quit = true,
+                None => {}
+            }
+
+            !quit
+        };
+
+        animated |= (day.animate)(input, part, &mut show).map_err(|error| error.to_string())?;
+    }
+
+    if animated {
+        Ok(())
+    } else {
+        Err(format!("day {} has no generations to animate", day.day))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn stdin_closed_while_paused() {
+        let (sender, receiver) = mpsc::channel();
+        sender.send(Control::Step).unwrap();
+        drop(sender);
+
+        let (mut controls, mut paused) = (Some(receiver), true);
+        let delay = Duration::from_millis(20);
+        assert!(matches!(
+            next_control(&mut controls, &mut paused, delay),
+            Some(Control::Step)
+        ));
+
+        let start = Instant::now();
+        assert!(next_control(&mut controls, &mut paused, delay).is_none());
+        assert!(start.elapsed() >= delay);
+        assert!(controls.is_none());
+        assert!(!paused);
+
+        let start = Instant::now();
+        assert!(next_control(&mut controls, &mut paused, delay).is_none());
+        assert!(start.elapsed() >= delay);
+    }
+}
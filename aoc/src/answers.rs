@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub const DEFAULT_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../answers.toml");
+
+#[derive(Default, Serialize, Deserialize)]
+struct Entry {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    part_1: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    part_2: Option<String>,
+}
+
+impl Entry {
+    fn part(&mut self, part: usize) -> &mut Option<String> {
+        match part {
+            1 => &mut self.part_1,
+            _ => &mut self.part_2,
+        }
+    }
+}
+
+/// Known answers, keyed by day (`day01`, ...) and by the hash of the input
+/// they were computed from.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Answers(BTreeMap<String, BTreeMap<String, Entry>>);
+
+impl Answers {
+    /// Loads the answers from `path`; a missing file is an empty database.
+    pub fn load(path: &str) -> Result<Self, String> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content)
+                .map_err(|error| format!("invalid answers {}: {}", path, error)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(format!("cannot read answers {}: {}", path, error)),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let content = toml::to_string(self).map_err(|error| error.to_string())?;
+
+        std::fs::write(path, content)
+            .map_err(|error| format!("cannot write answers {}: {}", path, error))
+    }
+
+    pub fn get(&self, day: u32, input: &str, part: usize) -> Option<&str> {
+        let entry = self.0.get(&key(day))?.get(&hash(input))?;
+
+        match part {
+            1 => entry.part_1.as_deref(),
+            _ => entry.part_2.as_deref(),
+        }
+    }
+
+    pub fn insert(&mut self, day: u32, input: &str, part: usize, answer: String) {
+        *self
+            .0
+            .entry(key(day))
+            .or_default()
+            .entry(hash(input))
+            .or_default()
+            .part(part) = Some(answer);
+    }
+}
+
+fn key(day: u32) -> String {
+    format!("day{:02}", day)
+}
+
+/// FNV-1a, so that the keys do not depend on the toolchain.
+fn hash(input: &str) -> String {
+    let hash = input.bytes().fold(0xcbf29ce484222325u64, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    });
+
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stable_hash() {
+        assert_eq!(hash(""), "cbf29ce484222325");
+        assert_eq!(hash("a"), "af63dc4c8601ec8c");
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut answers = Answers::default();
+        answers.insert(21, "input", 1, "5".to_string());
+        answers.insert(21, "input", 2, "mxmxvkd,sqjhc".to_string());
+        answers.insert(25, "input", 1, "14897079".to_string());
+
+        let answers: Answers = toml::from_str(&toml::to_string(&answers).unwrap()).unwrap();
+
+        assert_eq!(answers.get(21, "input", 1), Some("5"));
+        assert_eq!(answers.get(21, "input", 2), Some("mxmxvkd,sqjhc"));
+        assert_eq!(answers.get(25, "input", 2), None);
+        assert_eq!(answers.get(25, "other input", 1), None);
+    }
+}
@@ -0,0 +1,293 @@
+use common::Stats;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+pub const DEFAULT_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../bench-history.jsonl");
+
+/// Changes smaller than this fraction of the baseline are never reported,
+/// however significant.
+const THRESHOLD: f64 = 0.05;
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct Sample {
+    pub runs: usize,
+    pub median_ns: u64,
+    pub mean_ns: u64,
+    pub std_dev_ns: u64,
+    pub min_ns: u64,
+    pub max_ns: u64,
+}
+
+impl From<Stats> for Sample {
+    fn from(stats: Stats) -> Self {
+        let ns = |duration: std::time::Duration| duration.as_nanos() as u64;
+
+        Sample {
+            runs: stats.runs,
+            median_ns: ns(stats.median),
+            mean_ns: ns(stats.mean),
+            std_dev_ns: ns(stats.std_dev),
+            min_ns: ns(stats.min),
+            max_ns: ns(stats.max),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Timing {
+    pub day: u32,
+    pub part: usize,
+    pub parse: Sample,
+    pub solve: Sample,
+}
+
+/// One benchmark session: a line of the history file.
+#[derive(Serialize, Deserialize)]
+pub struct Entry {
+    pub revision: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub toolchain: String,
+    pub results: Vec<Timing>,
+}
+
+impl Entry {
+    fn name(&self) -> String {
+        match &self.label {
+            Some(label) => format!("{} ({})", self.revision, label),
+            None => self.revision.clone(),
+        }
+    }
+
+    pub fn get(&self, day: u32, part: usize) -> Option<&Timing> {
+        self.results
+            .iter()
+            .find(|result| result.day == day && result.part == part)
+    }
+}
+
+/// The benchmark history, oldest session first.
+pub struct History(Vec<Entry>);
+
+impl History {
+    /// Loads the history from `path`; a missing file is an empty history.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(error) => return Err(format!("cannot read history {}: {}", path, error)),
+        };
+
+        content
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                serde_json::from_str(line)
+                    .map_err(|error| format!("invalid history {}:{}: {}", path, i + 1, error))
+            })
+            .collect::<Result<_, _>>()
+            .map(History)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let mut content = Vec::new();
+        for entry in &self.0 {
+            serde_json::to_writer(&mut content, entry).map_err(|error| error.to_string())?;
+            writeln!(content).map_err(|error| error.to_string())?;
+        }
+
+        std::fs::write(path, content)
+            .map_err(|error| format!("cannot write history {}: {}", path, error))
+    }
+
+    /// The baseline to compare `entry` with: the most recent session whose
+    /// revision starts with `revision`, or else the most recent one of
+    /// another revision or label.
+    pub fn baseline(&self, entry: &Entry, revision: Option<&str>) -> Option<&Entry> {
+        self.0.iter().rev().find(|other| match revision {
+            Some(revision) => other.revision.starts_with(revision),
+            None => other.revision != entry.revision || other.label != entry.label,
+        })
+    }
+
+    /// Adds `entry`, replacing the session of the same revision and label.
+    pub fn push(&mut self, entry: Entry) {
+        self.0
+            .retain(|other| other.revision != entry.revision || other.label != entry.label);
+        self.0.push(entry);
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Change {
+    Slower,
+    Faster,
+    Unchanged,
+}
+
+/// Compares two samples with Welch's t-test at the 95% level; a change is
+/// reported only if it is significant and larger than `THRESHOLD`.
+pub fn compare(baseline: &Sample, current: &Sample) -> Change {
+    let (m1, m2) = (baseline.mean_ns as f64, current.mean_ns as f64);
+    if (m2 - m1).abs() <= m1 * THRESHOLD {
+        return Change::Unchanged;
+    }
+    if baseline.runs < 2 || current.runs < 2 {
+        return Change::Unchanged;
+    }
+
+    let variance = |sample: &Sample| (sample.std_dev_ns as f64).powi(2) / sample.runs as f64;
+    let (v1, v2) = (variance(baseline), variance(current));
+    if v1 + v2 == 0.0 {
+        return if m2 > m1 {
+            Change::Slower
+        } else {
+            Change::Faster
+        };
+    }
+
+    let t = (m2 - m1) / (v1 + v2).sqrt();
+    let df = (v1 + v2).powi(2)
+        / (v1.powi(2) / (baseline.runs - 1) as f64 + v2.powi(2) / (current.runs - 1) as f64);
+
+    if t.abs() <= critical_value(df) {
+        Change::Unchanged
+    } else if t > 0.0 {
+        Change::Slower
+    } else {
+        Change::Faster
+    }
+}
+
+/// Two-sided 95% critical value of Student's t distribution.
+fn critical_value(df: f64) -> f64 {
+    const TABLE: [f64; 30] = [
+        12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+        2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+        2.052, 2.048, 2.045, 2.042,
+    ];
+
+    match df.floor() as usize {
+        0 => TABLE[0],
+        df if df <= TABLE.len() => TABLE[df - 1],
+        _ => 1.960,
+    }
+}
+
+/// Prints how `entry` compares with `baseline` and returns whether any
+/// step got slower.
+pub fn print_comparison(entry: &Entry, baseline: Option<&Entry>) -> bool {
+    let ms = |ns: u64| format!("{:.3}ms", ns as f64 / 1_000_000.0);
+
+    match baseline {
+        Some(baseline) => println!("{} compared with {}\n", entry.name(), baseline.name()),
+        None => println!("{}: no baseline to compare with\n", entry.name()),
+    }
+
+    println!(
+        "{:>3}  {:>4}  {:<5}  {:>12}  {:>12}  {:>8}  change",
+        "day", "part", "step", "median", "baseline", ""
+    );
+
+    let mut slower = false;
+    for result in &entry.results {
+        let previous = baseline.and_then(|baseline| baseline.get(result.day, result.part));
+        let steps = [
+            (
+                "parse",
+                &result.parse,
+                previous.map(|previous| &previous.parse),
+            ),
+            (
+                "solve",
+                &result.solve,
+                previous.map(|previous| &previous.solve),
+            ),
+        ];
+
+        for (step, current, previous) in steps.iter() {
+            let (baseline, delta, change) = match previous {
+                Some(previous) => {
+                    let change = compare(previous, current);
+                    slower |= change == Change::Slower;
+
+                    (
+                        ms(previous.median_ns),
+                        format!(
+                            "{:+.1}%",
+                            (current.median_ns as f64 / previous.median_ns.max(1) as f64 - 1.0)
+                                * 100.0
+                        ),
+                        match change {
+                            Change::Slower => "SLOWER",
+                            Change::Faster => "faster",
+                            Change::Unchanged => "",
+                        },
+                    )
+                }
+                None => (String::new(), String::new(), "new"),
+            };
+
+            let line = format!(
+                "{:>3}  {:>4}  {:<5}  {:>12}  {:>12}  {:>8}  {}",
+                result.day,
+                result.part,
+                step,
+                ms(current.median_ns),
+                baseline,
+                delta,
+                change
+            );
+            println!("{}", line.trim_end());
+        }
+    }
+
+    slower
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(runs: usize, mean_ns: u64, std_dev_ns: u64) -> Sample {
+        Sample {
+            runs,
+            median_ns: mean_ns,
+            mean_ns,
+            std_dev_ns,
+            min_ns: mean_ns - std_dev_ns,
+            max_ns: mean_ns + std_dev_ns,
+        }
+    }
+
+    #[test]
+    fn compare_samples() {
+        let baseline = sample(20, 1_000_000, 10_000);
+
+        assert_eq!(
+            compare(&baseline, &sample(20, 1_100_000, 10_000)),
+            Change::Slower
+        );
+        assert_eq!(
+            compare(&baseline, &sample(20, 900_000, 10_000)),
+            Change::Faster
+        );
+        // significant, but below the threshold
+        assert_eq!(
+            compare(&baseline, &sample(20, 1_030_000, 10_000)),
+            Change::Unchanged
+        );
+        // above the threshold, but within the noise
+        assert_eq!(
+            compare(&baseline, &sample(3, 1_100_000, 500_000)),
+            Change::Unchanged
+        );
+    }
+
+    #[test]
+    fn critical_values() {
+        assert_eq!(critical_value(0.5), 12.706);
+        assert_eq!(critical_value(9.7), 2.262);
+        assert_eq!(critical_value(1000.0), 1.960);
+    }
+}
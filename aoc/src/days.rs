@@ -0,0 +1,79 @@
+use common::{Error, Picture, Rng, Run, Solution};
+use std::io::{BufRead, Write};
+use std::ops::RangeInclusive;
+
+pub type Runner = fn(&str, &[usize], usize) -> Result<Run, Error>;
+
+pub type Generator = fn(&mut Rng, usize) -> String;
+
+pub type Animator = fn(&str, usize, &mut dyn FnMut(usize, &str) -> bool) -> Result<bool, Error>;
+
+pub type Drawer = fn(&str, usize) -> Result<Option<Picture>, Error>;
+
+pub type Linter = fn(&str) -> Vec<Error>;
+
+pub type Repl = fn(&str, &mut dyn BufRead, &mut dyn Write) -> Result<(), Error>;
+
+pub struct Day {
+    pub day: u32,
+    pub input: &'static str,
+    pub run: Runner,
+    pub repl: Repl,
+    pub animate: Animator,
+    pub draw: Drawer,
+    pub lint: Linter,
+    pub generate: Generator,
+    /// The size of the generated inputs when none is given, about that of
+    /// the puzzle input.
+    pub size: usize,
+    /// The sizes the generator supports.
+    pub sizes: RangeInclusive<usize>,
+}
+
+macro_rules! day {
+    ($krate:ident :: $solution:ident, $size:expr) => {
+        day!($krate::$solution, $size, 1..=usize::MAX)
+    };
+    ($krate:ident :: $solution:ident, $size:expr, $sizes:expr) => {
+        Day {
+            day: <$krate::$solution as Solution>::DAY,
+            input: include_str!(concat!("../../", stringify!($krate), "/input")),
+            run: common::run::<$krate::$solution>,
+            repl: common::repl::<$krate::$solution>,
+            animate: common::animate::<$krate::$solution>,
+            draw: common::draw::<$krate::$solution>,
+            lint: common::lint::<$krate::$solution>,
+            generate: $krate::generate,
+            size: $size,
+            sizes: $sizes,
+        }
+    };
+}
+
+pub const DAYS: [Day; 25] = [
+    day!(day01::Day01, 200, day01::SIZES),
+    day!(day02::Day02, 1_000),
+    day!(day03::Day03, 323),
+    day!(day04::Day04, 290),
+    day!(day05::Day05, 800, day05::SIZES),
+    day!(day06::Day06, 490),
+    day!(day07::Day07, 594, day07::SIZES),
+    day!(day08::Day08, 620, day08::SIZES),
+    day!(day09::Day09, 1_000, day09::SIZES),
+    day!(day10::Day10, 100),
+    day!(day11::Day11, 90),
+    day!(day12::Day12, 780),
+    day!(day13::Day13, 80, day13::SIZES),
+    day!(day14::Day14, 560, day14::SIZES),
+    day!(day15::Day15, 7),
+    day!(day16::Day16, 240),
+    day!(day17::Day17, 8),
+    day!(day18::Day18, 370),
+    day!(day19::Day19, 400),
+    day!(day20::Day20, 12, day20::SIZES),
+    day!(day21::Day21, 40, day21::SIZES),
+    day!(day22::Day22, 25),
+    day!(day23::Day23, 9, day23::SIZES),
+    day!(day24::Day24, 450),
+    day!(day25::Day25, 10_000_000, day25::SIZES),
+];
@@ -0,0 +1,746 @@
+use common::{read_input, CountingAllocator, Error, Rng, Run};
+use std::time::{Duration, Instant};
+
+mod animate;
+mod answers;
+mod bench;
+mod days;
+mod output;
+mod pool;
+use answers::Answers;
+use bench::{Entry, History, Timing};
+use days::{Day, DAYS};
+use output::{Format, Row};
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const USAGE: &str =
+    "usage: aoc [verify|bench] [--day <n>|<from>-<to>]... [--part <1|2>] [--input <path>|-]
+           [--format <table|json|csv>] [--runs <n>] [--jobs <n>]
+       aoc --day <n> --animate [--part <1|2>] [--input <path>] [--fps <n>]
+       aoc --day <n> --part <1|2> --export <path> [--input <path>|-]
+       aoc generate --day <n> [--size <n>] [--seed <n>]
+       aoc repl --day <n> [--input <path>]
+       aoc lint [--day <n>|<from>-<to>]... [--input <path>|-]
+
+commands:
+  verify                     check the answers against the answers database
+                             instead of printing them
+  bench                      time the days over repeated runs, store the
+                             results in the benchmark history and flag the
+                             significant changes since the last revision
+  generate                   print a synthetic input for a day, to be read
+                             back with --input -
+  repl                       load the input of a day and explore it with
+                             commands read from stdin (type help for them)
+  lint                       check the inputs against the assumptions the
+                             solvers make without checking them (days 10, 13,
+                             14, 20) and print the violations with their lines
+
+options:
+  -d, --day <n>|<from>-<to>  run a single day or a range of days (default: all)
+  -p, --part <1|2>           run a single part (default: both)
+  -i, --input <path>|-       read the puzzle input from a file or stdin
+                             instead of the embedded one (single day only)
+  -f, --format <format>      print the answers as a table, JSON or CSV,
+                             with parse and solve times and the heap used
+                             (default: table)
+  -r, --runs <n>             repeat parsing and solving n times and report
+                             the median, min and max times
+                             (default: 1, 10 for bench)
+  -j, --jobs <n>             run up to n days at the same time
+                             (default: one per CPU, 1 for bench)
+      --animate              draw the generations of a cellular automaton day
+                             (11, 17, 24) in the terminal instead of solving it;
+                             enter pauses and resumes, s enter steps, q enter quits
+      --fps <n>              generations drawn per second (default: 10)
+      --export <path>        draw the state a part ends in (days 3, 12, 20, 24)
+                             to a .pbm, .ppm or .svg file instead of solving it
+      --answers <path>       answers database used by verify
+                             (default: answers.toml in the workspace root)
+      --record               store the missing answers while verifying
+      --history <path>       benchmark history used by bench
+                             (default: bench-history.jsonl in the workspace root)
+      --baseline <revision>  compare with this revision instead of the last one
+      --label <name>         tag the benchmark, e.g. with the features it was
+                             built with, to compare builds of one revision
+      --size <n>             size of the generated input, in the unit of the
+                             day: entries, lines, rows, tiles per side...
+                             (default: about that of the puzzle input)
+      --seed <n>             seed of the generated input (default: 0)
+  -h, --help                 print this help";
+
+#[derive(PartialEq)]
+enum Command {
+    Run,
+    Verify,
+    Bench,
+    Generate,
+    Repl,
+    Lint,
+}
+
+struct Options {
+    command: Command,
+    days: Vec<u32>,
+    parts: Vec<usize>,
+    input: Option<String>,
+    format: Format,
+    runs: usize,
+    jobs: usize,
+    answers: Option<String>,
+    record: bool,
+    history: Option<String>,
+    baseline: Option<String>,
+    label: Option<String>,
+    size: Option<usize>,
+    seed: u64,
+    animate: bool,
+    fps: f64,
+    export: Option<String>,
+}
+
+impl Options {
+    fn parse<I: Iterator<Item = String>>(args: I) -> Result<Option<Self>, String> {
+        let mut args = args.peekable();
+        let command = match args.peek().map(String::as_str) {
+            Some("verify") => Command::Verify,
+            Some("bench") => Command::Bench,
+            Some("generate") => Command::Generate,
+            Some("repl") => Command::Repl,
+            Some("lint") => Command::Lint,
+            _ => Command::Run,
+        };
+        if command != Command::Run {
+            args.next();
+        }
+        let (mut days, mut parts, mut input) = (Vec::new(), Vec::new(), None);
+        let (mut format, mut runs, mut jobs) = (Format::Table, None, None);
+        let (mut answers, mut record) = (None, false);
+        let (mut history, mut baseline, mut label) = (None, None, None);
+        let (mut size, mut seed) = (None, None);
+        let (mut animate, mut fps) = (false, None);
+        let mut export = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-d" | "--day" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("missing value for {}", arg))?;
+                    days.extend(parse_days(&value)?);
+                }
+                "-p" | "--part" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("missing value for {}", arg))?;
+                    match value.parse() {
+                        Ok(part) if part == 1 || part == 2 => parts.push(part),
+                        _ => return Err(format!("invalid part: {}", value)),
+                    }
+                }
+                "-i" | "--input" => {
+                    input = Some(
+                        args.next()
+                            .ok_or_else(|| format!("missing value for {}", arg))?,
+                    );
+                }
+                "-f" | "--format" => {
+                    format = args
+                        .next()
+                        .ok_or_else(|| format!("missing value for {}", arg))?
+                        .parse()?;
+                }
+                "-r" | "--runs" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("missing value for {}", arg))?;
+                    match value.parse() {
+                        Ok(value) if value > 0 => runs = Some(value),
+                        _ => return Err(format!("invalid runs: {}", value)),
+                    }
+                }
+                "-j" | "--jobs" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("missing value for {}", arg))?;
+                    match value.parse() {
+                        Ok(value) if value > 0 => jobs = Some(value),
+                        _ => return Err(format!("invalid jobs: {}", value)),
+                    }
+                }
+                "--answers" => {
+                    answers = Some(
+                        args.next()
+                            .ok_or_else(|| format!("missing value for {}", arg))?,
+                    );
+                }
+                "--record" => record = true,
+                "--history" | "--baseline" | "--label" => {
+                    let value = Some(
+                        args.next()
+                            .ok_or_else(|| format!("missing value for {}", arg))?,
+                    );
+                    match arg.as_str() {
+                        "--history" => history = value,
+                        "--baseline" => baseline = value,
+                        _ => label = value,
+                    }
+                }
+                "--size" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("missing value for {}", arg))?;
+                    match value.parse() {
+                        Ok(value) => size = Some(value),
+                        _ => return Err(format!("invalid size: {}", value)),
+                    }
+                }
+                "--seed" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("missing value for {}", arg))?;
+                    match value.parse() {
+                        Ok(value) => seed = Some(value),
+                        _ => return Err(format!("invalid seed: {}", value)),
+                    }
+                }
+                "--animate" => animate = true,
+                "--fps" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| format!("missing value for {}", arg))?;
+                    match value.parse::<f64>() {
+                        Ok(value) if value > 0.0 && value.is_finite() => fps = Some(value),
+                        _ => return Err(format!("invalid fps: {}", value)),
+                    }
+                }
+                "--export" => {
+                    export = Some(
+                        args.next()
+                            .ok_or_else(|| format!("missing value for {}", arg))?,
+                    );
+                }
+                "-h" | "--help" => return Ok(None),
+                _ => return Err(format!("unknown argument: {}", arg)),
+            }
+        }
+
+        if command == Command::Generate {
+            if days.len() != 1 {
+                return Err("generate requires a single --day".to_string());
+            }
+            if !parts.is_empty()
+                || input.is_some()
+                || format != Format::Table
+                || runs.is_some()
+                || jobs.is_some()
+            {
+                return Err(
+                    "--part, --input, --format, --runs and --jobs are not supported by generate"
+                        .to_string(),
+                );
+            }
+        } else if size.is_some() || seed.is_some() {
+            return Err("--size and --seed require generate".to_string());
+        }
+
+        if command == Command::Repl {
+            if days.len() != 1 {
+                return Err("repl requires a single --day".to_string());
+            }
+            if input.as_deref() == Some("-") {
+                return Err("repl reads its commands from stdin, not the input".to_string());
+            }
+            if !parts.is_empty() || format != Format::Table || runs.is_some() || jobs.is_some() {
+                return Err(
+                    "--part, --format, --runs and --jobs are not supported by repl".to_string(),
+                );
+            }
+        }
+
+        if command == Command::Lint
+            && (!parts.is_empty() || format != Format::Table || runs.is_some() || jobs.is_some())
+        {
+            return Err(
+                "--part, --format, --runs and --jobs are not supported by lint".to_string(),
+            );
+        }
+
+        if animate {
+            if command != Command::Run {
+                return Err("--animate is only supported when running days".to_string());
+            }
+            if days.len() != 1 {
+                return Err("--animate requires a single --day".to_string());
+            }
+            if input.as_deref() == Some("-") {
+                return Err("--animate reads its controls from stdin, not the input".to_string());
+            }
+            if format != Format::Table || runs.is_some() || jobs.is_some() {
+                return Err(
+                    "--format, --runs and --jobs are not supported by --animate".to_string()
+                );
+            }
+        } else if fps.is_some() {
+            return Err("--fps requires --animate".to_string());
+        }
+
+        if let Some(path) = &export {
+            if command != Command::Run || animate {
+                return Err("--export is only supported when running days".to_string());
+            }
+            if days.len() != 1 || parts.len() != 1 {
+                return Err("--export requires a single --day and a single --part".to_string());
+            }
+            if format != Format::Table || runs.is_some() || jobs.is_some() {
+                return Err("--format, --runs and --jobs are not supported by --export".to_string());
+            }
+            export_format(path)?;
+        }
+
+        if days.is_empty() {
+            days.extend(DAYS.iter().map(|day| day.day));
+        }
+        days.sort_unstable();
+        days.dedup();
+
+        if input.is_some() && days.len() != 1 {
+            return Err("--input requires a single --day".to_string());
+        }
+
+        if command != Command::Verify && (answers.is_some() || record) {
+            return Err("--answers and --record require verify".to_string());
+        }
+        if command == Command::Verify && (format != Format::Table || runs.is_some()) {
+            return Err("--format and --runs are not supported by verify".to_string());
+        }
+        if command != Command::Bench && (history.is_some() || baseline.is_some() || label.is_some())
+        {
+            return Err("--history, --baseline and --label require bench".to_string());
+        }
+        if command == Command::Bench && (format != Format::Table || jobs.is_some()) {
+            return Err("--format and --jobs are not supported by bench".to_string());
+        }
+        let runs = runs.unwrap_or(if command == Command::Bench { 10 } else { 1 });
+        let jobs = match (command == Command::Bench, jobs) {
+            (true, _) => 1,
+            (false, Some(jobs)) => jobs,
+            (false, None) => std::thread::available_parallelism().map_or(1, usize::from),
+        };
+
+        if parts.is_empty() {
+            parts.extend(&[1, 2]);
+        }
+        parts.sort_unstable();
+        parts.dedup();
+
+        Ok(Some(Options {
+            command,
+            days,
+            parts,
+            input,
+            format,
+            runs,
+            jobs,
+            answers,
+            record,
+            history,
+            baseline,
+            label,
+            size,
+            seed: seed.unwrap_or(0),
+            animate,
+            fps: fps.unwrap_or(10.0),
+            export,
+        }))
+    }
+}
+
+fn parse_days(value: &str) -> Result<Vec<u32>, String> {
+    let parse_day = |value: &str| match value.parse() {
+        Ok(day) if (1..=DAYS.len() as u32).contains(&day) => Ok(day),
+        _ => Err(format!("invalid day: {}", value)),
+    };
+
+    let mut parts = value.splitn(2, '-');
+    match (parts.next(), parts.next()) {
+        (Some(day), None) => Ok(vec![parse_day(day)?]),
+        (Some(from), Some(to)) => {
+            let (from, to) = (parse_day(from)?, parse_day(to)?);
+            if from > to {
+                Err(format!("invalid range: {}", value))
+            } else {
+                Ok((from..=to).collect())
+            }
+        }
+        _ => Err(format!("invalid day: {}", value)),
+    }
+}
+
+/// The picture format of an export, from the extension of its path.
+fn export_format(path: &str) -> Result<&str, String> {
+    match std::path::Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+    {
+        Some(format @ ("pbm" | "ppm" | "svg")) => Ok(format),
+        _ => Err(format!(
+            "cannot export to {}, expecting a .pbm, .ppm or .svg path",
+            path
+        )),
+    }
+}
+
+fn main() {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, USAGE);
+            std::process::exit(1);
+        }
+    };
+
+    let input = match options.input.as_deref().map(read_input).transpose() {
+        Ok(input) => input,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(1);
+        }
+    };
+
+    match options.command {
+        Command::Run if options.animate => {
+            for day in selected_days(&options) {
+                let input = input.as_deref().unwrap_or(day.input);
+                if let Err(error) = animate::play(day, input, &options.parts, options.fps) {
+                    eprintln!("{}", error);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Command::Run if options.export.is_some() => {
+            if let Err(error) = export(&options, input.as_deref()) {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        }
+        Command::Run => {
+            if let Err(error) = run(&options, input.as_deref()) {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        }
+        Command::Bench => match bench(&options, input.as_deref()) {
+            Ok(true) => {}
+            Ok(false) => std::process::exit(1),
+            Err(error) => {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        },
+        Command::Generate => {
+            if let Err(error) = generate(&options) {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        }
+        Command::Repl => {
+            if let Err(error) = repl(&options, input.as_deref()) {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        }
+        Command::Lint => {
+            if !lint(&options, input.as_deref()) {
+                std::process::exit(1);
+            }
+        }
+        Command::Verify => match verify(&options, input.as_deref()) {
+            Ok(true) => {}
+            Ok(false) => std::process::exit(1),
+            Err(error) => {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        },
+    }
+}
+
+fn selected_days(options: &Options) -> Vec<&'static Day> {
+    DAYS.iter()
+        .filter(|day| options.days.contains(&day.day))
+        .collect()
+}
+
+/// Runs `days` on the worker pool; a day that panics fails with the
+/// panic message, without stopping the others.
+fn run_days<F>(days: &[&'static Day], jobs: usize, f: F) -> Vec<Result<Run, Error>>
+where
+    F: Fn(&Day) -> Result<Run, Error> + Sync,
+{
+    pool::map(days, jobs, |day| f(day))
+        .into_iter()
+        .zip(days)
+        .map(|(run, day)| {
+            run.unwrap_or_else(|panic| {
+                Err(Error::new(format!("panicked: {}", panic)).in_day(day.day))
+            })
+        })
+        .collect()
+}
+
+fn run(options: &Options, input: Option<&str>) -> Result<(), String> {
+    let mut rows = Vec::new();
+    let mut total = Duration::default();
+
+    let days = selected_days(options);
+    let now = Instant::now();
+    let runs = run_days(&days, options.jobs, |day| {
+        (day.run)(input.unwrap_or(day.input), &options.parts, options.runs)
+    });
+    let wall_clock = now.elapsed();
+
+    for (day, run) in days.into_iter().zip(runs) {
+        let run = run.map_err(|error| error.to_string())?;
+
+        total += run.parse.median;
+        for part in run.parts {
+            let answer = match part.answer {
+                Ok(Some(answer)) => answer,
+                Ok(None) => continue,
+                Err(error) => return Err(error.to_string()),
+            };
+
+            total += part.elapsed.median;
+            rows.push(Row {
+                day: day.day,
+                part: part.part,
+                answer,
+                parse: run.parse,
+                solve: part.elapsed,
+                parse_memory: run.parse_memory,
+                solve_memory: part.memory,
+            });
+        }
+    }
+
+    output::print(options.format, &rows, total, wall_clock)
+}
+
+/// Checks every selected day and part against the answers database, which
+/// is keyed by the input, and returns whether none of them failed.
+fn verify(options: &Options, input: Option<&str>) -> Result<bool, String> {
+    let path = options.answers.as_deref().unwrap_or(answers::DEFAULT_PATH);
+    let mut answers = Answers::load(path)?;
+    let (mut passed, mut failed, mut missing) = (0, 0, 0);
+
+    let days = selected_days(options);
+    let runs = run_days(&days, options.jobs, |day| {
+        (day.run)(input.unwrap_or(day.input), &options.parts, 1)
+    });
+
+    println!("{:>3}  {:>4}  {:<8}  answer", "day", "part", "status");
+    for (day, run) in days.into_iter().zip(runs) {
+        let input = input.unwrap_or(day.input);
+        let results = match run {
+            Ok(run) => run
+                .parts
+                .into_iter()
+                .map(|part| (part.part, part.answer.map_err(|error| error.to_string())))
+                .collect(),
+            Err(error) => options
+                .parts
+                .iter()
+                .map(|&part| (part, Err(error.to_string())))
+                .collect::<Vec<_>>(),
+        };
+
+        for (part, result) in results {
+            let (status, answer) = match result {
+                Ok(None) => continue,
+                Ok(Some(answer)) => {
+                    let answer = answer.to_string();
+                    match answers.get(day.day, input, part) {
+                        Some(expected) if expected == answer => {
+                            passed += 1;
+                            ("ok", answer)
+                        }
+                        Some(expected) => {
+                            failed += 1;
+                            ("FAILED", format!("{} (expected {})", answer, expected))
+                        }
+                        None if options.record => {
+                            answers.insert(day.day, input, part, answer.clone());
+                            missing += 1;
+                            ("recorded", answer)
+                        }
+                        None => {
+                            missing += 1;
+                            ("missing", answer)
+                        }
+                    }
+                }
+                Err(error) => {
+                    failed += 1;
+                    ("FAILED", error)
+                }
+            };
+
+            println!("{:>3}  {:>4}  {:<8}  {}", day.day, part, status, answer);
+        }
+    }
+    println!(
+        "\n{} passed, {} failed, {} {}",
+        passed,
+        failed,
+        missing,
+        if options.record {
+            "recorded"
+        } else {
+            "missing"
+        }
+    );
+
+    if options.record && missing > 0 {
+        answers.save(path)?;
+    }
+
+    Ok(failed == 0)
+}
+
+/// Benchmarks the selected days and parts, stores the results in the
+/// history and returns whether none of them got significantly slower.
+fn bench(options: &Options, input: Option<&str>) -> Result<bool, String> {
+    let path = options.history.as_deref().unwrap_or(bench::DEFAULT_PATH);
+    let mut history = History::load(path)?;
+
+    let mut results = Vec::new();
+    for day in selected_days(options) {
+        let run = (day.run)(input.unwrap_or(day.input), &options.parts, options.runs)
+            .map_err(|error| error.to_string())?;
+
+        for part in run.parts {
+            match part.answer {
+                Ok(Some(_)) => {}
+                Ok(None) => continue,
+                Err(error) => return Err(error.to_string()),
+            }
+
+            results.push(Timing {
+                day: day.day,
+                part: part.part,
+                parse: run.parse.into(),
+                solve: part.elapsed.into(),
+            });
+        }
+    }
+
+    let entry = Entry {
+        revision: output::REVISION.to_string(),
+        label: options.label.clone(),
+        toolchain: output::TOOLCHAIN.to_string(),
+        results,
+    };
+    let baseline = history.baseline(&entry, options.baseline.as_deref());
+    if options.baseline.is_some() && baseline.is_none() {
+        return Err(format!(
+            "no benchmark of revision {} in {}",
+            options.baseline.as_deref().unwrap_or_default(),
+            path
+        ));
+    }
+
+    let slower = bench::print_comparison(&entry, baseline);
+
+    history.push(entry);
+    history.save(path)?;
+
+    Ok(!slower)
+}
+
+/// Prints the input generated for the selected day.
+fn generate(options: &Options) -> Result<(), String> {
+    for day in selected_days(options) {
+        let size = options.size.unwrap_or(day.size);
+        if !day.sizes.contains(&size) {
+            let sizes = match (*day.sizes.start(), *day.sizes.end()) {
+                (start, usize::MAX) => format!("at least {}", start),
+                (start, end) if start == end => start.to_string(),
+                (start, end) => format!("{} to {}", start, end),
+            };
+            return Err(format!(
+                "day {} generates inputs of size {}, not {}",
+                day.day, sizes, size
+            ));
+        }
+
+        print!("{}", (day.generate)(&mut Rng::new(options.seed), size));
+    }
+
+    Ok(())
+}
+
+/// Explores the selected day interactively, on stdin and stdout.
+fn repl(options: &Options, input: Option<&str>) -> Result<(), String> {
+    let (stdin, stdout) = (std::io::stdin(), std::io::stdout());
+
+    for day in selected_days(options) {
+        (day.repl)(
+            input.unwrap_or(day.input),
+            &mut stdin.lock(),
+            &mut stdout.lock(),
+        )
+        .map_err(|error| error.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Draws the state the selected part of the selected day ends in to the
+/// export path.
+fn export(options: &Options, input: Option<&str>) -> Result<(), String> {
+    let path = options.export.as_deref().unwrap_or_default();
+    let format = export_format(path)?;
+
+    for day in selected_days(options) {
+        for &part in &options.parts {
+            let picture = (day.draw)(input.unwrap_or(day.input), part)
+                .map_err(|error| error.to_string())?
+                .ok_or_else(|| format!("day {} has no picture to export", day.day))?;
+            let content = picture.encode(format).map_err(|error| error.to_string())?;
+
+            std::fs::write(path, content)
+                .map_err(|error| format!("cannot write {}: {}", path, error))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Lints the inputs of the selected days and returns whether none of them
+/// breaks an assumption.
+fn lint(options: &Options, input: Option<&str>) -> bool {
+    let days = selected_days(options);
+    let mut violations = 0;
+
+    for day in &days {
+        for error in (day.lint)(input.unwrap_or(day.input)) {
+            violations += 1;
+            println!("{}", error);
+        }
+    }
+    println!(
+        "{}days checked: {}, violations: {}",
+        if violations > 0 { "\n" } else { "" },
+        days.len(),
+        violations
+    );
+
+    violations == 0
+}
@@ -0,0 +1,235 @@
+use common::{Answer, Memory, Stats};
+use serde::Serialize;
+use std::time::Duration;
+
+pub const TOOLCHAIN: &str = env!("AOC_TOOLCHAIN");
+pub const REVISION: &str = env!("AOC_REVISION");
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("invalid format: {}", value)),
+        }
+    }
+}
+
+/// One part of one day; `parse` is the time spent parsing the day's input,
+/// shared by both its parts, and likewise for the heap used.
+pub struct Row {
+    pub day: u32,
+    pub part: usize,
+    pub answer: Answer,
+    pub parse: Stats,
+    pub solve: Stats,
+    pub parse_memory: Option<Memory>,
+    pub solve_memory: Option<Memory>,
+}
+
+/// A row as reported by JSON and CSV, with times in nanoseconds; the bare
+/// `parse_ns` and `solve_ns` are medians. The heap fields are missing when
+/// the allocations are not counted.
+#[derive(Serialize)]
+struct Record<'a> {
+    day: u32,
+    part: usize,
+    answer: &'a Answer,
+    runs: usize,
+    parse_ns: u64,
+    parse_min_ns: u64,
+    parse_max_ns: u64,
+    solve_ns: u64,
+    solve_min_ns: u64,
+    solve_max_ns: u64,
+    parse_peak_bytes: Option<usize>,
+    parse_allocations: Option<usize>,
+    solve_peak_bytes: Option<usize>,
+    solve_allocations: Option<usize>,
+}
+
+impl<'a> From<&'a Row> for Record<'a> {
+    fn from(row: &'a Row) -> Self {
+        let ns = |duration: Duration| duration.as_nanos() as u64;
+
+        Record {
+            day: row.day,
+            part: row.part,
+            answer: &row.answer,
+            runs: row.solve.runs,
+            parse_ns: ns(row.parse.median),
+            parse_min_ns: ns(row.parse.min),
+            parse_max_ns: ns(row.parse.max),
+            solve_ns: ns(row.solve.median),
+            solve_min_ns: ns(row.solve.min),
+            solve_max_ns: ns(row.solve.max),
+            parse_peak_bytes: row.parse_memory.map(|memory| memory.peak),
+            parse_allocations: row.parse_memory.map(|memory| memory.allocations),
+            solve_peak_bytes: row.solve_memory.map(|memory| memory.peak),
+            solve_allocations: row.solve_memory.map(|memory| memory.allocations),
+        }
+    }
+}
+
+/// `total` is the sum of the times of every row, `wall_clock` how long
+/// running all of them actually took, including repetitions and with days
+/// running concurrently.
+pub fn print(
+    format: Format,
+    rows: &[Row],
+    total: Duration,
+    wall_clock: Duration,
+) -> Result<(), String> {
+    match format {
+        Format::Table => {
+            print_table(rows, total, wall_clock);
+            Ok(())
+        }
+        Format::Json => print_json(rows, total, wall_clock),
+        Format::Csv => print_csv(rows),
+    }
+}
+
+fn print_table(rows: &[Row], total: Duration, wall_clock: Duration) {
+    let header = ["day", "part", "answer", "parse", "solve", "heap"].map(String::from);
+    let summary = |name: &str, duration: Duration| {
+        [
+            String::new(),
+            String::new(),
+            name.to_string(),
+            String::new(),
+            format!("{:.3}ms", duration.as_nanos() as f64 / 1_000_000.0),
+            String::new(),
+        ]
+    };
+
+    let mut lines = vec![header];
+    for (i, row) in rows.iter().enumerate() {
+        let parse = if i == 0 || rows[i - 1].day != row.day {
+            row.parse.to_string()
+        } else {
+            String::new()
+        };
+
+        lines.push([
+            row.day.to_string(),
+            row.part.to_string(),
+            row.answer.to_string(),
+            parse,
+            row.solve.to_string(),
+            row.solve_memory
+                .map_or_else(String::new, |memory| memory.to_string()),
+        ]);
+    }
+    lines.push(summary("total", total));
+    lines.push(summary("wall clock", wall_clock));
+
+    let mut widths = [0; 6];
+    for line in &lines {
+        for (width, cell) in widths.iter_mut().zip(line) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    for [day, part, answer, parse, solve, heap] in &lines {
+        let line = format!(
+            "{:>w0$}  {:>w1$}  {:<w2$}  {:>w3$}  {:>w4$}  {:>w5$}",
+            day,
+            part,
+            answer,
+            parse,
+            solve,
+            heap,
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+            w3 = widths[3],
+            w4 = widths[4],
+            w5 = widths[5],
+        );
+        println!("{}", line.trim_end());
+    }
+}
+
+fn print_json(rows: &[Row], total: Duration, wall_clock: Duration) -> Result<(), String> {
+    #[derive(Serialize)]
+    struct Report<'a> {
+        toolchain: &'a str,
+        revision: &'a str,
+        total_ns: u64,
+        wall_clock_ns: u64,
+        results: Vec<Record<'a>>,
+    }
+
+    let report = Report {
+        toolchain: TOOLCHAIN,
+        revision: REVISION,
+        total_ns: total.as_nanos() as u64,
+        wall_clock_ns: wall_clock.as_nanos() as u64,
+        results: rows.iter().map(Record::from).collect(),
+    };
+
+    serde_json::to_writer_pretty(std::io::stdout(), &report).map_err(|error| error.to_string())?;
+    println!();
+
+    Ok(())
+}
+
+fn print_csv(rows: &[Row]) -> Result<(), String> {
+    let mut writer = csv::Writer::from_writer(std::io::stdout());
+
+    writer
+        .write_record([
+            "toolchain",
+            "revision",
+            "day",
+            "part",
+            "answer",
+            "runs",
+            "parse_ns",
+            "parse_min_ns",
+            "parse_max_ns",
+            "solve_ns",
+            "solve_min_ns",
+            "solve_max_ns",
+            "parse_peak_bytes",
+            "parse_allocations",
+            "solve_peak_bytes",
+            "solve_allocations",
+        ])
+        .map_err(|error| error.to_string())?;
+    for record in rows.iter().map(Record::from) {
+        writer
+            .serialize((
+                TOOLCHAIN,
+                REVISION,
+                record.day,
+                record.part,
+                record.answer.to_string(),
+                record.runs,
+                record.parse_ns,
+                record.parse_min_ns,
+                record.parse_max_ns,
+                record.solve_ns,
+                record.solve_min_ns,
+                record.solve_max_ns,
+                record.parse_peak_bytes,
+                record.parse_allocations,
+                record.solve_peak_bytes,
+                record.solve_allocations,
+            ))
+            .map_err(|error| error.to_string())?;
+    }
+
+    writer.flush().map_err(|error| error.to_string())
+}
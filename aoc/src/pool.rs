@@ -0,0 +1,95 @@
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Applies `f` to every item on `jobs` threads, each taking the next item
+/// as soon as it is done with the previous one. The results are in the
+/// order of `items`, whatever the order in which they are computed; an item
+/// whose `f` panics gets the panic message instead, and the others go on.
+pub fn map<T, R, F>(items: &[T], jobs: usize, f: F) -> Vec<Result<R, String>>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let next = AtomicUsize::new(0);
+
+    let mut results = std::thread::scope(|scope| {
+        let workers = (0..jobs.clamp(1, items.len().max(1)))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        match items.get(i) {
+                            Some(item) => results.push((
+                                i,
+                                panic::catch_unwind(AssertUnwindSafe(|| f(item)))
+                                    .map_err(|payload| message(payload.as_ref())),
+                            )),
+                            None => return results,
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("worker panicked"))
+            .collect::<Vec<_>>()
+    });
+
+    results.sort_unstable_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// The message a panic was raised with.
+fn message(payload: &(dyn Any + Send)) -> String {
+    match (
+        payload.downcast_ref::<&str>(),
+        payload.downcast_ref::<String>(),
+    ) {
+        (Some(message), _) => message.to_string(),
+        (_, Some(message)) => message.clone(),
+        _ => "panicked".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ordered_results() {
+        let items = (0..100u64).collect::<Vec<_>>();
+
+        let results = map(&items, 8, |&i| {
+            std::thread::sleep(std::time::Duration::from_micros((100 - i) * 10));
+            i * i
+        });
+
+        assert_eq!(results, items.iter().map(|i| Ok(i * i)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn panicking_item() {
+        let items = (0..10u64).collect::<Vec<_>>();
+
+        let results = map(&items, 3, |&i| {
+            if i == 4 {
+                panic!("no {}", i);
+            }
+            i
+        });
+
+        assert_eq!(results[4], Err("no 4".to_string()));
+        assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 9);
+        assert_eq!(results[9], Ok(9));
+    }
+
+    #[test]
+    fn no_items() {
+        assert!(map(&[] as &[u32], 4, |&i| i).is_empty());
+    }
+}
@@ -0,0 +1,37 @@
+// The machine-readable formats are all that a run prints to stdout, so that
+// nothing a day writes gets in the way of parsing them.
+
+use std::process::Command;
+
+fn run(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_aoc"))
+        .args(args)
+        .output()
+        .expect("cannot run aoc");
+    assert!(output.status.success(), "aoc {:?} failed", args);
+
+    String::from_utf8(output.stdout).expect("stdout is not UTF-8")
+}
+
+#[test]
+fn day_20_json() {
+    let stdout = run(&["--day", "20", "--format", "json"]);
+    let report = serde_json::from_str::<serde_json::Value>(&stdout).expect("invalid JSON");
+
+    let results = report["results"].as_array().expect("no results");
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|result| result["day"] == 20));
+}
+
+#[test]
+fn day_20_csv() {
+    let stdout = run(&["--day", "20", "--format", "csv"]);
+    let mut reader = csv::Reader::from_reader(stdout.as_bytes());
+
+    let records = reader
+        .records()
+        .collect::<Result<Vec<_>, _>>()
+        .expect("invalid CSV");
+    assert_eq!(records.len(), 2);
+    assert!(records.iter().all(|record| &record[2] == "20"));
+}
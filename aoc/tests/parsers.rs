@@ -0,0 +1,98 @@
+// No input makes a parser panic: random edits of the puzzle inputs, and
+// random text, only ever parse or fail with an error.
+
+use common::Solution;
+use proptest::prelude::*;
+
+/// Chars that the parsers look for, and some that they do not expect.
+const CHARS: &str = "0123456789abcxyzXL.#-+*:,|()\"[]= \n\té€";
+
+#[derive(Debug, Clone)]
+enum Edit {
+    Delete(prop::sample::Index, usize),
+    Insert(prop::sample::Index, char),
+    Truncate(prop::sample::Index),
+}
+
+fn edit() -> impl Strategy<Value = Edit> {
+    let chars = CHARS.chars().collect::<Vec<_>>();
+
+    prop_oneof![
+        (any::<prop::sample::Index>(), 1..10usize).prop_map(|(at, n)| Edit::Delete(at, n)),
+        (any::<prop::sample::Index>(), prop::sample::select(chars))
+            .prop_map(|(at, c)| Edit::Insert(at, c)),
+        any::<prop::sample::Index>().prop_map(Edit::Truncate),
+    ]
+}
+
+/// Applies `edits` to `input`, keeping to char boundaries.
+fn apply(input: &str, edits: &[Edit]) -> String {
+    let mut input = input.chars().collect::<Vec<_>>();
+
+    for edit in edits {
+        match edit {
+            Edit::Delete(at, n) if !input.is_empty() => {
+                let at = at.index(input.len());
+                input.drain(at..(at + n).min(input.len()));
+            }
+            Edit::Insert(at, c) => {
+                let at = at.index(input.len() + 1);
+                input.insert(at, *c);
+            }
+            Edit::Truncate(at) if !input.is_empty() => {
+                input.truncate(at.index(input.len()));
+            }
+            _ => {}
+        }
+    }
+
+    input.into_iter().collect()
+}
+
+macro_rules! parser {
+    ($name:ident, $krate:ident :: $solution:ident) => {
+        mod $name {
+            use super::*;
+
+            const INPUT: &str = include_str!(concat!("../../", stringify!($krate), "/input"));
+
+            proptest! {
+                #[test]
+                fn edited_input(edits in prop::collection::vec(edit(), 1..8)) {
+                    let _ = <::$krate::$solution as Solution>::parse(&apply(INPUT, &edits));
+                }
+
+                #[test]
+                fn random_text(input in "(?s).{0,64}") {
+                    let _ = <::$krate::$solution as Solution>::parse(&input);
+                }
+            }
+        }
+    };
+}
+
+parser!(day01, day01::Day01);
+parser!(day02, day02::Day02);
+parser!(day03, day03::Day03);
+parser!(day04, day04::Day04);
+parser!(day05, day05::Day05);
+parser!(day06, day06::Day06);
+parser!(day07, day07::Day07);
+parser!(day08, day08::Day08);
+parser!(day09, day09::Day09);
+parser!(day10, day10::Day10);
+parser!(day11, day11::Day11);
+parser!(day12, day12::Day12);
+parser!(day13, day13::Day13);
+parser!(day14, day14::Day14);
+parser!(day15, day15::Day15);
+parser!(day16, day16::Day16);
+parser!(day17, day17::Day17);
+parser!(day18, day18::Day18);
+parser!(day19, day19::Day19);
+parser!(day20, day20::Day20);
+parser!(day21, day21::Day21);
+parser!(day22, day22::Day22);
+parser!(day23, day23::Day23);
+parser!(day24, day24::Day24);
+parser!(day25, day25::Day25);
@@ -0,0 +1,173 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// The system allocator, counting the allocations of each thread and how
+/// much heap it has in use, so that `run` can report the memory of a day
+/// even with other days running on other threads. A binary opts in with
+///
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOCATOR: common::CountingAllocator = common::CountingAllocator;
+/// ```
+pub struct CountingAllocator;
+
+/// Set by the first allocation through `CountingAllocator`.
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    // signed, as a thread may free what another one allocated
+    static IN_USE: Cell<isize> = const { Cell::new(0) };
+    static PEAK: Cell<isize> = const { Cell::new(0) };
+}
+
+/// Counts an allocation (if `allocation`) and a change of `delta` bytes in
+/// use; a no-op while the thread is being torn down.
+fn record(delta: isize, allocation: bool) {
+    let _ = IN_USE.try_with(|in_use| {
+        let value = in_use.get() + delta;
+        in_use.set(value);
+
+        let _ = PEAK.try_with(|peak| peak.set(peak.get().max(value)));
+        if allocation {
+            let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        }
+    });
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            INSTALLED.store(true, Ordering::Relaxed);
+            record(layout.size() as isize, true);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            INSTALLED.store(true, Ordering::Relaxed);
+            record(layout.size() as isize, true);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        record(-(layout.size() as isize), false);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            record(new_size as isize - layout.size() as isize, true);
+        }
+        new_ptr
+    }
+}
+
+/// Heap used by a step, on the thread that ran it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Memory {
+    /// Allocations and reallocations.
+    pub allocations: usize,
+    /// The most bytes in use at once on top of those in use before the
+    /// step; what the step kept allocated when it ended counts.
+    pub peak: usize,
+}
+
+impl Memory {
+    /// The larger of both counts.
+    pub fn max(self, other: Self) -> Self {
+        Self {
+            allocations: self.allocations.max(other.allocations),
+            peak: self.peak.max(other.peak),
+        }
+    }
+}
+
+/// In binary units, with the allocations.
+impl std::fmt::Display for Memory {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+        let mut size = self.peak as f64;
+        let mut unit = 0;
+        while size >= 1024.0 && unit < UNITS.len() - 1 {
+            size /= 1024.0;
+            unit += 1;
+        }
+
+        if unit == 0 {
+            write!(fmt, "{}B", self.peak)?;
+        } else {
+            write!(fmt, "{:.1}{}", size, UNITS[unit])?;
+        }
+        write!(fmt, " ({} allocs)", self.allocations)
+    }
+}
+
+/// Runs `f` and measures the heap it uses; `None` when `CountingAllocator`
+/// is not the global allocator. Measurements must not be nested.
+pub fn measure<T, F: FnOnce() -> T>(f: F) -> (T, Option<Memory>) {
+    let allocations = ALLOCATIONS.with(Cell::get);
+    let in_use = IN_USE.with(Cell::get);
+    PEAK.with(|peak| peak.set(in_use));
+
+    let value = f();
+
+    if !INSTALLED.load(Ordering::Relaxed) {
+        return (value, None);
+    }
+
+    let memory = Memory {
+        allocations: ALLOCATIONS.with(Cell::get) - allocations,
+        peak: (PEAK.with(Cell::get) - in_use).max(0) as usize,
+    };
+
+    (value, Some(memory))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let memory = |peak| Memory {
+            allocations: 3,
+            peak,
+        };
+
+        assert_eq!(memory(512).to_string(), "512B (3 allocs)");
+        assert_eq!(memory(1536).to_string(), "1.5KiB (3 allocs)");
+        assert_eq!(memory(3 << 20).to_string(), "3.0MiB (3 allocs)");
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    #[test]
+    fn measure_step() {
+        let kept = vec![0u8; 4096];
+
+        let (value, memory) = measure(|| {
+            let mut v = Vec::new();
+            for i in 0..100_000u32 {
+                v.push(i);
+            }
+            let dropped = vec![0u8; 1 << 20];
+            v.len() + dropped.len()
+        });
+
+        let memory = memory.expect("not installed");
+        assert_eq!(value, 100_000 + (1 << 20));
+        assert!(memory.peak >= 400_000 + (1 << 20));
+        assert!(memory.peak < 800_000 + (1 << 20) + kept.len());
+        assert!(memory.allocations > 2);
+        assert!(memory.allocations < 100);
+    }
+}
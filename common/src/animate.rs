@@ -0,0 +1,24 @@
+use crate::{Error, Solution};
+
+/// Parses `input` and passes the generations of `part` to `show`, with
+/// their number, until they end or `show` returns false. Returns whether
+/// the part has generations at all.
+pub fn animate<S: Solution>(
+    input: &str,
+    part: usize,
+    show: &mut dyn FnMut(usize, &str) -> bool,
+) -> Result<bool, Error> {
+    let input = S::parse(input)?;
+
+    match S::generations(&input, part) {
+        Some(generations) => {
+            for (i, frame) in generations.enumerate() {
+                if !show(i, &frame) {
+                    break;
+                }
+            }
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
@@ -0,0 +1,65 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(untagged)]
+pub enum Answer {
+    Signed(i128),
+    Unsigned(u128),
+    Text(String),
+}
+
+macro_rules! from {
+    ($variant:ident, $target:ty, $($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(value: $t) -> Self {
+                    Answer::$variant(value as $target)
+                }
+            }
+        )*
+    };
+}
+
+from!(Signed, i128, i32, i64, i128);
+from!(Unsigned, u128, u32, u64, u128, usize);
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Answer::Text(value)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(value: &str) -> Self {
+        Answer::Text(value.to_string())
+    }
+}
+
+impl std::fmt::Display for Answer {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Answer::Signed(value) => value.fmt(fmt),
+            Answer::Unsigned(value) => value.fmt(fmt),
+            Answer::Text(value) => value.fmt(fmt),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        assert_eq!(Answer::from(-42i32).to_string(), "-42");
+        assert_eq!(Answer::from(u128::MAX).to_string(), u128::MAX.to_string());
+        assert_eq!(Answer::from("mxmxvkd,sqjhc").to_string(), "mxmxvkd,sqjhc");
+    }
+
+    #[test]
+    fn from_integers() {
+        assert_eq!(Answer::from(42usize), Answer::Unsigned(42));
+        assert_eq!(Answer::from(42u32), Answer::Unsigned(42));
+        assert_eq!(Answer::from(42i128), Answer::Signed(42));
+    }
+}
@@ -0,0 +1,272 @@
+use std::iter::FromIterator;
+
+const BITS: usize = u64::BITS as usize;
+
+/// A set of small integers, one bit each, packed in `u64` words.
+///
+/// It holds the indices `0..len()`; setting a bit beyond that grows it. Two
+/// sets are equal when the same bits are set, whatever their lengths.
+#[derive(Clone, Default)]
+pub struct BitSet {
+    // the bits from `len` on in the last word are always clear
+    data: Vec<u64>,
+    len: usize,
+}
+
+impl BitSet {
+    /// An empty set of `len` bits.
+    pub fn new(len: usize) -> Self {
+        Self {
+            data: vec![0; words(len)],
+            len,
+        }
+    }
+
+    /// The number of bits, set or not.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of bits set.
+    pub fn count_ones(&self) -> usize {
+        self.data
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Whether bit `index` is set; the bits beyond `len()` never are.
+    pub fn get(&self, index: usize) -> bool {
+        index < self.len && self.data[index / BITS] & 1 << (index % BITS) != 0
+    }
+
+    /// Sets or clears bit `index`, growing the set to hold it if needed.
+    pub fn set(&mut self, index: usize, b: bool) {
+        if index >= self.len {
+            if !b {
+                return;
+            }
+            self.grow(index + 1);
+        }
+
+        let word = &mut self.data[index / BITS];
+        if b {
+            *word |= 1 << (index % BITS);
+        } else {
+            *word &= !(1 << (index % BITS));
+        }
+    }
+
+    /// Makes room for `len` bits, clear; does nothing if it already has.
+    pub fn grow(&mut self, len: usize) {
+        if len > self.len {
+            self.data.resize(words(len), 0);
+            self.len = len;
+        }
+    }
+
+    /// Clears every bit, keeping the length.
+    pub fn clear(&mut self) {
+        self.data.iter_mut().for_each(|word| *word = 0);
+    }
+
+    /// The indices of the bits set, in increasing order.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            data: &self.data,
+            offset: 0,
+            word: self.data.first().copied().unwrap_or(0),
+        }
+    }
+
+    /// The bits set in either set, as long as the longer one.
+    pub fn union(&self, other: &Self) -> Self {
+        let (mut result, shorter) = if self.len >= other.len {
+            (self.clone(), other)
+        } else {
+            (other.clone(), self)
+        };
+        for (word, other) in result.data.iter_mut().zip(&shorter.data) {
+            *word |= other;
+        }
+        result
+    }
+
+    /// The bits set in both sets, as long as `self`.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        for (i, word) in result.data.iter_mut().enumerate() {
+            *word &= other.data.get(i).copied().unwrap_or(0);
+        }
+        result
+    }
+
+    /// The bits set in `self` but not in `other`, as long as `self`.
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        for (word, other) in result.data.iter_mut().zip(&other.data) {
+            *word &= !other;
+        }
+        result
+    }
+}
+
+fn words(len: usize) -> usize {
+    len.div_ceil(BITS)
+}
+
+impl PartialEq for BitSet {
+    fn eq(&self, other: &Self) -> bool {
+        let (longer, shorter) = if self.data.len() >= other.data.len() {
+            (&self.data, &other.data)
+        } else {
+            (&other.data, &self.data)
+        };
+
+        longer[..shorter.len()] == shorter[..] && longer[shorter.len()..].iter().all(|&w| w == 0)
+    }
+}
+
+impl Eq for BitSet {}
+
+/// As the set of the indices of the bits set.
+impl std::fmt::Debug for BitSet {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// Iterator over the indices of the bits set in a `BitSet`.
+pub struct Iter<'a> {
+    data: &'a [u64],
+    // the index of the first bit of `word`
+    offset: usize,
+    // what is left to visit of the current word
+    word: u64,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.word == 0 {
+            self.data = self.data.get(1..).filter(|data| !data.is_empty())?;
+            self.offset += BITS;
+            self.word = self.data[0];
+        }
+
+        let bit = self.word.trailing_zeros() as usize;
+        self.word &= self.word - 1;
+        Some(self.offset + bit)
+    }
+}
+
+impl<'a> IntoIterator for &'a BitSet {
+    type Item = usize;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl Extend<usize> for BitSet {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|index| self.set(index, true));
+    }
+}
+
+impl FromIterator<usize> for BitSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::default();
+        set.extend(iter);
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set() {
+        let mut set = BitSet::new(1024);
+        set.set(129, true);
+
+        assert!(set.get(129));
+        assert!(!set.get(128));
+        assert!(!set.get(130));
+
+        set.set(129, false);
+        assert!(!set.get(129));
+    }
+
+    #[test]
+    fn get() {
+        let set = BitSet::new(1024);
+        assert!(!set.get(129));
+        assert!(!set.get(4096));
+    }
+
+    #[test]
+    fn packed() {
+        let set = BitSet::new(1024);
+        assert_eq!(set.data.len(), 16);
+        assert_eq!(BitSet::new(65).data.len(), 2);
+        assert_eq!(BitSet::new(0).data.len(), 0);
+    }
+
+    #[test]
+    fn grow() {
+        let mut set = BitSet::new(10);
+        set.set(200, true);
+        assert_eq!(set.len(), 201);
+        assert!(set.get(200));
+
+        set.set(1000, false);
+        assert_eq!(set.len(), 201);
+
+        set.clear();
+        assert_eq!(set.len(), 201);
+        assert_eq!(set.count_ones(), 0);
+    }
+
+    #[test]
+    fn iter() {
+        let indices = vec![0, 3, 63, 64, 127, 500];
+        let set = indices.iter().copied().collect::<BitSet>();
+
+        assert_eq!(set.len(), 501);
+        assert_eq!(set.count_ones(), indices.len());
+        assert_eq!(set.iter().collect::<Vec<_>>(), indices);
+        assert_eq!(BitSet::new(300).iter().next(), None);
+        assert_eq!(format!("{:?}", set), "{0, 3, 63, 64, 127, 500}");
+    }
+
+    #[test]
+    fn operations() {
+        let a = vec![1, 2, 70, 200].into_iter().collect::<BitSet>();
+        let b = vec![2, 3, 70].into_iter().collect::<BitSet>();
+
+        let set = |indices: &[usize]| indices.iter().copied().collect::<BitSet>();
+        assert_eq!(a.union(&b), set(&[1, 2, 3, 70, 200]));
+        assert_eq!(b.union(&a), set(&[1, 2, 3, 70, 200]));
+        assert_eq!(a.intersection(&b), set(&[2, 70]));
+        assert_eq!(b.intersection(&a), set(&[2, 70]));
+        assert_eq!(a.difference(&b), set(&[1, 200]));
+        assert_eq!(b.difference(&a), set(&[3]));
+    }
+
+    #[test]
+    fn equality() {
+        let mut a = BitSet::new(1000);
+        a.set(5, true);
+        assert_eq!(a, vec![5].into_iter().collect());
+        assert_ne!(a, BitSet::new(1000));
+        assert_eq!(BitSet::new(0), BitSet::new(1000));
+    }
+}
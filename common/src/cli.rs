@@ -0,0 +1,58 @@
+use crate::{run, Solution};
+use std::io::Read;
+
+/// Reads a puzzle input from `path`, or from stdin if `path` is `-`.
+pub fn read_input(path: &str) -> Result<String, String> {
+    let mut input = String::new();
+    let result = if path == "-" {
+        std::io::stdin().read_to_string(&mut input).map(|_| input)
+    } else {
+        std::fs::read_to_string(path)
+    };
+
+    result.map_err(|error| format!("cannot read input {}: {}", path, error))
+}
+
+fn exit(message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
+
+/// The `main` of every day binary: solves `input`, or the one given with
+/// `--input`, and prints the answers followed by the time spent parsing and
+/// solving each part, repeated `--runs` times.
+pub fn main<S: Solution>(input: &str) {
+    let usage = format!("usage: day{:02} [--input <path>|-] [--runs <n>]", S::DAY);
+
+    let (mut path, mut runs) = (None, 1);
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--input", Some(value)) => path = Some(value),
+            ("--runs", Some(value)) => match value.parse() {
+                Ok(value) if value > 0 => runs = value,
+                _ => exit(&format!("invalid runs: {}\n\n{}", value, usage)),
+            },
+            _ => exit(&usage),
+        }
+    }
+
+    let owned = path.map(|path| read_input(&path).unwrap_or_else(|error| exit(&error)));
+    let input = owned.as_deref().unwrap_or(input);
+
+    let run = run::<S>(input, &[1, 2], runs).unwrap_or_else(|error| exit(&error.to_string()));
+
+    let mut elapsed = vec![format!("parse elapsed: {}", run.parse)];
+    for part in run.parts {
+        match part.answer {
+            Ok(Some(answer)) => println!("part {}: {}", part.part, answer),
+            Ok(None) => continue,
+            Err(error) => exit(&error.to_string()),
+        }
+        elapsed.push(format!("part {} elapsed: {}", part.part, part.elapsed));
+    }
+
+    for line in elapsed {
+        println!("{}", line);
+    }
+}
@@ -0,0 +1,162 @@
+/// An invalid puzzle input (or one a solver cannot handle), with enough
+/// context to point at the offending text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub day: Option<u32>,
+    /// 1-based line and column of `snippet` in the puzzle input.
+    pub position: Option<(usize, usize)>,
+    pub snippet: String,
+    pub message: String,
+}
+
+impl Error {
+    pub fn new<M: Into<String>>(message: M) -> Self {
+        Self {
+            day: None,
+            position: None,
+            snippet: String::new(),
+            message: message.into(),
+        }
+    }
+
+    /// Error about `snippet`, which must be a slice of `input`; the position
+    /// is relative to `input`.
+    pub fn at<M: Into<String>>(input: &str, snippet: &str, message: M) -> Self {
+        Self {
+            day: None,
+            position: locate(input, snippet),
+            snippet: snippet.to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// Moves an error raised while parsing `part`, a slice of `input`, so
+    /// that its position is relative to `input`.
+    pub fn within(mut self, input: &str, part: &str) -> Self {
+        self.position = match (self.position, locate(input, part)) {
+            (Some((1, column)), Some((line, offset))) => Some((line, offset + column - 1)),
+            (Some((line, column)), Some((offset, _))) => Some((offset + line - 1, column)),
+            (position, _) => position,
+        };
+        self
+    }
+
+    pub fn in_day(mut self, day: u32) -> Self {
+        self.day = Some(day);
+        self
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        self.position.map(|(line, _)| line)
+    }
+
+    pub fn column(&self) -> Option<usize> {
+        self.position.map(|(_, column)| column)
+    }
+}
+
+/// 1-based line and column where `snippet` starts, if it is a slice of `input`.
+fn locate(input: &str, snippet: &str) -> Option<(usize, usize)> {
+    let start = input.as_ptr() as usize;
+    let offset = (snippet.as_ptr() as usize).checked_sub(start)?;
+    if offset > input.len() {
+        return None;
+    }
+
+    let before = &input[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rfind('\n')
+        .map_or(before, |i| &before[i + 1..])
+        .chars()
+        .count()
+        + 1;
+
+    Some((line, column))
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(day) = self.day {
+            write!(fmt, "day {}: ", day)?;
+        }
+        if let Some((line, column)) = self.position {
+            write!(fmt, "line {}, column {}: ", line, column)?;
+        }
+        fmt.write_str(&self.message)?;
+        if !self.snippet.is_empty() {
+            write!(fmt, ": {:?}", self.snippet)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn at_first_line() {
+        let input = "nop +0\nacc x1";
+        let error = Error::at(input, &input[4..6], "invalid number");
+
+        assert_eq!(error.position, Some((1, 5)));
+        assert_eq!(error.snippet, "+0");
+    }
+
+    #[test]
+    fn at_other_line() {
+        let input = "nop +0\nacc x1";
+        let error = Error::at(input, &input[11..], "invalid number");
+
+        assert_eq!(error.position, Some((2, 5)));
+    }
+
+    #[test]
+    fn at_unrelated_snippet() {
+        assert_eq!(
+            Error::at("abc", "abc".to_string().as_str(), "x").position,
+            None
+        );
+    }
+
+    #[test]
+    fn within() {
+        let input = "rules\n\nnop +0\nacc x1";
+        let part = &input[7..];
+        let line = &part[7..];
+
+        let error = Error::at(line, &line[4..], "invalid number");
+        assert_eq!(error.position, Some((1, 5)));
+
+        let error = error.within(part, line);
+        assert_eq!(error.position, Some((2, 5)));
+
+        let error = error.within(input, part);
+        assert_eq!(error.position, Some((4, 5)));
+    }
+
+    #[test]
+    fn within_same_line() {
+        let input = "mem[8] = x";
+        let value = &input[9..];
+
+        let error = Error::at(value, value, "invalid number").within(input, value);
+        assert_eq!(error.position, Some((1, 10)));
+    }
+
+    #[test]
+    fn display() {
+        let input = "nop +0\nfoo +1";
+        let error = Error::at(input, &input[7..10], "invalid opcode").in_day(8);
+
+        assert_eq!(
+            error.to_string(),
+            "day 8: line 2, column 1: invalid opcode: \"foo\""
+        );
+        assert_eq!(Error::new("not found").to_string(), "not found");
+    }
+}
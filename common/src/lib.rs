@@ -0,0 +1,27 @@
+mod alloc;
+mod animate;
+mod answer;
+mod bit_set;
+mod cli;
+mod error;
+mod lint;
+mod picture;
+mod repl;
+mod rng;
+mod run;
+mod solution;
+
+pub mod num;
+
+pub use alloc::{measure, CountingAllocator, Memory};
+pub use animate::animate;
+pub use answer::Answer;
+pub use bit_set::BitSet;
+pub use cli::{main, read_input};
+pub use error::Error;
+pub use lint::lint;
+pub use picture::{draw, Picture, Raster, Rgb, Svg, BLACK, WHITE};
+pub use repl::repl;
+pub use rng::Rng;
+pub use run::{run, PartRun, Run, Stats};
+pub use solution::Solution;
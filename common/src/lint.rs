@@ -0,0 +1,75 @@
+use crate::{Error, Solution};
+
+/// Parses `input` and checks it against the assumptions the solvers of the
+/// day rely on without checking them; an input that does not parse has its
+/// parse error as the only violation.
+pub fn lint<S: Solution>(input: &str) -> Vec<Error> {
+    match S::parse(input) {
+        Ok(parsed) => S::lint(input, &parsed)
+            .into_iter()
+            .map(|error| error.in_day(S::DAY))
+            .collect(),
+        Err(error) => vec![error],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Answer;
+
+    struct Numbers;
+
+    impl Solution for Numbers {
+        const DAY: u32 = 1;
+
+        type Input<'a> = Vec<u64>;
+
+        fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+            input
+                .lines()
+                .map(|line| {
+                    line.parse()
+                        .map_err(|_| Error::at(input, line, "invalid number").in_day(Self::DAY))
+                })
+                .collect()
+        }
+
+        fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+            Ok(input.iter().sum::<u64>().into())
+        }
+
+        fn part_2(_input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+            Ok(None)
+        }
+
+        fn lint(text: &str, _input: &Self::Input<'_>) -> Vec<Error> {
+            text.lines()
+                .filter(|line| line.starts_with('0'))
+                .map(|line| Error::at(text, line, "leading zero"))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn violations() {
+        let errors = lint::<Numbers>("1\n02\n3\n04");
+
+        assert_eq!(
+            errors.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "day 1: line 2, column 1: leading zero: \"02\"",
+                "day 1: line 4, column 1: leading zero: \"04\"",
+            ]
+        );
+        assert!(lint::<Numbers>("1\n2").is_empty());
+    }
+
+    #[test]
+    fn parse_error() {
+        let errors = lint::<Numbers>("1\nx");
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line(), Some(2));
+    }
+}
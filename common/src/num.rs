@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+/// Extended Euclid: `(g, x, y)` with `g = gcd(a, b) = a * x + b * y`.
+#[allow(clippy::many_single_char_names)]
+pub fn gcd_ex(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_s, mut s) = (1, 0);
+    let (mut old_t, mut t) = (0, 1);
+
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_s, s) = (s, old_s - q * s);
+        (old_t, t) = (t, old_t - q * t);
+    }
+
+    if old_r < 0 {
+        (-old_r, -old_s, -old_t)
+    } else {
+        (old_r, old_s, old_t)
+    }
+}
+
+/// `a * b mod m`, in `0..m`, without overflowing for any `m > 0`.
+pub fn mod_mul(a: i128, b: i128, m: i128) -> i128 {
+    let (mut a, mut b) = (a.rem_euclid(m), b.rem_euclid(m));
+    if let Some(product) = a.checked_mul(b) {
+        return product % m;
+    }
+
+    let mut result = 0;
+    while b > 0 {
+        if b & 1 == 1 {
+            result = mod_add(result, a, m);
+        }
+        a = mod_add(a, a, m);
+        b >>= 1;
+    }
+
+    result
+}
+
+/// `a + b mod m` for `a` and `b` in `0..m`.
+fn mod_add(a: i128, b: i128, m: i128) -> i128 {
+    if a >= m - b {
+        a - (m - b)
+    } else {
+        a + b
+    }
+}
+
+/// `base ^ exp mod m`, in `0..m`, by squaring.
+pub fn mod_pow(base: i128, mut exp: u128, m: i128) -> i128 {
+    let mut base = base.rem_euclid(m);
+    let mut result = 1 % m;
+
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mod_mul(result, base, m);
+        }
+        base = mod_mul(base, base, m);
+        exp >>= 1;
+    }
+
+    result
+}
+
+/// The inverse of `a` modulo `m`, if they are coprime.
+pub fn mod_inv(a: i128, m: i128) -> Option<i128> {
+    match gcd_ex(a.rem_euclid(m), m) {
+        (1, x, _) => Some(x.rem_euclid(m)),
+        _ => None,
+    }
+}
+
+/// Solves `x ≡ r (mod m)` for every `(r, m)`, moduli not necessarily
+/// coprime: `(x, lcm)` with the smallest `x >= 0`, or `None` if the
+/// congruences contradict each other or the lcm overflows.
+pub fn crt(congruences: &[(i128, i128)]) -> Option<(i128, i128)> {
+    congruences
+        .iter()
+        .try_fold((0, 1), |(r1, m1): (i128, i128), &(r2, m2)| {
+            let (g, _, _) = gcd_ex(m1, m2);
+            let diff = (r2 - r1).rem_euclid(m2);
+            if diff % g != 0 {
+                return None;
+            }
+
+            let lcm = (m1 / g).checked_mul(m2)?;
+            let k = mod_mul(diff / g, mod_inv(m1 / g, m2 / g)?, m2 / g);
+
+            Some(((r1 + mod_mul(m1, k, lcm)).rem_euclid(lcm), lcm))
+        })
+}
+
+/// The smallest `x >= 0` with `base ^ x ≡ target (mod m)`, by baby-step
+/// giant-step; `base` must be coprime with `m`.
+pub fn discrete_log(base: i128, target: i128, m: i128) -> Option<u128> {
+    let target = target.rem_euclid(m);
+    let n = (m as f64).sqrt().ceil() as i128;
+
+    let mut baby_steps = HashMap::new();
+    let mut value = 1 % m;
+    for j in 0..n {
+        baby_steps.entry(value).or_insert(j);
+        value = mod_mul(value, base, m);
+    }
+
+    let giant_step = mod_inv(mod_pow(base, n as u128, m), m)?;
+    let mut value = target;
+    for i in 0..n {
+        if let Some(j) = baby_steps.get(&value) {
+            return Some((i * n + j) as u128);
+        }
+        value = mod_mul(value, giant_step, m);
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extended_gcd() {
+        assert_eq!(gcd_ex(13, 19), (1, 3, -2));
+        assert_eq!(gcd_ex(937, 397), (1, -186, 439));
+        assert_eq!(gcd_ex(12, 18), (6, -1, 1));
+        assert_eq!(gcd_ex(0, 5), (5, 0, 1));
+    }
+
+    #[test]
+    fn modular_arithmetic() {
+        let m = i128::MAX - 1;
+
+        assert_eq!(mod_mul(-3, 5, 7), 6);
+        assert_eq!(mod_mul(m - 1, m - 1, m), 1);
+        assert_eq!(mod_pow(7, 8, 20201227), 5764801);
+        assert_eq!(mod_pow(2, 0, 1), 0);
+        assert_eq!(mod_pow(m - 1, 3, m), m - 1);
+        assert_eq!(mod_inv(3, 7), Some(5));
+        assert_eq!(mod_inv(-3, 7), Some(2));
+        assert_eq!(mod_inv(4, 8), None);
+    }
+
+    #[test]
+    fn chinese_remainder() {
+        assert_eq!(crt(&[]), Some((0, 1)));
+        assert_eq!(crt(&[(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt(&[(1, 4), (3, 6)]), Some((9, 12)));
+        assert_eq!(crt(&[(1, 4), (2, 6)]), None);
+        assert_eq!(crt(&[(0, i128::MAX), (1, 2)]), None);
+    }
+
+    #[test]
+    fn discrete_logarithm() {
+        assert_eq!(discrete_log(7, 5764801, 20201227), Some(8));
+        assert_eq!(discrete_log(7, 17807724, 20201227), Some(11));
+        assert_eq!(discrete_log(7, 1, 20201227), Some(0));
+        assert_eq!(discrete_log(2, 3, 7), None);
+
+        for x in 0..100 {
+            assert_eq!(discrete_log(3, mod_pow(3, x, 1009), 1009), Some(x % 1008));
+        }
+    }
+}
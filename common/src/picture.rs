@@ -0,0 +1,286 @@
+use crate::{Error, Solution};
+use std::fmt::Write;
+
+/// A colour, as red, green and blue.
+pub type Rgb = (u8, u8, u8);
+
+pub const BLACK: Rgb = (0, 0, 0);
+pub const WHITE: Rgb = (255, 255, 255);
+
+/// Plain PBM and PPM lines must not be longer than this.
+const LINE: usize = 70;
+
+/// Parses `input` and draws the state `part` ends in, if the day draws one.
+pub fn draw<S: Solution>(input: &str, part: usize) -> Result<Option<Picture>, Error> {
+    let input = S::parse(input)?;
+
+    S::picture(&input, part).transpose()
+}
+
+/// A picture of a day's state, to attach to a bug report.
+pub enum Picture {
+    Raster(Raster),
+    Vector(Svg),
+}
+
+impl Picture {
+    /// Encodes the picture as plain (ASCII) PBM, plain PPM or SVG; vector
+    /// pictures are only SVG.
+    pub fn encode(&self, format: &str) -> Result<String, Error> {
+        match (self, format) {
+            (Picture::Raster(raster), "pbm") => Ok(raster.to_pbm()),
+            (Picture::Raster(raster), "ppm") => Ok(raster.to_ppm()),
+            (Picture::Raster(raster), "svg") => Ok(raster.to_svg().to_string()),
+            (Picture::Vector(svg), "svg") => Ok(svg.to_string()),
+            (Picture::Vector(_), "pbm") | (Picture::Vector(_), "ppm") => {
+                Err(Error::new("this picture can only be saved as SVG"))
+            }
+            _ => Err(Error::new(format!(
+                "unknown picture format {}, expecting pbm, ppm or svg",
+                format
+            ))),
+        }
+    }
+}
+
+/// A grid of pixels, row by row from the top left corner.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Raster {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Raster {
+    /// A white raster.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![WHITE; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Colours the pixel at `(x, y)`, ignoring those out of the raster.
+    pub fn set(&mut self, (x, y): (usize, usize), colour: Rgb) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = colour;
+        }
+    }
+
+    pub fn get(&self, (x, y): (usize, usize)) -> Option<Rgb> {
+        if x < self.width && y < self.height {
+            Some(self.pixels[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    /// Plain PBM, with every pixel that is not white black.
+    pub fn to_pbm(&self) -> String {
+        let mut output = format!("P1\n{} {}\n", self.width, self.height);
+        wrap(
+            &mut output,
+            self.pixels
+                .iter()
+                .map(|&pixel| if pixel == WHITE { "0" } else { "1" }),
+        );
+        output
+    }
+
+    /// Plain PPM.
+    pub fn to_ppm(&self) -> String {
+        let mut output = format!("P3\n{} {}\n255\n", self.width, self.height);
+        wrap(
+            &mut output,
+            self.pixels
+                .iter()
+                .map(|(r, g, b)| format!("{} {} {}", r, g, b)),
+        );
+        output
+    }
+
+    /// An SVG with a square for every pixel that is not white.
+    pub fn to_svg(&self) -> Svg {
+        let mut svg = Svg::new(0.0, 0.0, self.width as f64, self.height as f64);
+        for (i, &pixel) in self.pixels.iter().enumerate() {
+            if pixel != WHITE {
+                let (x, y) = ((i % self.width) as f64, (i / self.width) as f64);
+                svg.polygon(
+                    &[(x, y), (x + 1.0, y), (x + 1.0, y + 1.0), (x, y + 1.0)],
+                    pixel,
+                );
+            }
+        }
+        svg
+    }
+}
+
+/// Appends `values` to `output` separated by spaces, in lines of at most
+/// `LINE` characters.
+fn wrap<S: AsRef<str>, I: Iterator<Item = S>>(output: &mut String, values: I) {
+    let mut line = 0;
+    for value in values {
+        let value = value.as_ref();
+        if line > 0 && line + 1 + value.len() > LINE {
+            output.push('\n');
+            line = 0;
+        } else if line > 0 {
+            output.push(' ');
+            line += 1;
+        }
+        output.push_str(value);
+        line += value.len();
+    }
+    output.push('\n');
+}
+
+/// An SVG drawing, in the coordinates of its view box.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Svg {
+    view_box: (f64, f64, f64, f64),
+    elements: Vec<String>,
+}
+
+impl Svg {
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Self {
+            view_box: (x, y, width, height),
+            elements: Vec::new(),
+        }
+    }
+
+    /// A line through `points`, `width` thick.
+    pub fn polyline(&mut self, points: &[(f64, f64)], colour: Rgb, width: f64) {
+        self.elements.push(format!(
+            r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="{}"/>"#,
+            points_attribute(points),
+            hex(colour),
+            width
+        ));
+    }
+
+    /// A filled polygon through `points`.
+    pub fn polygon(&mut self, points: &[(f64, f64)], colour: Rgb) {
+        self.elements.push(format!(
+            r#"<polygon points="{}" fill="{}"/>"#,
+            points_attribute(points),
+            hex(colour)
+        ));
+    }
+
+    /// A dot of radius `r` at `(x, y)`.
+    pub fn circle(&mut self, (x, y): (f64, f64), r: f64, colour: Rgb) {
+        self.elements.push(format!(
+            r#"<circle cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+            x,
+            y,
+            r,
+            hex(colour)
+        ));
+    }
+}
+
+impl std::fmt::Display for Svg {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let (x, y, width, height) = self.view_box;
+
+        writeln!(
+            fmt,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+            x, y, width, height
+        )?;
+        writeln!(
+            fmt,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="white"/>"#,
+            x, y, width, height
+        )?;
+        for element in &self.elements {
+            writeln!(fmt, "{}", element)?;
+        }
+        writeln!(fmt, "</svg>")
+    }
+}
+
+fn points_attribute(points: &[(f64, f64)]) -> String {
+    points.iter().fold(String::new(), |mut output, (x, y)| {
+        if !output.is_empty() {
+            output.push(' ');
+        }
+        let _ = write!(output, "{},{}", x, y);
+        output
+    })
+}
+
+fn hex((r, g, b): Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raster() -> Raster {
+        let mut raster = Raster::new(3, 2);
+        raster.set((0, 0), BLACK);
+        raster.set((2, 1), (255, 0, 0));
+        raster.set((3, 0), BLACK);
+        raster
+    }
+
+    #[test]
+    fn pbm() {
+        assert_eq!(raster().to_pbm(), "P1\n3 2\n1 0 0 0 0 1\n");
+    }
+
+    #[test]
+    fn ppm() {
+        assert_eq!(
+            raster().to_ppm(),
+            "P3\n3 2\n255\n0 0 0 255 255 255 255 255 255 255 255 255 255 255 255 255 0 0\n"
+        );
+    }
+
+    #[test]
+    fn short_lines() {
+        let ppm = Raster::new(50, 50).to_ppm();
+
+        assert!(ppm.lines().all(|line| line.len() <= LINE));
+        assert_eq!(ppm.split_ascii_whitespace().count(), 4 + 50 * 50 * 3);
+    }
+
+    #[test]
+    fn svg() {
+        let mut svg = Svg::new(-1.0, -1.0, 4.0, 2.5);
+        svg.polyline(&[(0.0, 0.0), (1.5, -1.0)], (255, 0, 0), 0.1);
+        svg.circle((0.0, 0.0), 0.5, BLACK);
+
+        assert_eq!(
+            svg.to_string(),
+            r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="-1 -1 4 2.5">
+<rect x="-1" y="-1" width="4" height="2.5" fill="white"/>
+<polyline points="0,0 1.5,-1" fill="none" stroke="#ff0000" stroke-width="0.1"/>
+<circle cx="0" cy="0" r="0.5" fill="#000000"/>
+</svg>
+"##
+        );
+        assert_eq!(raster().to_svg().elements.len(), 2);
+    }
+
+    #[test]
+    fn formats() {
+        let vector = Picture::Vector(Svg::new(0.0, 0.0, 1.0, 1.0));
+
+        assert!(vector.encode("svg").is_ok());
+        assert!(vector.encode("pbm").is_err());
+        assert!(Picture::Raster(raster()).encode("png").is_err());
+    }
+}
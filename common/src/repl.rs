@@ -0,0 +1,182 @@
+use crate::{Error, Solution};
+use std::io::{BufRead, Write};
+use std::time::Instant;
+
+const COMMANDS: &[(&str, &str)] = &[
+    ("part <1|2>", "solve a part on the current state"),
+    ("reset", "parse the input again, undoing any step"),
+    ("help", "list the commands"),
+    ("quit", "leave the REPL (or end of input)"),
+];
+
+/// Parses `input` and reads commands from `reader`, one per line, printing
+/// their results to `writer`: the common ones, and those of the day in
+/// `Solution::COMMANDS`. Fails only if the input does not parse or on an
+/// I/O error; a failing command prints its error and the REPL goes on.
+pub fn repl<S: Solution>(
+    input: &str,
+    reader: &mut dyn BufRead,
+    writer: &mut dyn Write,
+) -> Result<(), Error> {
+    let io = |error: std::io::Error| Error::new(error.to_string()).in_day(S::DAY);
+
+    let mut state = S::parse(input)?;
+    writeln!(
+        writer,
+        "day {} input loaded, type help for the commands",
+        S::DAY
+    )
+    .map_err(io)?;
+
+    let mut line = String::new();
+    loop {
+        write!(writer, "day{:02}> ", S::DAY).map_err(io)?;
+        writer.flush().map_err(io)?;
+
+        line.clear();
+        if reader.read_line(&mut line).map_err(io)? == 0 {
+            writeln!(writer).map_err(io)?;
+            return Ok(());
+        }
+
+        let words = line.split_ascii_whitespace().collect::<Vec<_>>();
+        let output = match words.as_slice() {
+            [] => continue,
+            ["quit"] | ["exit"] => return Ok(()),
+            ["help"] => Ok(help(COMMANDS.iter().chain(S::COMMANDS))),
+            ["reset"] => S::parse(input).map(|input| {
+                state = input;
+                "input parsed again".to_string()
+            }),
+            ["part", part] => solve::<S>(&state, part),
+            [command, args @ ..] => S::explore(&mut state, command, args).unwrap_or_else(|| {
+                Err(Error::new(format!(
+                    "unknown command {}, type help for the commands",
+                    command
+                )))
+            }),
+        };
+
+        match output {
+            Ok(output) if output.is_empty() => {}
+            Ok(output) => writeln!(writer, "{}", output.trim_end()).map_err(io)?,
+            Err(error) => writeln!(writer, "error: {}", error).map_err(io)?,
+        }
+    }
+}
+
+fn help<'a, I: Iterator<Item = &'a (&'a str, &'a str)>>(commands: I) -> String {
+    commands
+        .map(|(usage, description)| format!("  {:<24} {}", usage, description))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn solve<S: Solution>(input: &S::Input<'_>, part: &str) -> Result<String, Error> {
+    let now = Instant::now();
+    let answer = match part {
+        "1" => S::part_1(input).map(Some),
+        "2" => S::part_2(input),
+        _ => return Err(Error::new(format!("invalid part: {}", part))),
+    }?;
+    let elapsed = now.elapsed();
+
+    Ok(match answer {
+        Some(answer) => format!("part {}: {} ({:?})", part, answer, elapsed),
+        None => format!("day {} has no part {}", S::DAY, part),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Answer;
+
+    /// Counts down from the number in the input.
+    struct Countdown;
+
+    impl Solution for Countdown {
+        const DAY: u32 = 1;
+
+        type Input<'a> = u64;
+
+        fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+            input
+                .trim()
+                .parse()
+                .map_err(|_| Error::at(input, input, "invalid number"))
+        }
+
+        fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+            Ok((*input).into())
+        }
+
+        fn part_2(_input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+            Ok(None)
+        }
+
+        const COMMANDS: &'static [(&'static str, &'static str)] =
+            &[("step [n]", "count down n times")];
+
+        fn explore(
+            input: &mut Self::Input<'_>,
+            command: &str,
+            args: &[&str],
+        ) -> Option<Result<String, Error>> {
+            match (command, args) {
+                ("step", []) => *input = input.saturating_sub(1),
+                ("step", [n]) => match n.parse::<u64>() {
+                    Ok(n) => *input = input.saturating_sub(n),
+                    Err(_) => return Some(Err(Error::new("invalid step"))),
+                },
+                _ => return None,
+            }
+
+            Some(Ok(input.to_string()))
+        }
+    }
+
+    fn session(input: &str, commands: &str) -> Result<Vec<String>, Error> {
+        let mut output = Vec::new();
+        repl::<Countdown>(input, &mut commands.as_bytes(), &mut output)?;
+
+        Ok(String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .skip(1)
+            .map(|line| line.trim_start_matches("day01> ").to_string())
+            .filter(|line| !line.is_empty())
+            .collect())
+    }
+
+    #[test]
+    fn commands() {
+        assert_eq!(
+            session("10", "step\nstep 3\n\nreset\nstep 20\npart 2\nquit\nstep\n").unwrap(),
+            vec!["9", "6", "input parsed again", "0", "day 1 has no part 2",]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            session("10", "step x\npart 3\njump\n").unwrap(),
+            vec![
+                "error: invalid step",
+                "error: invalid part: 3",
+                "error: unknown command jump, type help for the commands",
+            ]
+        );
+        assert!(session("ten", "").is_err());
+    }
+
+    #[test]
+    fn help_lists_the_day_commands() {
+        let output = session("10", "help\n").unwrap();
+
+        assert!(output.iter().any(|line| line.contains("reset")));
+        assert!(output
+            .iter()
+            .any(|line| line.contains("count down n times")));
+    }
+}
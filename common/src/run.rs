@@ -0,0 +1,212 @@
+use crate::{measure, Answer, Error, Memory, Solution};
+use std::time::{Duration, Instant};
+
+/// Timing statistics over repeated runs of the same step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub runs: usize,
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+    /// Sample standard deviation, zero for a single run.
+    pub std_dev: Duration,
+}
+
+impl Stats {
+    /// `samples` must not be empty.
+    pub fn new(mut samples: Vec<Duration>) -> Self {
+        samples.sort_unstable();
+
+        let runs = samples.len();
+        let nanos = samples.iter().map(|sample| sample.as_nanos() as f64);
+        let mean = nanos.clone().sum::<f64>() / runs as f64;
+        let variance = if runs > 1 {
+            nanos.map(|sample| (sample - mean).powi(2)).sum::<f64>() / (runs - 1) as f64
+        } else {
+            0.0
+        };
+
+        Self {
+            runs,
+            min: samples[0],
+            median: (samples[(runs - 1) / 2] + samples[runs / 2]) / 2,
+            max: samples[runs - 1],
+            mean: Duration::from_nanos(mean.round() as u64),
+            std_dev: Duration::from_nanos(variance.sqrt().round() as u64),
+        }
+    }
+}
+
+/// The median, followed by the range when there is more than one run.
+impl std::fmt::Display for Stats {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let ms = |duration: Duration| duration.as_nanos() as f64 / 1_000_000.0;
+
+        if self.runs > 1 {
+            write!(
+                fmt,
+                "{:.3}ms ({:.3}..{:.3})",
+                ms(self.median),
+                ms(self.min),
+                ms(self.max)
+            )
+        } else {
+            write!(fmt, "{:.3}ms", ms(self.median))
+        }
+    }
+}
+
+/// The answers of a day together with how long parsing and each part took
+/// and, with the `CountingAllocator` installed, the heap they used (the
+/// most of any run).
+pub struct Run {
+    pub parse: Stats,
+    pub parse_memory: Option<Memory>,
+    pub parts: Vec<PartRun>,
+}
+
+pub struct PartRun {
+    pub part: usize,
+    pub answer: Result<Option<Answer>, Error>,
+    pub elapsed: Stats,
+    pub memory: Option<Memory>,
+}
+
+/// Parses `input` and solves the given `parts` on it `runs` times (at least
+/// once), timing parsing and each part separately. Fails only if parsing
+/// fails; a part that fails is not repeated.
+pub fn run<S: Solution>(input: &str, parts: &[usize], runs: usize) -> Result<Run, Error> {
+    let runs = runs.max(1);
+
+    let mut samples = Vec::with_capacity(runs);
+    let (mut parsed, mut parse_memory) = (None, None);
+    for _ in 0..runs {
+        // the previous result is dropped first, not while measuring
+        drop(parsed.take());
+
+        let now = Instant::now();
+        let (input, memory) = measure(|| S::parse(input));
+        samples.push(now.elapsed());
+
+        parsed = Some(input?);
+        parse_memory = most(parse_memory, memory);
+    }
+    let parse = Stats::new(samples);
+    let parsed = parsed.unwrap();
+
+    let parts = parts
+        .iter()
+        .map(|&part| {
+            let mut samples = Vec::with_capacity(runs);
+            let (mut answer, mut memory) = (Ok(None), None);
+            for _ in 0..runs {
+                let now = Instant::now();
+                let (result, used) = measure(|| match part {
+                    1 => S::part_1(&parsed).map(Some),
+                    _ => S::part_2(&parsed),
+                });
+                samples.push(now.elapsed());
+
+                answer = result;
+                memory = most(memory, used);
+
+                if answer.is_err() {
+                    break;
+                }
+            }
+
+            PartRun {
+                part,
+                answer,
+                elapsed: Stats::new(samples),
+                memory,
+            }
+        })
+        .collect();
+
+    Ok(Run {
+        parse,
+        parse_memory,
+        parts,
+    })
+}
+
+fn most(a: Option<Memory>, b: Option<Memory>) -> Option<Memory> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Sum;
+
+    impl Solution for Sum {
+        const DAY: u32 = 0;
+
+        type Input<'a> = Vec<u32>;
+
+        fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+            input
+                .lines()
+                .map(|line| {
+                    line.parse()
+                        .map_err(|_| Error::at(input, line, "invalid number"))
+                })
+                .collect()
+        }
+
+        fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+            Ok(input.iter().sum::<u32>().into())
+        }
+
+        fn part_2(_input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+            Err(Error::new("no second part"))
+        }
+    }
+
+    #[test]
+    fn run_parts() {
+        let run = run::<Sum>("1\n2\n3", &[2, 1], 3).unwrap();
+
+        assert_eq!(
+            run.parts.iter().map(|part| part.part).collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+        assert_eq!(run.parse.runs, 3);
+        assert!(run.parts[0].answer.is_err());
+        assert_eq!(run.parts[0].elapsed.runs, 1);
+        assert_eq!(run.parts[1].answer, Ok(Some(Answer::from(6u32))));
+        // the common tests count the allocations
+        assert!(run
+            .parse_memory
+            .is_some_and(|memory| memory.allocations == 1));
+    }
+
+    #[test]
+    fn run_parse_error() {
+        assert!(run::<Sum>("1\nx", &[1, 2], 1).is_err());
+    }
+
+    #[test]
+    fn stats() {
+        let ms = Duration::from_millis;
+
+        let stats = Stats::new(vec![ms(3), ms(1), ms(2)]);
+        assert_eq!((stats.min, stats.median, stats.max), (ms(1), ms(2), ms(3)));
+        assert_eq!(stats.to_string(), "2.000ms (1.000..3.000)");
+
+        let stats = Stats::new(vec![ms(4), ms(1), ms(2), ms(8)]);
+        assert_eq!(stats.median, ms(3));
+        assert_eq!(stats.mean, Duration::from_micros(3750));
+        assert_eq!(stats.std_dev, Duration::from_nanos(3095696));
+
+        let stats = Stats::new(vec![ms(5)]);
+        assert_eq!(stats.std_dev, Duration::default());
+        assert_eq!(stats.to_string(), "5.000ms");
+    }
+}
@@ -0,0 +1,54 @@
+use crate::{Answer, Error, Picture};
+
+/// A day of the calendar: how to read its puzzle input and how to solve
+/// both parts from the parsed form.
+pub trait Solution {
+    const DAY: u32;
+
+    /// The parsed puzzle input, possibly borrowing from the raw text.
+    type Input<'a>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error>;
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error>;
+
+    /// `None` when the day has no second part (day 25).
+    fn part_2(input: &Self::Input<'_>) -> Result<Option<Answer>, Error>;
+
+    /// The generations of a cellular automaton day, one drawing each from
+    /// the initial state on, for `aoc --animate`; `None` if `part` does not
+    /// compute any.
+    fn generations(
+        _input: &Self::Input<'_>,
+        _part: usize,
+    ) -> Option<Box<dyn Iterator<Item = String>>> {
+        None
+    }
+
+    /// A picture of the state `part` ends in, for `aoc --export`; `None` if
+    /// the day draws none.
+    fn picture(_input: &Self::Input<'_>, _part: usize) -> Option<Result<Picture, Error>> {
+        None
+    }
+
+    /// Checks the raw `text` and its parsed form against the assumptions
+    /// the solvers make without checking them, for `aoc lint`; every
+    /// violation points at the offending text where there is one.
+    fn lint(_text: &str, _input: &Self::Input<'_>) -> Vec<Error> {
+        Vec::new()
+    }
+
+    /// The day's own commands in the REPL, as usage and description.
+    const COMMANDS: &'static [(&'static str, &'static str)] = &[];
+
+    /// Runs one of `COMMANDS` on the parsed input, which it may change (to
+    /// step a simulation), and returns what to print; `None` if `command`
+    /// is not one of them.
+    fn explore(
+        _input: &mut Self::Input<'_>,
+        _command: &str,
+        _args: &[&str],
+    ) -> Option<Result<String, Error>> {
+        None
+    }
+}
@@ -0,0 +1,103 @@
+use crate::RANGE;
+use common::Rng;
+use std::ops::RangeInclusive;
+
+/// The sizes `generate` supports: the pair and the three entries that sum
+/// to 2020 take five.
+pub const SIZES: RangeInclusive<usize> = 5..=usize::MAX;
+
+/// `size` entries (at least 5), exactly two of which sum to 2020 and
+/// exactly three others of which sum to 2020 as well.
+///
+/// Apart from one of the pair, the entries are either at most 1009 (the
+/// planted ones) or above 1010, so that no two or three of the latter can
+/// sum to 2020; the fillers are chosen so as not to complete a sum with the
+/// small ones either.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let half = RANGE as u64 / 2;
+
+    let planted = loop {
+        let a = rng.range(half + 1, RANGE as u64 - 1);
+        let (c, d) = (rng.range(half / 2, half - 1), rng.range(half / 2, half - 1));
+        let e = RANGE as u64 - c - d;
+        let planted = [a, RANGE as u64 - a, c, d, e];
+
+        if e < half && counts(&planted) == (1, 1) {
+            break planted;
+        }
+    };
+    let small = &planted[1..];
+
+    let excluded = small
+        .iter()
+        .flat_map(|s| {
+            small
+                .iter()
+                .filter(move |t| t != &s)
+                .map(move |t| s + t)
+                .chain(std::iter::once(*s))
+        })
+        .filter(|&sum| sum < RANGE as u64)
+        .map(|sum| RANGE as u64 - sum)
+        .collect::<Vec<_>>();
+
+    let mut entries = planted.to_vec();
+    while entries.len() < size {
+        let entry = rng.range(half + 1, RANGE as u64);
+        if !excluded.contains(&entry) {
+            entries.push(entry);
+        }
+    }
+    rng.shuffle(&mut entries);
+
+    entries.iter().map(|entry| format!("{}\n", entry)).collect()
+}
+
+/// How many pairs and triples of `entries` sum to 2020.
+fn counts(entries: &[u64]) -> (usize, usize) {
+    let n = entries.len();
+    let (mut pairs, mut triples) = (0, 0);
+
+    for i in 0..n {
+        for j in i + 1..n {
+            if entries[i] + entries[j] == RANGE as u64 {
+                pairs += 1;
+            }
+            for k in j + 1..n {
+                if entries[i] + entries[j] + entries[k] == RANGE as u64 {
+                    triples += 1;
+                }
+            }
+        }
+    }
+
+    (pairs, triples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unique_answers() {
+        for seed in 0..20 {
+            let input = crate::parse(&generate(&mut Rng::new(seed), 150)).unwrap();
+
+            assert_eq!(input.len(), 150);
+            assert_eq!(
+                counts(&input.iter().map(|&i| i as u64).collect::<Vec<_>>()),
+                (1, 1)
+            );
+            assert!(crate::solve_1(&input).is_ok());
+            assert!(crate::solve_2(&input).is_ok());
+        }
+    }
+
+    #[test]
+    fn large_input() {
+        let input = crate::parse(&generate(&mut Rng::new(1), 5000)).unwrap();
+
+        assert_eq!(input.len(), 5000);
+        assert!(crate::solve_2(&input).is_ok());
+    }
+}
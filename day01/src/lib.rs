@@ -1,82 +1,281 @@
-#![feature(test)]
+#![cfg_attr(feature = "bench", feature(test))]
+
+#[cfg(feature = "bench")]
 extern crate test;
 
-#[macro_use]
-extern crate lazy_static;
+use common::{Answer, BitSet, Error, Solution};
+
+mod generate;
+mod stream;
 
-mod bit_set;
+pub use generate::{generate, SIZES};
+pub use stream::{find_sum_in, stream, SumFinder};
 
 const RANGE: usize = 2020;
 
-lazy_static! {
-    static ref INPUT: Vec<usize> = include_str!("../input")
+/// The embedded puzzle input, parsed on first use.
+#[cfg(feature = "input")]
+fn input() -> &'static Vec<usize> {
+    static INPUT: std::sync::OnceLock<Vec<usize>> = std::sync::OnceLock::new();
+
+    INPUT.get_or_init(|| parse(include_str!("../input")).expect("invalid input"))
+}
+
+fn parse(input: &str) -> Result<Vec<usize>, Error> {
+    input
         .lines()
-        .map(|l| l.parse().unwrap())
-        .collect();
+        .map(|l| l.parse().map_err(|_| Error::at(input, l, "invalid number")))
+        .collect()
+}
+
+/// Entries of an expense report found to sum to a target, in the order
+/// they appear in the report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entries {
+    pub indices: Vec<usize>,
+    pub values: Vec<usize>,
+}
+
+impl Entries {
+    /// The product of the values, `None` if it overflows.
+    pub fn product(&self) -> Option<usize> {
+        self.values
+            .iter()
+            .try_fold(1usize, |product, &value| product.checked_mul(value))
+    }
 }
 
-fn solve_1(input: &[usize]) -> usize {
-    let mut set = bit_set::BitSet::new(RANGE);
+/// Targets up to this use a bit set of the values seen to find pairs,
+/// larger ones sorting and two pointers.
+const BIT_SET_TARGET: usize = 1 << 16;
 
-    for &i in input {
-        let d = RANGE - i;
-        if set.get(d) {
-            return d * i;
+/// Finds `k` distinct entries of `input` summing to `target`, `None` if there
+/// are none; entries may have equal values. Takes O(n) for a pair up to
+/// `BIT_SET_TARGET` and O(n log n + n^(k-1)) otherwise, so O(n^2) for a
+/// triple.
+pub fn find_sum(input: &[usize], k: usize, target: usize) -> Option<Entries> {
+    let mut indices = match k {
+        0 if target == 0 => Vec::new(),
+        0 => return None,
+        2 if target <= BIT_SET_TARGET => find_pair(input, target)?,
+        _ => {
+            let mut sorted = input
+                .iter()
+                .copied()
+                .enumerate()
+                .filter(|&(_, value)| value <= target)
+                .map(|(index, value)| (value, index))
+                .collect::<Vec<_>>();
+            sorted.sort_unstable();
+
+            find_sorted(&sorted, k, target)?
         }
-        set.set(i, true);
+    };
+
+    indices.sort_unstable();
+    let values = indices.iter().map(|&index| input[index]).collect();
+
+    Some(Entries { indices, values })
+}
+
+/// A pair summing to `target`, with a bit set of the values seen so far.
+fn find_pair(input: &[usize], target: usize) -> Option<Vec<usize>> {
+    let mut seen = BitSet::new(target + 1);
+
+    for (j, &value) in input.iter().enumerate().filter(|&(_, &v)| v <= target) {
+        let complement = target - value;
+        if seen.get(complement) {
+            let i = input[..j].iter().position(|&v| v == complement)?;
+            return Some(vec![i, j]);
+        }
+        seen.set(value, true);
     }
 
-    panic!("not found");
+    None
 }
 
-fn solve_2(input: &[usize]) -> usize {
-    for i in 0..input.len() - 2 {
-        for j in i + 1..input.len() - 1 {
-            let s = input[i] + input[j];
-            if s < RANGE {
-                let d = RANGE - s;
-                for t in j + 1..input.len() {
-                    if input[t] == d {
-                        return input[i] * input[j] * input[t];
-                    }
+/// The indices of `k` entries of `sorted`, `(value, index)` pairs sorted by
+/// value, summing to `target`.
+fn find_sorted(sorted: &[(usize, usize)], k: usize, target: usize) -> Option<Vec<usize>> {
+    match k {
+        0 => None,
+        1 => sorted
+            .binary_search_by_key(&target, |&(value, _)| value)
+            .ok()
+            .map(|i| vec![sorted[i].1]),
+        2 => {
+            let (mut i, mut j) = (0, sorted.len().checked_sub(1)?);
+            while i < j {
+                // a sum past `usize::MAX` is past any target too
+                let sum = sorted[i].0.checked_add(sorted[j].0);
+                match sum.map_or(std::cmp::Ordering::Greater, |sum| sum.cmp(&target)) {
+                    std::cmp::Ordering::Equal => return Some(vec![sorted[i].1, sorted[j].1]),
+                    std::cmp::Ordering::Less => i += 1,
+                    std::cmp::Ordering::Greater => j -= 1,
+                }
+            }
+            None
+        }
+        _ => {
+            for (i, &(value, index)) in sorted.iter().enumerate() {
+                // the others are at least as large
+                if value.saturating_mul(k) > target {
+                    break;
+                }
+                // the same value again finds nothing new
+                if i > 0 && sorted[i - 1].0 == value {
+                    continue;
+                }
+
+                if let Some(mut indices) = find_sorted(&sorted[i + 1..], k - 1, target - value) {
+                    indices.push(index);
+                    return Some(indices);
                 }
             }
+            None
         }
     }
+}
 
-    panic!("not found")
+fn solve(input: &[usize], k: usize) -> Result<usize, Error> {
+    find_sum(input, k, RANGE)
+        .ok_or_else(|| Error::new(format!("no {} entries sum to {}", k, RANGE)))?
+        .product()
+        .ok_or_else(|| Error::new("the product of the entries overflows"))
 }
 
+fn solve_1(input: &[usize]) -> Result<usize, Error> {
+    solve(input, 2)
+}
+
+fn solve_2(input: &[usize]) -> Result<usize, Error> {
+    solve(input, 3)
+}
+
+#[cfg(feature = "input")]
 pub fn part_1() -> usize {
-    solve_1(&INPUT)
+    solve_1(input()).expect("invalid input")
 }
 
+#[cfg(feature = "input")]
 pub fn part_2() -> usize {
-    solve_2(&INPUT)
+    solve_2(input()).expect("invalid input")
+}
+
+pub fn solve_part_1(input: &str) -> Result<usize, Error> {
+    parse(input)
+        .and_then(|input| solve_1(&input))
+        .map_err(|e| e.in_day(Day01::DAY))
+}
+
+pub fn solve_part_2(input: &str) -> Result<usize, Error> {
+    parse(input)
+        .and_then(|input| solve_2(&input))
+        .map_err(|e| e.in_day(Day01::DAY))
+}
+
+pub struct Day01;
+
+impl Solution for Day01 {
+    const DAY: u32 = 1;
+
+    type Input<'a> = Vec<usize>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+        parse(input).map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+        solve_1(input)
+            .map(Answer::from)
+            .map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+        solve_2(input)
+            .map(|answer| Some(answer.into()))
+            .map_err(|e| e.in_day(Self::DAY))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     static INPUT: [usize; 6] = [1721, 979, 366, 299, 675, 1456];
 
     #[test]
     fn same_results_1() {
-        assert_eq!(solve_1(&INPUT), 514579);
+        assert_eq!(solve_1(&INPUT), Ok(514579));
     }
 
     #[test]
     fn same_results_2() {
-        assert_eq!(solve_2(&INPUT), 241861950);
+        assert_eq!(solve_2(&INPUT), Ok(241861950));
+    }
+
+    #[test]
+    fn entries() {
+        let entries = find_sum(&INPUT, 3, RANGE).unwrap();
+        assert_eq!(entries.indices, vec![1, 2, 4]);
+        assert_eq!(entries.values, vec![979, 366, 675]);
+        assert_eq!(entries.product(), Some(241861950));
+    }
+
+    #[test]
+    fn duplicates() {
+        let input = [1010, 3, 1010];
+        assert_eq!(find_sum(&input, 2, RANGE).unwrap().indices, vec![0, 2]);
+        assert_eq!(find_sum(&input[..2], 2, RANGE), None);
+
+        let input = [5, 5, 5, 7];
+        assert_eq!(find_sum(&input, 3, 15).unwrap().indices, vec![0, 1, 2]);
+        assert_eq!(find_sum(&input, 4, 20), None);
+    }
+
+    #[test]
+    fn large_targets() {
+        let input = [1 << 40, 12, 3 << 40, 7];
+        for &k in &[2, 3] {
+            let target = (4 << 40) + if k == 3 { 7 } else { 0 };
+            let entries = find_sum(&input, k, target).unwrap();
+            assert_eq!(entries.values.iter().sum::<usize>(), target);
+        }
+        assert_eq!(find_sum(&input, 2, 1 << 20), None);
+
+        let input = [usize::MAX - 5, usize::MAX - 1, 3, usize::MAX / 2, 4];
+        let entries = find_sum(&input, 2, usize::MAX - 1).unwrap();
+        assert_eq!(entries.values, vec![usize::MAX - 5, 4]);
+        assert_eq!(find_sum(&input, 3, usize::MAX), None);
+        assert_eq!(find_sum(&input, 2, usize::MAX), None);
+    }
+
+    #[test]
+    fn any_count() {
+        assert_eq!(find_sum(&INPUT, 1, 366).unwrap().indices, vec![2]);
+        assert_eq!(find_sum(&INPUT, 0, 0).unwrap().values, Vec::<usize>::new());
+        assert_eq!(find_sum(&INPUT, 0, 1), None);
+
+        let entries = find_sum(&INPUT, 4, 1721 + 979 + 366 + 299).unwrap();
+        assert_eq!(entries.indices, vec![0, 1, 2, 3]);
+        assert_eq!(find_sum(&INPUT, 7, 5496), None);
+    }
+
+    #[test]
+    fn no_solution() {
+        assert!(solve_1(&[1, 2, 3]).is_err());
+        assert!(solve_2(&[2020, 0]).is_err());
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_1(b: &mut Bencher) {
         b.iter(part_1);
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_2(b: &mut Bencher) {
         b.iter(part_2);
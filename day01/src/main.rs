@@ -1,17 +1,27 @@
-use std::time::Instant;
-
-use day01::{part_1, part_2};
-
 fn main() {
-    let now = Instant::now();
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
 
-    println!("part 1: {}", part_1());
-    println!("part 2: {}", part_2());
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["--stream", path] => {
+            let result = if path == "-" {
+                day01::stream(std::io::stdin().lock(), std::io::stdout().lock())
+            } else {
+                match std::fs::File::open(path) {
+                    Ok(file) => {
+                        day01::stream(std::io::BufReader::new(file), std::io::stdout().lock())
+                    }
+                    Err(error) => {
+                        eprintln!("cannot read input {}: {}", path, error);
+                        std::process::exit(1);
+                    }
+                }
+            };
 
-    let elapsed = now.elapsed();
-    println!(
-        "elapsed: {}ms ({}ns)",
-        elapsed.as_millis(),
-        elapsed.as_nanos()
-    );
+            if let Err(error) = result {
+                eprintln!("{}", error.in_day(1));
+                std::process::exit(1);
+            }
+        }
+        _ => common::main::<day01::Day01>(include_str!("../input")),
+    }
 }
@@ -0,0 +1,262 @@
+use crate::{find_sum, Entries, RANGE};
+use common::Error;
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+/// Looks for `k` entries summing to a target in a report fed one entry at a
+/// time, remembering no more than `k - 1` entries of each value up to the
+/// target; its memory depends on `k` and the target, not on the report.
+///
+/// A new entry completes a pair or a triple in O(1); keeping one for
+/// triples takes O(entries kept), as it is paired with each of them.
+pub struct SumFinder {
+    k: usize,
+    target: usize,
+    // the indices of the entries kept, by value
+    seen: HashMap<usize, Vec<usize>>,
+    // for triples, two entries kept by their sum, as `(index, value)`
+    pairs: HashMap<usize, [(usize, usize); 2]>,
+    next: usize,
+}
+
+impl SumFinder {
+    pub fn new(k: usize, target: usize) -> Self {
+        Self {
+            k,
+            target,
+            seen: HashMap::new(),
+            pairs: HashMap::new(),
+            next: 0,
+        }
+    }
+
+    /// Adds the next entry of the report; the entries summing to the target
+    /// if this one completes them, with indices counted from the first entry
+    /// pushed.
+    pub fn push(&mut self, value: usize) -> Option<Entries> {
+        let index = self.next;
+        self.next += 1;
+
+        if self.k == 0 || value > self.target {
+            return None;
+        }
+
+        if let Some(mut found) = self.complete(self.target - value) {
+            found.sort_unstable();
+            found.push((index, value));
+            return Some(Entries {
+                indices: found.iter().map(|&(index, _)| index).collect(),
+                values: found.iter().map(|&(_, value)| value).collect(),
+            });
+        }
+
+        // a sum needs at most `k - 1` others of the same value
+        if self.k > 1 {
+            let indices = self.seen.entry(value).or_default();
+            if indices.len() + 1 < self.k {
+                indices.push(index);
+                if self.k == 3 {
+                    self.pair(index, value);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Records the sums of the entry just kept with each of the others, the
+    /// earliest of them for sums already made by this entry.
+    fn pair(&mut self, index: usize, value: usize) {
+        for (&other, indices) in &self.seen {
+            let sum = match other.checked_add(value) {
+                Some(sum) if sum <= self.target => sum,
+                _ => continue,
+            };
+
+            for &i in indices.iter().filter(|&&i| i != index) {
+                let pair = [(i, other), (index, value)];
+                self.pairs
+                    .entry(sum)
+                    .and_modify(|old| {
+                        if old[1].0 == index && i < old[0].0 {
+                            *old = pair;
+                        }
+                    })
+                    .or_insert(pair);
+            }
+        }
+    }
+
+    /// `k - 1` entries kept summing to `rest`, as `(index, value)` pairs.
+    fn complete(&self, rest: usize) -> Option<Vec<(usize, usize)>> {
+        match self.k {
+            1 => (rest == 0).then(Vec::new),
+            2 => self.seen.get(&rest).map(|indices| vec![(indices[0], rest)]),
+            3 => self.pairs.get(&rest).map(|pair| pair.to_vec()),
+            _ => {
+                let mut kept = self
+                    .seen
+                    .iter()
+                    .flat_map(|(&value, indices)| indices.iter().map(move |&index| (index, value)))
+                    .collect::<Vec<_>>();
+                kept.sort_unstable();
+                let values = kept.iter().map(|&(_, value)| value).collect::<Vec<_>>();
+
+                find_sum(&values, self.k - 1, rest)
+                    .map(|found| found.indices.iter().map(|&i| kept[i]).collect())
+            }
+        }
+    }
+}
+
+/// The numbers of `reader`, one a line; an error locates the line.
+fn numbers<R: BufRead>(reader: R) -> impl Iterator<Item = Result<usize, Error>> {
+    reader.lines().enumerate().map(|(i, line)| {
+        let line = line.map_err(|error| Error::new(format!("cannot read input: {}", error)))?;
+        let text = line.trim();
+        text.parse().map_err(|_| Error {
+            position: Some((i + 1, 1)),
+            ..Error::at(text, text, "invalid number")
+        })
+    })
+}
+
+/// Reads `reader` until `k` of its numbers sum to `target`, without keeping
+/// the report in memory; the entries have the 0-based line indices.
+pub fn find_sum_in<R: BufRead>(
+    reader: R,
+    k: usize,
+    target: usize,
+) -> Result<Option<Entries>, Error> {
+    if k == 0 {
+        return Ok(find_sum(&[], 0, target));
+    }
+
+    let mut finder = SumFinder::new(k, target);
+    for number in numbers(reader) {
+        if let Some(entries) = finder.push(number?) {
+            return Ok(Some(entries));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Reads a report from `reader` and writes to `out` each part as soon as its
+/// entries are read, with their 1-based lines; stops reading once both are.
+pub fn stream<R: BufRead, W: Write>(reader: R, mut out: W) -> Result<(), Error> {
+    let write = |out: &mut W, part: usize, entries: &Entries| {
+        let product = entries
+            .product()
+            .ok_or_else(|| Error::new("the product of the entries overflows"))?;
+        let lines = entries
+            .indices
+            .iter()
+            .zip(&entries.values)
+            .map(|(index, value)| format!("{} (line {})", value, index + 1))
+            .collect::<Vec<_>>();
+
+        writeln!(out, "part {}: {} = {}", part, product, lines.join(" * "))
+            .map_err(|error| Error::new(format!("cannot write: {}", error)))
+    };
+
+    let mut finders = vec![(1, SumFinder::new(2, RANGE)), (2, SumFinder::new(3, RANGE))];
+    for number in numbers(reader) {
+        let number = number?;
+        let mut i = 0;
+        while i < finders.len() {
+            if let Some(entries) = finders[i].1.push(number) {
+                write(&mut out, finders[i].0, &entries)?;
+                finders.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+
+        if finders.is_empty() {
+            return Ok(());
+        }
+    }
+
+    let k = finders[0].1.k;
+    Err(Error::new(format!("no {} entries sum to {}", k, RANGE)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPORT: &str = "1721\n979\n366\n299\n675\n1456\n";
+
+    #[test]
+    fn same_results() {
+        let pair = find_sum_in(REPORT.as_bytes(), 2, RANGE).unwrap().unwrap();
+        assert_eq!(pair.indices, vec![0, 3]);
+        assert_eq!(pair.product(), Some(514579));
+
+        let triple = find_sum_in(REPORT.as_bytes(), 3, RANGE).unwrap().unwrap();
+        assert_eq!(triple.indices, vec![1, 2, 4]);
+        assert_eq!(triple.product(), Some(241861950));
+    }
+
+    #[test]
+    fn bounded_memory() {
+        let mut finder = SumFinder::new(3, 100);
+        for value in (0..100_000).map(|i| 40 + i % 7 + if i % 2 == 0 { 1000 } else { 0 }) {
+            assert_eq!(finder.push(value), None);
+        }
+        assert_eq!(finder.seen.values().map(Vec::len).sum::<usize>(), 14);
+
+        let entries = finder.push(20).unwrap();
+        assert_eq!(entries.values, vec![40, 40, 20]);
+        assert_eq!(entries.indices, vec![7, 21, 100_000]);
+    }
+
+    #[test]
+    fn duplicates() {
+        let mut finder = SumFinder::new(3, 15);
+        assert_eq!(finder.push(5), None);
+        assert_eq!(finder.push(5), None);
+        assert_eq!(finder.push(5).unwrap().indices, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn any_count() {
+        let mut finder = SumFinder::new(4, 10);
+        for value in [1, 2, 2, 9, 3] {
+            assert_eq!(finder.push(value), None);
+        }
+        assert_eq!(finder.push(3).unwrap().indices, vec![1, 2, 4, 5]);
+
+        let mut finder = SumFinder::new(1, 7);
+        assert_eq!(finder.push(3), None);
+        assert_eq!(finder.push(7).unwrap().indices, vec![1]);
+    }
+
+    #[test]
+    fn crlf() {
+        let pair = find_sum_in("1721\r\n979\r\n299\r\n".as_bytes(), 2, RANGE);
+        assert_eq!(pair.unwrap().unwrap().values, vec![1721, 299]);
+    }
+
+    #[test]
+    fn report() {
+        let mut out = Vec::new();
+        stream(format!("{}garbage\n", REPORT).as_bytes(), &mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "part 1: 514579 = 1721 (line 1) * 299 (line 4)\n\
+             part 2: 241861950 = 979 (line 2) * 366 (line 3) * 675 (line 5)\n"
+        );
+    }
+
+    #[test]
+    fn invalid_number() {
+        let error = find_sum_in("1\n2\nx3\n".as_bytes(), 2, 10).unwrap_err();
+
+        assert_eq!(error.position, Some((3, 1)));
+        assert_eq!(error.snippet, "x3");
+        assert!(stream("1721\n".as_bytes(), Vec::new()).is_err());
+    }
+}
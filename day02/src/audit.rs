@@ -0,0 +1,206 @@
+use crate::{Password, Policy};
+use common::Error;
+use serde::Serialize;
+use std::io::Write;
+
+/// Every line of an input checked against some policies, for review rather
+/// than just a count; unparsable lines are reported instead of failing.
+#[derive(Debug, Serialize)]
+pub struct Audit {
+    pub policies: Vec<Summary>,
+    /// The lines that could not be parsed.
+    pub unparsed: usize,
+    pub lines: Vec<Line>,
+}
+
+/// How many lines a policy accepts.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Summary {
+    pub policy: String,
+    pub valid: usize,
+}
+
+/// One line of the input: the reason it could not be parsed, or how it
+/// fares with each policy.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Line {
+    /// 1-based.
+    pub line: usize,
+    pub text: String,
+    pub error: Option<String>,
+    pub checks: Vec<Check>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Check {
+    pub policy: String,
+    pub passes: bool,
+    /// Why the line breaks the policy.
+    pub reason: Option<String>,
+}
+
+/// Checks every line of `input` against every one of `policies`.
+pub fn audit(input: &str, policies: &[Box<dyn Policy>]) -> Audit {
+    let names = policies.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+    let lines = input
+        .lines()
+        .enumerate()
+        .map(|(i, text)| {
+            let (error, checks) = match text.parse::<Password>() {
+                Ok(password) => (
+                    None,
+                    policies
+                        .iter()
+                        .zip(&names)
+                        .map(|(policy, name)| {
+                            let reason = policy.check(&password).err();
+                            Check {
+                                policy: name.clone(),
+                                passes: reason.is_none(),
+                                reason,
+                            }
+                        })
+                        .collect(),
+                ),
+                Err(error) => (Some(error.within(input, text).to_string()), Vec::new()),
+            };
+
+            Line {
+                line: i + 1,
+                text: text.to_string(),
+                error,
+                checks,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    Audit {
+        policies: names
+            .into_iter()
+            .enumerate()
+            .map(|(i, policy)| Summary {
+                policy,
+                valid: lines
+                    .iter()
+                    .filter(|line| line.checks.get(i).is_some_and(|check| check.passes))
+                    .count(),
+            })
+            .collect(),
+        unparsed: lines.iter().filter(|line| line.error.is_some()).count(),
+        lines,
+    }
+}
+
+impl Audit {
+    pub fn write_json<W: Write>(&self, out: W) -> Result<(), Error> {
+        serde_json::to_writer_pretty(out, self).map_err(write_error)
+    }
+
+    /// One row per line and policy, or per unparsable line with the error
+    /// as the reason and no policy.
+    pub fn write_csv<W: Write>(&self, out: W) -> Result<(), Error> {
+        let mut writer = csv::Writer::from_writer(out);
+
+        writer
+            .write_record(["line", "text", "parsed", "policy", "passes", "reason"])
+            .map_err(write_error)?;
+        for line in &self.lines {
+            if let Some(reason) = &line.error {
+                writer
+                    .serialize((line.line, &line.text, false, "", false, reason))
+                    .map_err(write_error)?;
+            }
+            for check in &line.checks {
+                writer
+                    .serialize((
+                        line.line,
+                        &line.text,
+                        true,
+                        &check.policy,
+                        check.passes,
+                        check.reason.as_deref().unwrap_or(""),
+                    ))
+                    .map_err(write_error)?;
+            }
+        }
+
+        writer.flush().map_err(write_error)
+    }
+}
+
+fn write_error<E: std::fmt::Display>(error: E) -> Error {
+    Error::new(format!("cannot write the audit: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Count, Positions};
+
+    const INPUT: &str = "1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\nbad line\n";
+
+    fn sample() -> Audit {
+        audit(INPUT, &[Box::new(Count), Box::new(Positions)])
+    }
+
+    #[test]
+    fn lines() {
+        let audit = sample();
+
+        assert_eq!(
+            audit.policies,
+            vec![
+                Summary {
+                    policy: "count".to_string(),
+                    valid: 2
+                },
+                Summary {
+                    policy: "positions".to_string(),
+                    valid: 1
+                }
+            ]
+        );
+        assert_eq!(audit.unparsed, 1);
+        assert_eq!(
+            audit.lines[1].checks[0].reason.as_deref(),
+            Some("found 0 'b', allowed 1-3")
+        );
+        assert_eq!(
+            audit.lines[3].error.as_deref(),
+            Some("line 4, column 1: expecting `<min>-<max> <letter>: <password>`: \"bad line\"")
+        );
+        assert!(audit.lines[3].checks.is_empty());
+    }
+
+    #[test]
+    fn csv() {
+        let mut out = Vec::new();
+        sample().write_csv(&mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let rows = csv.lines().collect::<Vec<_>>();
+
+        assert_eq!(rows.len(), 1 + 3 * 2 + 1);
+        assert_eq!(rows[0], "line,text,parsed,policy,passes,reason");
+        assert_eq!(
+            rows[3],
+            "2,1-3 b: cdefg,true,count,false,\"found 0 'b', allowed 1-3\""
+        );
+        assert!(rows[7].starts_with("4,bad line,false,,false,"));
+    }
+
+    #[test]
+    fn json() {
+        let mut out = Vec::new();
+        sample().write_json(&mut out).unwrap();
+        let json = serde_json::from_slice::<serde_json::Value>(&out).unwrap();
+
+        assert_eq!(json["unparsed"], 1);
+        assert_eq!(json["policies"][1]["valid"], 1);
+        assert_eq!(
+            json["lines"][2]["checks"][1]["reason"],
+            "'c' at both positions 2 and 9"
+        );
+        assert_eq!(json["lines"][0]["checks"][0]["passes"], true);
+    }
+}
@@ -0,0 +1,40 @@
+use common::Rng;
+
+/// `size` password lines, with the policy letter over-represented in the
+/// passwords so that both policies accept some of them.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let p1 = rng.range(1, 10);
+            let p2 = rng.range(p1 + 1, p1 + 10);
+            let c = (b'a' + rng.below(26) as u8) as char;
+            let password = (0..rng.range(p1, p2 + 5))
+                .map(|_| {
+                    if rng.chance(0.4) {
+                        c
+                    } else {
+                        (b'a' + rng.below(26) as u8) as char
+                    }
+                })
+                .collect::<String>();
+
+            format!("{}-{} {}: {}\n", p1, p2, c, password)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_input() {
+        for seed in 0..10 {
+            let input = crate::parse(&generate(&mut Rng::new(seed), 500)).unwrap();
+
+            assert_eq!(input.len(), 500);
+            assert!((1..500).contains(&crate::solve_1(&input)));
+            assert!((1..500).contains(&crate::solve_2(&input)));
+        }
+    }
+}
@@ -1,13 +1,37 @@
-#![feature(test)]
+#![cfg_attr(feature = "bench", feature(test))]
+
+#[cfg(feature = "bench")]
 extern crate test;
 
+#[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
 
+use common::{Answer, Error, Solution};
 use regex::Regex;
-use std::{num::ParseIntError, str::FromStr};
+use std::str::FromStr;
+
+mod audit;
+mod generate;
+mod policy;
+
+pub use audit::{audit, Audit, Check, Line, Summary};
+pub use generate::generate;
+pub use policy::{
+    policies, policy, All, Any, Class, Count, Forbids, MaxLength, MinLength, Not, Policy,
+    Positions, Requires,
+};
+
+/// A regex compiled on first use.
+macro_rules! regex {
+    ($pattern:expr) => {{
+        static RE: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+        RE.get_or_init(|| Regex::new($pattern).unwrap())
+    }};
+}
 
-struct Password {
+#[derive(Debug)]
+pub struct Password {
     p1: usize,
     p2: usize,
     c: char,
@@ -15,95 +39,140 @@ struct Password {
 }
 
 impl Password {
-    fn is_valid_1(&self) -> bool {
-        let count = self.password.chars().filter(|&c| c == self.c).count();
-        count >= self.p1 && count <= self.p2
+    /// The two numbers before the letter.
+    pub fn numbers(&self) -> (usize, usize) {
+        (self.p1, self.p2)
     }
 
-    fn is_valid_2(&self) -> bool {
-        self.password
-            .char_indices()
-            .filter(|&(i, _)| i == self.p1 - 1 || i == self.p2 - 1)
-            .filter(|&(_, c)| c == self.c)
-            .count()
-            == 1
+    pub fn letter(&self) -> char {
+        self.c
     }
-}
 
-#[derive(Debug)]
-struct ParseError {
-    error: &'static str,
-}
-
-impl From<ParseIntError> for ParseError {
-    fn from(_: ParseIntError) -> Self {
-        Self {
-            error: "invalid number",
-        }
+    pub fn password(&self) -> &str {
+        &self.password
     }
 }
 
 impl FromStr for Password {
-    type Err = ParseError;
+    type Err = Error;
 
     fn from_str(line: &str) -> Result<Self, Self::Err> {
-        match RE.captures(line) {
-            Some(cap) => Ok(Self {
-                p1: cap[1].parse()?,
-                p2: cap[2].parse()?,
-                c: cap[3].chars().next().ok_or(ParseError {
-                    error: "expecting a letter",
-                })?,
-                password: cap[4].to_string(),
-            }),
-            _ => Err(ParseError {
-                error: "invalid format",
-            }),
-        }
+        let cap = regex!(r"^(\d+)-(\d+) (.): (.*)$")
+            .captures(line)
+            .ok_or_else(|| Error::at(line, line, "expecting `<min>-<max> <letter>: <password>`"))?;
+        let number = |i| {
+            let m = cap.get(i).map_or("", |m| m.as_str());
+            m.parse().map_err(|_| Error::at(line, m, "invalid number"))
+        };
+
+        Ok(Self {
+            p1: number(1)?,
+            p2: number(2)?,
+            c: cap[3].chars().next().unwrap_or_default(),
+            password: cap[4].to_string(),
+        })
     }
 }
 
-lazy_static! {
-    static ref INPUT: Vec<&'static str> = include_str!("../input").lines().collect();
-    static ref RE: Regex = Regex::new(r"^(\d+)-(\d+) ([a-z]): ([a-z]+)$").unwrap();
+/// The embedded puzzle input, parsed on first use.
+#[cfg(feature = "input")]
+fn input() -> &'static Vec<Password> {
+    static INPUT: std::sync::OnceLock<Vec<Password>> = std::sync::OnceLock::new();
+
+    INPUT.get_or_init(|| parse(include_str!("../input")).expect("invalid input"))
+}
+
+fn parse(input: &str) -> Result<Vec<Password>, Error> {
+    input
+        .lines()
+        .map(|line| line.parse().map_err(|e: Error| e.within(input, line)))
+        .collect()
 }
 
-fn solve<P: FnMut(&Password) -> bool>(input: &[&str], test: P) -> usize {
+/// The number of passwords valid under `policy`.
+pub fn solve<P: Policy + ?Sized>(input: &[Password], policy: &P) -> usize {
     input
         .iter()
-        .map(|line| line.parse().expect("invalid input"))
-        .filter(test)
+        .filter(|password| policy.is_valid(password))
         .count()
 }
 
-fn solve_1(input: &[&str]) -> usize {
-    solve(input, Password::is_valid_1)
+fn solve_1(input: &[Password]) -> usize {
+    solve(input, &Count)
 }
 
-fn solve_2(input: &[&str]) -> usize {
-    solve(input, Password::is_valid_2)
+fn solve_2(input: &[Password]) -> usize {
+    solve(input, &Positions)
 }
 
+#[cfg(feature = "input")]
 pub fn part_1() -> usize {
-    solve_1(&INPUT)
+    solve_1(input())
 }
 
+#[cfg(feature = "input")]
 pub fn part_2() -> usize {
-    solve_2(&INPUT)
+    solve_2(input())
+}
+
+pub fn solve_part_1(input: &str) -> Result<usize, Error> {
+    parse(input)
+        .map(|input| solve_1(&input))
+        .map_err(|e| e.in_day(Day02::DAY))
+}
+
+pub fn solve_part_2(input: &str) -> Result<usize, Error> {
+    parse(input)
+        .map(|input| solve_2(&input))
+        .map_err(|e| e.in_day(Day02::DAY))
+}
+
+pub struct Day02;
+
+impl Solution for Day02 {
+    const DAY: u32 = 2;
+
+    type Input<'a> = Vec<Password>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+        parse(input).map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+        Ok(solve_1(input).into())
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+        Ok(Some(solve_2(input).into()))
+    }
+
+    const COMMANDS: &'static [(&'static str, &'static str)] = &[(
+        "policy <policy>",
+        "the number of passwords a policy accepts, e.g. `policy all(count, min-length(8))`",
+    )];
+
+    fn explore(
+        input: &mut Self::Input<'_>,
+        command: &str,
+        args: &[&str],
+    ) -> Option<Result<String, Error>> {
+        if command != "policy" {
+            return None;
+        }
+
+        Some(policy(&args.join(" ")).map(|policy| solve(input, policy.as_ref()).to_string()))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     lazy_static! {
-        static ref INPUT: Vec<&'static str> =
-            vec!["1-3 a: abcde", "1-3 b: cdefg", "2-9 c: ccccccccc",];
-        static ref INPUT_PARSED: Vec<Password> = super::INPUT
-            .iter()
-            .map(|line| line.parse().expect("invalid input"))
-            .collect();
+        static ref INPUT: Vec<Password> =
+            parse("1-3 a: abcde\n1-3 b: cdefg\n2-9 c: ccccccccc\n").expect("invalid input");
     }
 
     #[test]
@@ -116,32 +185,44 @@ mod tests {
         assert_eq!(solve_2(&INPUT), 1);
     }
 
+    #[test]
+    fn parse_error_position() {
+        let error = parse("1-3 a: abcde\n1-99999999999999999999 b: cdefg").unwrap_err();
+
+        assert_eq!(error.position, Some((2, 3)));
+        assert_eq!(error.message, "invalid number");
+    }
+
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_1(b: &mut Bencher) {
         b.iter(part_1);
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_2(b: &mut Bencher) {
         b.iter(part_2);
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_sample_1_bis(b: &mut Bencher) {
         b.iter(|| {
-            INPUT_PARSED
+            super::input()
                 .iter()
-                .filter(|password| password.is_valid_1())
+                .filter(|password| Count.is_valid(password))
                 .count();
         });
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_sample_2_bis(b: &mut Bencher) {
         b.iter(|| {
-            INPUT_PARSED
+            super::input()
                 .iter()
-                .filter(|password| password.is_valid_2())
+                .filter(|password| Positions.is_valid(password))
                 .count();
         });
     }
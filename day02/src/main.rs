@@ -1,17 +1,71 @@
-use std::time::Instant;
+use common::Solution;
+use day02::{Day02, Policy};
 
-use day02::{part_1, part_2};
+fn exit(message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1);
+}
 
+/// With `--policy` or `--config`, prints how many passwords each policy
+/// accepts instead of solving the puzzle; with `--audit`, how every line
+/// fares with each of them, the two of the puzzle by default.
 fn main() {
-    let now = Instant::now();
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if !args
+        .iter()
+        .any(|arg| arg == "--policy" || arg == "--config" || arg == "--audit")
+    {
+        common::main::<Day02>(include_str!("../input"));
+        return;
+    }
+
+    let usage = "usage: day02 [--input <path>|-] [--audit json|csv] \
+                 (--policy <policy>|--config <path>)...";
+    let (mut path, mut audit, mut policies) = (None, None, Vec::<Box<dyn Policy>>::new());
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--input", Some(value)) => path = Some(value),
+            ("--audit", Some(value)) if value == "json" || value == "csv" => audit = Some(value),
+            ("--policy", Some(value)) => match day02::policy(&value) {
+                Ok(policy) => policies.push(policy),
+                Err(error) => exit(&error.to_string()),
+            },
+            ("--config", Some(value)) => {
+                let config = common::read_input(&value).unwrap_or_else(|error| exit(&error));
+                match day02::policies(&config) {
+                    Ok(config) => policies.extend(config),
+                    Err(error) => exit(&format!("{}: {}", value, error)),
+                }
+            }
+            _ => exit(usage),
+        }
+    }
+
+    let owned = path.map(|path| common::read_input(&path).unwrap_or_else(|error| exit(&error)));
+    let input = owned.as_deref().unwrap_or(include_str!("../input"));
+
+    if let Some(format) = audit {
+        if policies.is_empty() {
+            policies = vec![Box::new(day02::Count), Box::new(day02::Positions)];
+        }
+
+        let audit = day02::audit(input, &policies);
+        let out = std::io::stdout().lock();
+        let result = if format == "json" {
+            audit.write_json(out).map(|_| println!())
+        } else {
+            audit.write_csv(out)
+        };
+        if let Err(error) = result {
+            exit(&error.to_string());
+        }
+        return;
+    }
 
-    println!("part 1: {}", part_1());
-    println!("part 2: {}", part_2());
+    let input = Day02::parse(input).unwrap_or_else(|error| exit(&error.to_string()));
 
-    let elapsed = now.elapsed();
-    println!(
-        "elapsed: {}ms ({}ns)",
-        elapsed.as_millis(),
-        elapsed.as_nanos(),
-    );
+    for policy in policies {
+        println!("{}: {}", policy, day02::solve(&input, policy.as_ref()));
+    }
 }
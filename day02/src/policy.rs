@@ -0,0 +1,550 @@
+use crate::Password;
+use common::Error;
+use std::fmt;
+
+/// A rule a password line must follow. Its `Display` is the text `policy`
+/// parses it back from.
+pub trait Policy: fmt::Display {
+    /// Why `password` breaks the policy, if it does.
+    fn check(&self, password: &Password) -> Result<(), String>;
+
+    fn is_valid(&self, password: &Password) -> bool {
+        self.check(password).is_ok()
+    }
+}
+
+/// The policy of part 1: the letter appears between `min` and `max` times.
+pub struct Count;
+
+/// The policy of part 2: the letter is at exactly one of the two (1-based)
+/// positions, counted in characters.
+pub struct Positions;
+
+/// At least that many characters.
+pub struct MinLength(pub usize);
+
+/// At most that many characters.
+pub struct MaxLength(pub usize);
+
+/// At least one character of the class.
+pub struct Requires(pub Class);
+
+/// Not containing the text.
+pub struct Forbids(pub String);
+
+/// Every one of the policies.
+pub struct All(pub Vec<Box<dyn Policy>>);
+
+/// At least one of the policies.
+pub struct Any(pub Vec<Box<dyn Policy>>);
+
+/// Breaking the policy.
+pub struct Not(pub Box<dyn Policy>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Class {
+    Lowercase,
+    Uppercase,
+    Digit,
+    /// Neither alphanumeric nor whitespace.
+    Symbol,
+}
+
+impl Class {
+    const ALL: [(Class, &'static str); 4] = [
+        (Class::Lowercase, "lowercase"),
+        (Class::Uppercase, "uppercase"),
+        (Class::Digit, "digit"),
+        (Class::Symbol, "symbol"),
+    ];
+
+    pub fn contains(self, c: char) -> bool {
+        match self {
+            Class::Lowercase => c.is_lowercase(),
+            Class::Uppercase => c.is_uppercase(),
+            Class::Digit => c.is_numeric(),
+            Class::Symbol => !c.is_alphanumeric() && !c.is_whitespace(),
+        }
+    }
+}
+
+impl fmt::Display for Class {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let (_, name) = Class::ALL.iter().find(|(class, _)| class == self).unwrap();
+        fmt.write_str(name)
+    }
+}
+
+impl Policy for Count {
+    fn check(&self, password: &Password) -> Result<(), String> {
+        let (min, max) = password.numbers();
+        let count = password
+            .password()
+            .chars()
+            .filter(|&c| c == password.letter())
+            .count();
+
+        if count >= min && count <= max {
+            Ok(())
+        } else {
+            Err(format!(
+                "found {} '{}', allowed {}-{}",
+                count,
+                password.letter(),
+                min,
+                max
+            ))
+        }
+    }
+}
+
+impl Policy for Positions {
+    fn check(&self, password: &Password) -> Result<(), String> {
+        let (p1, p2) = password.numbers();
+        let at = |position: usize| {
+            position > 0 && password.password().chars().nth(position - 1) == Some(password.letter())
+        };
+
+        match (at(p1), at(p2)) {
+            (true, false) | (false, true) => Ok(()),
+            (true, true) => Err(format!(
+                "'{}' at both positions {} and {}",
+                password.letter(),
+                p1,
+                p2
+            )),
+            (false, false) => Err(format!(
+                "'{}' at neither position {} nor {}",
+                password.letter(),
+                p1,
+                p2
+            )),
+        }
+    }
+}
+
+impl Policy for MinLength {
+    fn check(&self, password: &Password) -> Result<(), String> {
+        let length = password.password().chars().count();
+        if length >= self.0 {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} characters, expecting at least {}",
+                length, self.0
+            ))
+        }
+    }
+}
+
+impl Policy for MaxLength {
+    fn check(&self, password: &Password) -> Result<(), String> {
+        let length = password.password().chars().count();
+        if length <= self.0 {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} characters, expecting at most {}",
+                length, self.0
+            ))
+        }
+    }
+}
+
+impl Policy for Requires {
+    fn check(&self, password: &Password) -> Result<(), String> {
+        if password.password().chars().any(|c| self.0.contains(c)) {
+            Ok(())
+        } else {
+            Err(format!("no {} character", self.0))
+        }
+    }
+}
+
+impl Policy for Forbids {
+    fn check(&self, password: &Password) -> Result<(), String> {
+        if password.password().contains(&self.0) {
+            Err(format!("contains \"{}\"", self.0))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Policy for All {
+    fn check(&self, password: &Password) -> Result<(), String> {
+        let reasons = self
+            .0
+            .iter()
+            .filter_map(|policy| policy.check(password).err())
+            .collect::<Vec<_>>();
+
+        if reasons.is_empty() {
+            Ok(())
+        } else {
+            Err(reasons.join("; "))
+        }
+    }
+}
+
+impl Policy for Any {
+    fn check(&self, password: &Password) -> Result<(), String> {
+        let mut reasons = Vec::new();
+        for policy in &self.0 {
+            match policy.check(password) {
+                Ok(()) => return Ok(()),
+                Err(reason) => reasons.push(reason),
+            }
+        }
+
+        Err(format!("none passes ({})", reasons.join("; ")))
+    }
+}
+
+impl Policy for Not {
+    fn check(&self, password: &Password) -> Result<(), String> {
+        match self.0.check(password) {
+            Ok(()) => Err(format!("passes {}", self.0)),
+            Err(_) => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for Count {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("count")
+    }
+}
+
+impl fmt::Display for Positions {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str("positions")
+    }
+}
+
+impl fmt::Display for MinLength {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "min-length({})", self.0)
+    }
+}
+
+impl fmt::Display for MaxLength {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "max-length({})", self.0)
+    }
+}
+
+impl fmt::Display for Requires {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "requires({})", self.0)
+    }
+}
+
+impl fmt::Display for Forbids {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "forbids(\"{}\")", self.0)
+    }
+}
+
+/// `name(p1, p2, ...)`.
+fn list(fmt: &mut fmt::Formatter, name: &str, policies: &[Box<dyn Policy>]) -> fmt::Result {
+    write!(fmt, "{}(", name)?;
+    for (i, policy) in policies.iter().enumerate() {
+        if i > 0 {
+            fmt.write_str(", ")?;
+        }
+        write!(fmt, "{}", policy)?;
+    }
+    fmt.write_str(")")
+}
+
+impl fmt::Display for All {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        list(fmt, "all", &self.0)
+    }
+}
+
+impl fmt::Display for Any {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        list(fmt, "any", &self.0)
+    }
+}
+
+impl fmt::Display for Not {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "not({})", self.0)
+    }
+}
+
+/// Parses a policy written as its `Display`:
+///
+/// - `count` and `positions`, the policies of the two parts;
+/// - `min-length(8)` and `max-length(20)`;
+/// - `requires(digit)`, of `lowercase`, `uppercase`, `digit` or `symbol`;
+/// - `forbids("abc")`, of any text without a double quote;
+/// - `all(...)`, `any(...)` of one or more policies, and `not(...)`, nested
+///   at most 64 deep.
+pub fn policy(text: &str) -> Result<Box<dyn Policy>, Error> {
+    let mut parser = Parser { text, rest: text };
+    let policy = parser.policy(0)?;
+
+    parser.skip_whitespace();
+    if !parser.rest.is_empty() {
+        return Err(parser.error(parser.rest, "unexpected text after the policy"));
+    }
+    Ok(policy)
+}
+
+/// Parses a configuration of one policy per line, skipping blank lines and
+/// those starting with `#`.
+pub fn policies(config: &str) -> Result<Vec<Box<dyn Policy>>, Error> {
+    config
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|line| policy(line).map_err(|e| e.within(config, line)))
+        .collect()
+}
+
+/// The deepest nesting of `all`, `any` and `not` accepted, so that parsing
+/// and checking a policy cannot overflow the stack.
+const MAX_DEPTH: usize = 64;
+
+struct Parser<'a> {
+    text: &'a str,
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, snippet: &str, message: &str) -> Error {
+        Error::at(self.text, snippet, message)
+    }
+
+    /// The next character, or the end of the text, as a snippet.
+    fn next(&self) -> &'a str {
+        &self.rest[..self.rest.chars().next().map_or(0, char::len_utf8)]
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.rest = self.rest.trim_start();
+    }
+
+    fn expect(&mut self, c: char, message: &str) -> Result<(), Error> {
+        self.skip_whitespace();
+        match self.rest.strip_prefix(c) {
+            Some(rest) => {
+                self.rest = rest;
+                Ok(())
+            }
+            None => Err(self.error(self.next(), message)),
+        }
+    }
+
+    /// A run of letters, digits and dashes.
+    fn word(&mut self) -> &'a str {
+        self.skip_whitespace();
+        let end = self
+            .rest
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+            .unwrap_or(self.rest.len());
+        let (word, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        word
+    }
+
+    /// `depth` is the number of `all`, `any` and `not` the policy is in.
+    fn policy(&mut self, depth: usize) -> Result<Box<dyn Policy>, Error> {
+        let name = self.word();
+        let policy: Box<dyn Policy> = match name {
+            "count" => return Ok(Box::new(Count)),
+            "positions" => return Ok(Box::new(Positions)),
+            "all" | "any" | "not" if depth == MAX_DEPTH => {
+                return Err(self.error(name, "policy nested too deeply"))
+            }
+            "min-length" | "max-length" | "requires" | "forbids" | "all" | "any" | "not" => {
+                self.expect('(', "expecting (")?;
+                match name {
+                    "min-length" => Box::new(MinLength(self.number()?)),
+                    "max-length" => Box::new(MaxLength(self.number()?)),
+                    "requires" => Box::new(Requires(self.class()?)),
+                    "forbids" => Box::new(Forbids(self.string()?)),
+                    "all" => Box::new(All(self.list(depth + 1)?)),
+                    "any" => Box::new(Any(self.list(depth + 1)?)),
+                    _ => Box::new(Not(self.policy(depth + 1)?)),
+                }
+            }
+            "" => return Err(self.error(self.next(), "expecting a policy")),
+            _ => return Err(self.error(name, "unknown policy")),
+        };
+
+        self.expect(')', "expecting )")?;
+        Ok(policy)
+    }
+
+    fn number(&mut self) -> Result<usize, Error> {
+        let word = self.word();
+        word.parse().map_err(|_| {
+            self.error(
+                if word.is_empty() { self.next() } else { word },
+                "invalid number",
+            )
+        })
+    }
+
+    fn class(&mut self) -> Result<Class, Error> {
+        let word = self.word();
+        Class::ALL
+            .iter()
+            .find(|(_, name)| *name == word)
+            .map(|&(class, _)| class)
+            .ok_or_else(|| {
+                self.error(
+                    if word.is_empty() { self.next() } else { word },
+                    "expecting lowercase, uppercase, digit or symbol",
+                )
+            })
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        self.expect('"', "expecting a quoted text")?;
+        let end = self
+            .rest
+            .find('"')
+            .ok_or_else(|| self.error(self.rest, "missing closing \""))?;
+        let (string, rest) = self.rest.split_at(end);
+        if string.is_empty() {
+            return Err(self.error(&rest[..1], "expecting a non-empty text"));
+        }
+        self.rest = &rest[1..];
+        Ok(string.to_string())
+    }
+
+    /// Policies separated by commas, up to the closing parenthesis.
+    fn list(&mut self, depth: usize) -> Result<Vec<Box<dyn Policy>>, Error> {
+        let mut policies = vec![self.policy(depth)?];
+        loop {
+            self.skip_whitespace();
+            if self.rest.starts_with(')') {
+                return Ok(policies);
+            }
+            self.expect(',', "expecting , or )")?;
+            policies.push(self.policy(depth)?);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn password(line: &str) -> Password {
+        line.parse().unwrap()
+    }
+
+    #[test]
+    fn reasons() {
+        let line = password("1-3 a: aaaaab");
+        assert_eq!(
+            Count.check(&line),
+            Err("found 5 'a', allowed 1-3".to_string())
+        );
+        assert_eq!(
+            Positions.check(&line),
+            Err("'a' at both positions 1 and 3".to_string())
+        );
+        assert_eq!(
+            MinLength(8).check(&line),
+            Err("6 characters, expecting at least 8".to_string())
+        );
+        assert_eq!(
+            Requires(Class::Digit).check(&line),
+            Err("no digit character".to_string())
+        );
+        assert_eq!(
+            Forbids("ab".to_string()).check(&line),
+            Err("contains \"ab\"".to_string())
+        );
+        assert_eq!(
+            Not(Box::new(MaxLength(6))).check(&line),
+            Err("passes max-length(6)".to_string())
+        );
+    }
+
+    #[test]
+    fn utf8() {
+        let line = password("2-3 é: çéé€ 1");
+        assert!(Count.is_valid(&line));
+        assert!(!Positions.is_valid(&line));
+        assert!(MaxLength(6).is_valid(&line));
+        assert!(Requires(Class::Symbol).is_valid(&line));
+        assert!(!Requires(Class::Uppercase).is_valid(&line));
+    }
+
+    #[test]
+    fn round_trip() {
+        let text = "all(count, any(min-length(8), requires(digit)), forbids(\"pass\"), \
+                    not(requires(uppercase)))";
+        let policy = policy(text).unwrap();
+        assert_eq!(policy.to_string(), text);
+
+        assert!(policy.is_valid(&password("1-3 a: abc1")));
+        assert_eq!(
+            policy.check(&password("1-3 a: Pass")),
+            Err(
+                "none passes (4 characters, expecting at least 8; no digit character); \
+                 passes requires(uppercase)"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = |text| policy(text).map(|_| ()).unwrap_err();
+
+        let e = error("all(count, lenght(3))");
+        assert_eq!(
+            (e.position, e.message.as_str()),
+            (Some((1, 12)), "unknown policy")
+        );
+        let e = error("all(count positions)");
+        assert_eq!(
+            (e.position, e.message.as_str()),
+            (Some((1, 11)), "expecting , or )")
+        );
+        let e = error("requires(digits)");
+        assert_eq!(e.snippet, "digits");
+        let e = error("count)");
+        assert_eq!(e.message, "unexpected text after the policy");
+        let e = error("forbids(\"abc)");
+        assert_eq!(e.message, "missing closing \"");
+        let e = error("not(");
+        assert_eq!(e.message, "expecting a policy");
+    }
+
+    #[test]
+    fn too_deep() {
+        let nested = |depth| format!("{}count{}", "not(".repeat(depth), ")".repeat(depth));
+        assert!(policy(&nested(MAX_DEPTH)).is_ok());
+
+        let e = policy(&nested(200_000)).map(|_| ()).unwrap_err();
+        assert_eq!(
+            (e.position, e.message.as_str()),
+            (Some((1, 4 * MAX_DEPTH + 1)), "policy nested too deeply")
+        );
+        let e = policy(&format!("all(count, {})", nested(MAX_DEPTH)))
+            .map(|_| ())
+            .unwrap_err();
+        assert_eq!(e.message, "policy nested too deeply");
+    }
+
+    #[test]
+    fn config() {
+        let config = "# part 1\ncount\n\n  positions\nmin-length(x)\n";
+        let error = policies(config).map(|_| ()).unwrap_err();
+        assert_eq!(error.position, Some((5, 12)));
+
+        let policies = policies(&config[..config.len() - 14]).unwrap();
+        assert_eq!(policies.len(), 2);
+        assert_eq!(policies[1].to_string(), "positions");
+    }
+}
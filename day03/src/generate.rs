@@ -0,0 +1,34 @@
+use common::Rng;
+
+const WIDTH: u64 = 31;
+
+/// A map of `size` rows of 31 squares, about a fifth of them trees.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            (0..WIDTH)
+                .map(|_| if rng.chance(0.2) { '#' } else { '.' })
+                .chain(std::iter::once('\n'))
+                .collect::<String>()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_input() {
+        for seed in 0..10 {
+            let tree_map = crate::TreeMap::new(&generate(&mut Rng::new(seed), 1000)).unwrap();
+
+            assert_eq!(
+                (tree_map.0.width(), tree_map.0.height()),
+                (WIDTH as usize, 1000)
+            );
+            assert!(crate::solve_1(&tree_map) > 0);
+            assert!(crate::solve_2(&tree_map) > 0);
+        }
+    }
+}
@@ -1,71 +1,150 @@
-#![feature(test)]
+#![cfg_attr(feature = "bench", feature(test))]
+
+#[cfg(feature = "bench")]
 extern crate test;
 
+#[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
 
-lazy_static! {
-    static ref INPUT: TreeMap = TreeMap::new(include_str!("../input"));
-}
+use common::{Answer, Error, Picture, Raster, Rgb, Solution};
+use grid::Grid;
+
+mod generate;
+
+pub use generate::generate;
 
-struct TreeMap {
-    map: Vec<Vec<bool>>,
-    x_dim: usize,
-    y_dim: usize,
+/// The embedded puzzle input, parsed on first use.
+#[cfg(feature = "input")]
+fn input() -> &'static TreeMap {
+    static INPUT: std::sync::OnceLock<TreeMap> = std::sync::OnceLock::new();
+
+    INPUT.get_or_init(|| TreeMap::new(include_str!("../input")).expect("invalid input"))
 }
 
-impl TreeMap {
-    fn new(input: &str) -> Self {
-        let map: Vec<Vec<bool>> = input
-            .lines()
-            .map(|line| line.chars().map(|c| c == '#').collect())
-            .collect();
+pub struct TreeMap(Grid<bool>);
 
-        let x_dim = map[0].len();
-        let y_dim = map.len();
+impl TreeMap {
+    fn new(input: &str) -> Result<Self, Error> {
+        Grid::parse(input, "expecting '.' or '#'", |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })
+        .map(Self)
+    }
 
-        Self { map, x_dim, y_dim }
+    /// The squares the toboggan goes through on a slope, after the first.
+    fn path(&self, (dx, dy): (usize, usize)) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (1..)
+            .map(move |i| ((i * dx) % self.0.width(), i * dy))
+            .take_while(move |&(_, y)| y < self.0.height())
     }
 
-    fn walk(&self, (dx, dy): (usize, usize)) -> usize {
-        let (mut x, mut y) = (0, 0);
-        let mut count = 0;
+    fn walk(&self, slope: (usize, usize)) -> usize {
+        self.path(slope)
+            .filter(|&position| self.0[position])
+            .count()
+    }
 
-        while y + dy < self.y_dim {
-            x = (x + dx) % self.x_dim;
-            y += dy;
-            if self.map[y][x] {
-                count += 1;
+    /// The map with the paths of `slopes` in colour, and the trees they hit
+    /// in red.
+    fn picture(&self, slopes: &[(usize, usize)]) -> Raster {
+        const COLOURS: [Rgb; 5] = [
+            (66, 133, 244),
+            (251, 188, 5),
+            (171, 71, 188),
+            (0, 172, 193),
+            (255, 112, 67),
+        ];
+
+        let mut raster = Raster::new(self.0.width(), self.0.height());
+        for (position, &tree) in self.0.positions() {
+            if tree {
+                raster.set(position, (27, 94, 32));
+            }
+        }
+        for (slope, &colour) in slopes.iter().zip(COLOURS.iter().cycle()) {
+            for position in self.path(*slope) {
+                raster.set(
+                    position,
+                    if self.0[position] {
+                        (229, 57, 53)
+                    } else {
+                        colour
+                    },
+                );
             }
         }
 
-        count
+        raster
     }
 }
 
+const SLOPE: (usize, usize) = (3, 1);
+
+const SLOPES: [(usize, usize); 5] = [(1, 1), (3, 1), (5, 1), (7, 1), (1, 2)];
+
 fn solve_1(tree_map: &TreeMap) -> usize {
-    tree_map.walk((3, 1))
+    tree_map.walk(SLOPE)
 }
 
 fn solve_2(tree_map: &TreeMap) -> usize {
-    tree_map.walk((1, 1))
-        * tree_map.walk((3, 1))
-        * tree_map.walk((5, 1))
-        * tree_map.walk((7, 1))
-        * tree_map.walk((1, 2))
+    SLOPES.iter().map(|&slope| tree_map.walk(slope)).product()
 }
 
+#[cfg(feature = "input")]
 pub fn part_1() -> usize {
-    solve_1(&INPUT)
+    solve_1(input())
 }
 
+#[cfg(feature = "input")]
 pub fn part_2() -> usize {
-    solve_2(&INPUT)
+    solve_2(input())
+}
+
+pub fn solve_part_1(input: &str) -> Result<usize, Error> {
+    TreeMap::new(input)
+        .map(|input| solve_1(&input))
+        .map_err(|e| e.in_day(Day03::DAY))
+}
+
+pub fn solve_part_2(input: &str) -> Result<usize, Error> {
+    TreeMap::new(input)
+        .map(|input| solve_2(&input))
+        .map_err(|e| e.in_day(Day03::DAY))
+}
+
+pub struct Day03;
+
+impl Solution for Day03 {
+    const DAY: u32 = 3;
+
+    type Input<'a> = TreeMap;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+        TreeMap::new(input).map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+        Ok(solve_1(input).into())
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+        Ok(Some(solve_2(input).into()))
+    }
+
+    fn picture(input: &Self::Input<'_>, part: usize) -> Option<Result<Picture, Error>> {
+        let slopes = if part == 1 { &[SLOPE][..] } else { &SLOPES[..] };
+
+        Some(Ok(Picture::Raster(input.picture(slopes))))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     lazy_static! {
@@ -81,7 +160,8 @@ mod tests {
 #.##...#...
 #...##....#
 .#..#...#.#"
-        );
+        )
+        .expect("invalid input");
     }
 
     #[test]
@@ -94,11 +174,31 @@ mod tests {
         assert_eq!(solve_2(&INPUT), 336);
     }
 
+    #[test]
+    fn ragged_map() {
+        let error = TreeMap::new("..#\n.#\n").map(drop).expect_err("ragged map");
+
+        assert_eq!(error.position, Some((2, 1)));
+    }
+
+    #[test]
+    fn path_picture() {
+        let raster = INPUT.picture(&[SLOPE]);
+
+        assert_eq!((raster.width(), raster.height()), (11, 11));
+        assert_eq!(raster.get((3, 1)), Some((66, 133, 244)));
+        assert_eq!(raster.get((6, 2)), Some((229, 57, 53)));
+        assert_eq!(raster.get((2, 0)), Some((27, 94, 32)));
+        assert_eq!(raster.get((1, 0)), Some(common::WHITE));
+    }
+
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_1(b: &mut Bencher) {
         b.iter(part_1);
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_2(b: &mut Bencher) {
         b.iter(part_2);
@@ -0,0 +1,95 @@
+use common::Rng;
+
+const COLORS: [&str; 7] = ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
+
+fn digits(rng: &mut Rng, n: usize) -> String {
+    (0..n)
+        .map(|_| (b'0' + rng.below(10) as u8) as char)
+        .collect()
+}
+
+fn hex(rng: &mut Rng) -> String {
+    (0..6)
+        .map(|_| *rng.choose(b"0123456789abcdef") as char)
+        .collect()
+}
+
+fn year(rng: &mut Rng, valid: bool, low: u64, high: u64) -> String {
+    if valid {
+        rng.range(low, high).to_string()
+    } else if rng.chance(0.5) {
+        rng.range(low - 10, low - 1).to_string()
+    } else {
+        rng.range(high + 1, high + 10).to_string()
+    }
+}
+
+fn value(rng: &mut Rng, field: &str, valid: bool) -> String {
+    match field {
+        "byr" => year(rng, valid, 1920, 2002),
+        "iyr" => year(rng, valid, 2010, 2020),
+        "eyr" => year(rng, valid, 2020, 2030),
+        "hgt" => match (valid, rng.chance(0.5)) {
+            (true, true) => format!("{}cm", rng.range(150, 193)),
+            (true, false) => format!("{}in", rng.range(59, 76)),
+            (false, true) => format!("{}in", rng.range(150, 193)),
+            (false, false) => rng.range(59, 193).to_string(),
+        },
+        "hcl" if valid => format!("#{}", hex(rng)),
+        "hcl" => hex(rng),
+        "ecl" if valid => rng.choose(&COLORS).to_string(),
+        "ecl" => rng.choose(&["xry", "zzz", "gmt", "utc"]).to_string(),
+        "pid" if valid => digits(rng, 9),
+        "pid" => {
+            let n = *rng.choose(&[8, 10]);
+            digits(rng, n)
+        }
+        _ => rng.range(100, 350).to_string(),
+    }
+}
+
+/// `size` passports: some lack a required field, some have invalid values,
+/// and `cid` comes and goes, with fields spread over one to four lines.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let mut fields = crate::FIELDS.to_vec();
+            if rng.chance(0.5) {
+                fields.pop();
+            }
+            if rng.chance(0.15) {
+                fields.remove(rng.below(7) as usize);
+            }
+            rng.shuffle(&mut fields);
+
+            let mut passport = String::new();
+            for (i, field) in fields.iter().enumerate() {
+                if i > 0 {
+                    passport.push(if rng.chance(0.3) { '\n' } else { ' ' });
+                }
+                let valid = !rng.chance(0.05);
+                passport.push_str(&format!("{}:{}", field, value(rng, field, valid)));
+            }
+
+            passport + "\n"
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_input() {
+        for seed in 0..10 {
+            let text = generate(&mut Rng::new(seed), 300);
+            let input = crate::parse(&text).unwrap();
+            let (part_1, part_2) = (crate::solve_1(&input), crate::solve_2(&input));
+
+            assert_eq!(input.len(), 300);
+            assert!(part_2 > 0 && part_2 < part_1 && part_1 < 300);
+        }
+    }
+}
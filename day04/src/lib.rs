@@ -1,21 +1,47 @@
-#![feature(test)]
+#![cfg_attr(feature = "bench", feature(test))]
+
+#[cfg(feature = "bench")]
 extern crate test;
 
+#[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
 
+use common::{Answer, Error, Solution};
 use regex::Regex;
 use std::collections::HashSet;
 
-lazy_static! {
-    static ref INPUT: Vec<&'static str> = include_str!("../input").split("\n\n").collect();
-    static ref BYR_RE: Regex = Regex::new(r"^(\d{4})$").unwrap();
-    static ref IYR_RE: Regex = Regex::new(r"^(\d{4})$").unwrap();
-    static ref EYR_RE: Regex = Regex::new(r"^(\d{4})$").unwrap();
-    static ref HGT_RE: Regex = Regex::new(r"^(\d+)((?:in)|(?:cm))$").unwrap();
-    static ref HCL_RE: Regex = Regex::new(r"^#[\da-f]{6}$").unwrap();
-    static ref ECL_RE: Regex = Regex::new(r"^(amb)|(blu)|(brn)|(gry)|(grn)|(hzl)|(oth)$").unwrap();
-    static ref PID_RE: Regex = Regex::new(r"^\d{9}$").unwrap();
+mod generate;
+
+pub use generate::generate;
+
+/// A regex compiled on first use.
+macro_rules! regex {
+    ($pattern:expr) => {{
+        static RE: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+        RE.get_or_init(|| Regex::new($pattern).unwrap())
+    }};
+}
+
+/// The embedded puzzle input, parsed on first use.
+#[cfg(feature = "input")]
+fn input() -> &'static Vec<&'static str> {
+    static INPUT: std::sync::OnceLock<Vec<&'static str>> = std::sync::OnceLock::new();
+
+    INPUT.get_or_init(|| parse(include_str!("../input")).expect("invalid input"))
+}
+
+const FIELDS: [&str; 8] = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid", "cid"];
+
+fn parse(input: &str) -> Result<Vec<&str>, Error> {
+    if let Some(field) = input
+        .split_ascii_whitespace()
+        .find(|field| !FIELDS.iter().any(|&k| field.split(':').next() == Some(k)))
+    {
+        return Err(Error::at(input, field, "unknown passport field"));
+    }
+
+    Ok(input.split("\n\n").collect())
 }
 
 fn solve<F: Fn(&str, Option<&str>) -> Option<bool>>(input: &[&str], check: F) -> usize {
@@ -25,13 +51,13 @@ fn solve<F: Fn(&str, Option<&str>) -> Option<bool>>(input: &[&str], check: F) ->
         .filter_map(|pp| {
             pp.filter_map(|p| {
                 let mut i = p.split(':');
-                let k = i.next().unwrap();
+                let k = i.next().unwrap_or_default();
                 let v = i.next();
                 match (k, check(k, v)) {
                     ("cid", _) => None,
                     (_, Some(true)) => Some((k, true)),
                     (_, Some(false)) => Some((k, false)),
-                    (_, None) => unimplemented!(),
+                    (_, None) => Some((k, false)),
                 }
             })
             .try_fold(HashSet::new(), |mut s, (k, v)| {
@@ -58,7 +84,7 @@ fn solve_2(input: &[&str]) -> usize {
     solve(input, |k, v| match k {
         "byr" => v
             .map(|v| {
-                BYR_RE
+                regex!(r"^(\d{4})$")
                     .captures(v)
                     .map(|cap| {
                         cap[1]
@@ -71,7 +97,7 @@ fn solve_2(input: &[&str]) -> usize {
             .or(Some(false)),
         "iyr" => v
             .map(|v| {
-                IYR_RE
+                regex!(r"^(\d{4})$")
                     .captures(v)
                     .map(|cap| {
                         cap[1]
@@ -84,7 +110,7 @@ fn solve_2(input: &[&str]) -> usize {
             .or(Some(false)),
         "eyr" => v
             .map(|v| {
-                EYR_RE
+                regex!(r"^(\d{4})$")
                     .captures(v)
                     .map(|cap| {
                         cap[1]
@@ -97,7 +123,7 @@ fn solve_2(input: &[&str]) -> usize {
             .or(Some(false)),
         "hgt" => v
             .map(|v| {
-                HGT_RE
+                regex!(r"^(\d+)((?:in)|(?:cm))$")
                     .captures(v)
                     .map(|cap| match (cap[1].parse::<u32>(), &cap[2]) {
                         (Ok(v), "in") => (59..=76).contains(&v),
@@ -107,24 +133,63 @@ fn solve_2(input: &[&str]) -> usize {
                     .unwrap_or(false)
             })
             .or(Some(false)),
-        "hcl" => v.map(|v| HCL_RE.is_match(v)).or(Some(false)),
-        "ecl" => v.map(|v| ECL_RE.is_match(v)).or(Some(false)),
-        "pid" => v.map(|v| PID_RE.is_match(v)).or(Some(false)),
+        "hcl" => v
+            .map(|v| regex!(r"^#[\da-f]{6}$").is_match(v))
+            .or(Some(false)),
+        "ecl" => v
+            .map(|v| regex!(r"^(amb)|(blu)|(brn)|(gry)|(grn)|(hzl)|(oth)$").is_match(v))
+            .or(Some(false)),
+        "pid" => v.map(|v| regex!(r"^\d{9}$").is_match(v)).or(Some(false)),
         _ => None,
     })
 }
 
+#[cfg(feature = "input")]
 pub fn part_1() -> usize {
-    solve_1(&INPUT)
+    solve_1(input())
 }
 
+#[cfg(feature = "input")]
 pub fn part_2() -> usize {
-    solve_2(&INPUT)
+    solve_2(input())
+}
+
+pub fn solve_part_1(input: &str) -> Result<usize, Error> {
+    parse(input)
+        .map(|input| solve_1(&input))
+        .map_err(|e| e.in_day(Day04::DAY))
+}
+
+pub fn solve_part_2(input: &str) -> Result<usize, Error> {
+    parse(input)
+        .map(|input| solve_2(&input))
+        .map_err(|e| e.in_day(Day04::DAY))
+}
+
+pub struct Day04;
+
+impl Solution for Day04 {
+    const DAY: u32 = 4;
+
+    type Input<'a> = Vec<&'a str>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+        parse(input).map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+        Ok(solve_1(input).into())
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+        Ok(Some(solve_2(input).into()))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     lazy_static! {
@@ -221,11 +286,21 @@ hcl:#623a2f hcl:#623a2f"
         assert_eq!(solve_2(&input), 0);
     }
 
+    #[test]
+    fn unknown_field() {
+        let error = parse("byr:1937 iyr:2017\nhgt:183cm foo:bar").unwrap_err();
+
+        assert_eq!(error.position, Some((2, 11)));
+        assert_eq!(error.snippet, "foo:bar");
+    }
+
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_1(b: &mut Bencher) {
         b.iter(part_1);
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_2(b: &mut Bencher) {
         b.iter(part_2);
@@ -0,0 +1,58 @@
+use common::Rng;
+use std::ops::RangeInclusive;
+
+/// The sizes `generate` supports.
+pub const SIZES: RangeInclusive<usize> = 3..=1000;
+
+fn boarding_pass(seat: u64) -> String {
+    (0..10)
+        .rev()
+        .map(|bit| match (bit >= 3, seat >> bit & 1) {
+            (true, 0) => 'F',
+            (true, _) => 'B',
+            (false, 0) => 'L',
+            (false, _) => 'R',
+        })
+        .collect()
+}
+
+/// The boarding passes of `size` consecutive seats (clamped to `SIZES`) but
+/// one, which is neither the first nor the last.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.clamp(*SIZES.start(), *SIZES.end()) as u64;
+    let first = rng.range(1, 1023 - size);
+    let free = rng.range(first + 1, first + size - 2);
+
+    let mut seats = (first..first + size)
+        .filter(|&seat| seat != free)
+        .collect::<Vec<_>>();
+    rng.shuffle(&mut seats);
+
+    seats
+        .into_iter()
+        .map(|seat| boarding_pass(seat) + "\n")
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_free_seat() {
+        assert_eq!(boarding_pass(357), "FBFBBFFRLR");
+
+        for (seed, size) in (0..20).zip([3, 10, 500, 1000].iter().cycle()) {
+            let seats = crate::parse(&generate(&mut Rng::new(seed), *size)).unwrap();
+            let max = crate::solve_1(&seats).unwrap();
+            let min = *seats.iter().min().unwrap();
+            let free = (min..=max)
+                .filter(|seat| !seats.contains(seat))
+                .collect::<Vec<_>>();
+
+            assert_eq!(seats.len(), size - 1);
+            assert_eq!(free.len(), 1);
+            assert_eq!(crate::solve_2(&seats), Ok(free[0]));
+        }
+    }
+}
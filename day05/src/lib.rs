@@ -1,95 +1,164 @@
-#![feature(test)]
+#![cfg_attr(feature = "bench", feature(test))]
+
+#[cfg(feature = "bench")]
 extern crate test;
 
-#[macro_use]
-extern crate lazy_static;
+use common::{Answer, BitSet, Error, Solution};
+
+mod generate;
+
+pub use generate::{generate, SIZES};
 
-use std::collections::HashSet;
+/// The embedded puzzle input, parsed on first use.
+#[cfg(feature = "input")]
+fn input() -> &'static Vec<usize> {
+    static INPUT: std::sync::OnceLock<Vec<usize>> = std::sync::OnceLock::new();
 
-lazy_static! {
-    static ref INPUT: Vec<&'static str> =
-        include_str!("../input").split_ascii_whitespace().collect();
+    INPUT.get_or_init(|| parse(include_str!("../input")).expect("invalid input"))
 }
 
-fn calc(data: &str) -> usize {
-    let (_, _, row, _, _, col) = data.chars().fold(
+fn calc(data: &str) -> Result<usize, Error> {
+    let (_, _, row, _, _, col) = data.char_indices().try_fold(
         (0, 127, 0, 0, 7, 0),
-        |(start_row, end_row, row, start_col, end_col, col), c| {
+        |(start_row, end_row, row, start_col, end_col, col), (i, c)| {
             let middle_row = || (end_row + start_row) / 2;
             let middle_col = || (end_col + start_col) / 2;
 
             match c {
-                'F' => (start_row, middle_row(), start_row, start_col, end_col, col),
-                'B' => (middle_row() + 1, end_row, end_row, start_col, end_col, col),
-                'R' => (start_row, end_row, row, middle_col() + 1, end_col, end_col),
-                'L' => (start_row, end_row, row, start_col, middle_col(), start_col),
-                _ => unimplemented!(),
+                'F' => Ok((start_row, middle_row(), start_row, start_col, end_col, col)),
+                'B' => Ok((middle_row() + 1, end_row, end_row, start_col, end_col, col)),
+                'R' => Ok((start_row, end_row, row, middle_col() + 1, end_col, end_col)),
+                'L' => Ok((start_row, end_row, row, start_col, middle_col(), start_col)),
+                _ => Err(Error::at(
+                    data,
+                    &data[i..i + c.len_utf8()],
+                    "expecting F, B, L or R",
+                )),
             }
         },
-    );
-    row * 8 + col
+    )?;
+    Ok(row * 8 + col)
 }
 
-fn solve_1(input: &[&str]) -> usize {
-    input.iter().map(|line| calc(line)).max().unwrap()
+fn parse(input: &str) -> Result<Vec<usize>, Error> {
+    input
+        .split_ascii_whitespace()
+        .map(|line| calc(line).map_err(|e| e.within(input, line)))
+        .collect()
 }
 
-fn solve_2(input: &[&str]) -> usize {
-    let seats = input.iter().map(|line| calc(line)).collect::<HashSet<_>>();
+fn solve_1(seats: &[usize]) -> Result<usize, Error> {
+    seats
+        .iter()
+        .copied()
+        .max()
+        .ok_or_else(|| Error::new("no boarding passes"))
+}
+
+fn solve_2(seats: &[usize]) -> Result<usize, Error> {
+    let seats = seats.iter().copied().collect::<BitSet>();
 
-    let (min, max) = (*seats.iter().min().unwrap(), *seats.iter().max().unwrap());
+    let (min, max) = match (seats.iter().next(), seats.iter().last()) {
+        (Some(min), Some(max)) => (min, max),
+        _ => return Err(Error::new("no boarding passes")),
+    };
+    let is_free =
+        |seat: usize| seat > 0 && !seats.get(seat) && seats.get(seat + 1) && seats.get(seat - 1);
 
     let (start, mut distance) = (127 / 2 * 8 + 8 / 2, 0);
 
-    while start + distance < max || start - distance > min {
+    while start + distance < max || start.saturating_sub(distance) > min {
         let current_up = start + distance;
-        if !seats.contains(&current_up)
-            && seats.contains(&(current_up + 1))
-            && seats.contains(&(current_up - 1))
-        {
-            return current_up;
+        if is_free(current_up) {
+            return Ok(current_up);
         }
 
-        let current_down = start - distance;
-        if !seats.contains(&current_down)
-            && seats.contains(&(current_down + 1))
-            && seats.contains(&(current_down - 1))
-        {
-            return current_down;
+        let current_down = start.saturating_sub(distance);
+        if is_free(current_down) {
+            return Ok(current_down);
         }
 
         distance += 1;
     }
 
-    unimplemented!()
+    Err(Error::new("no free seat between two taken ones"))
 }
 
+#[cfg(feature = "input")]
 pub fn part_1() -> usize {
-    solve_1(&INPUT)
+    solve_1(input()).expect("invalid input")
 }
 
+#[cfg(feature = "input")]
 pub fn part_2() -> usize {
-    solve_2(&INPUT)
+    solve_2(input()).expect("invalid input")
+}
+
+pub fn solve_part_1(input: &str) -> Result<usize, Error> {
+    parse(input)
+        .and_then(|input| solve_1(&input))
+        .map_err(|e| e.in_day(Day05::DAY))
+}
+
+pub fn solve_part_2(input: &str) -> Result<usize, Error> {
+    parse(input)
+        .and_then(|input| solve_2(&input))
+        .map_err(|e| e.in_day(Day05::DAY))
+}
+
+pub struct Day05;
+
+impl Solution for Day05 {
+    const DAY: u32 = 5;
+
+    type Input<'a> = Vec<usize>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+        parse(input).map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+        solve_1(input)
+            .map(Answer::from)
+            .map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+        solve_2(input)
+            .map(|answer| Some(answer.into()))
+            .map_err(|e| e.in_day(Self::DAY))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     #[test]
     fn same_results_part_1() {
-        assert_eq!(calc("FBFBBFFRLR"), 357);
-        assert_eq!(calc("BFFFBBFRRR"), 567);
-        assert_eq!(calc("FFFBBBFRRR"), 119);
-        assert_eq!(calc("BBFFBBFRLL"), 820);
+        assert_eq!(calc("FBFBBFFRLR"), Ok(357));
+        assert_eq!(calc("BFFFBBFRRR"), Ok(567));
+        assert_eq!(calc("FFFBBBFRRR"), Ok(119));
+        assert_eq!(calc("BBFFBBFRLL"), Ok(820));
+    }
+
+    #[test]
+    fn invalid_boarding_pass() {
+        let error = parse("FBFBBFFRLR\nBFFXBBFRRR\n").unwrap_err();
+
+        assert_eq!(error.position, Some((2, 4)));
+        assert_eq!(error.snippet, "X");
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_1(b: &mut Bencher) {
         b.iter(part_1);
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_2(b: &mut Bencher) {
         b.iter(part_2);
@@ -0,0 +1,48 @@
+use common::Rng;
+
+/// `size` groups of one to five people, who share some of their answers.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let mut questions = (b'a'..=b'z').map(char::from).collect::<Vec<_>>();
+            rng.shuffle(&mut questions);
+            let shared = rng.below(8) as usize;
+
+            (0..rng.range(1, 5))
+                .map(|_| {
+                    let mut answers = questions[..shared].to_vec();
+                    answers.extend(
+                        questions[shared..]
+                            .iter()
+                            .filter(|_| rng.chance(0.2))
+                            .copied(),
+                    );
+                    if answers.is_empty() {
+                        answers.push(questions[0]);
+                    }
+                    rng.shuffle(&mut answers);
+
+                    answers.into_iter().collect::<String>() + "\n"
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_input() {
+        for seed in 0..10 {
+            let text = generate(&mut Rng::new(seed), 400);
+            let input = text.split("\n\n").collect::<Vec<_>>();
+
+            assert_eq!(input.len(), 400);
+            assert!(crate::solve_2(&input) > 0);
+            assert!(crate::solve_2(&input) < crate::solve_1(&input));
+        }
+    }
+}
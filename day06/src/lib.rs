@@ -1,75 +1,97 @@
-#![feature(test)]
+#![cfg_attr(feature = "bench", feature(test))]
+
+#[cfg(feature = "bench")]
 extern crate test;
 
+#[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
 
-use std::{collections::HashSet, hash::Hash, iter::FromIterator, ops::Deref};
+use common::{Answer, BitSet, Error, Solution};
 
-lazy_static! {
-    static ref INPUT: Vec<&'static str> = include_str!("../input").split("\n\n").collect();
-}
+mod generate;
 
-struct SetIntersection<T>(HashSet<T>);
+pub use generate::generate;
 
-impl<T> Deref for SetIntersection<T> {
-    type Target = HashSet<T>;
+/// The embedded puzzle input, parsed on first use.
+#[cfg(feature = "input")]
+fn input() -> &'static Vec<&'static str> {
+    static INPUT: std::sync::OnceLock<Vec<&'static str>> = std::sync::OnceLock::new();
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
-    }
+    INPUT.get_or_init(|| include_str!("../input").split("\n\n").collect())
 }
 
-impl<T: Eq + Hash + Clone> FromIterator<HashSet<T>> for SetIntersection<T> {
-    fn from_iter<I: IntoIterator<Item = HashSet<T>>>(iter: I) -> Self {
-        SetIntersection(
-            iter.into_iter()
-                .fold(None, |s: Option<HashSet<T>>, set| {
-                    s.map(|c| c.intersection(&set).cloned().collect())
-                        .or(Some(set))
-                })
-                .unwrap_or_else(HashSet::new),
-        )
-    }
+/// The questions answered yes to in `answers`, `a` being 0.
+fn questions(answers: &str) -> BitSet {
+    answers
+        .bytes()
+        .filter(u8::is_ascii_lowercase)
+        .map(|c| (c - b'a') as usize)
+        .collect()
 }
 
 fn solve_1(input: &[&str]) -> usize {
     input
         .iter()
-        .map(|group| {
-            group
-                .chars()
-                .filter(|c| ('a'..='z').contains(c))
-                .collect::<HashSet<_>>()
-                .len()
-        })
+        .map(|group| questions(group).count_ones())
         .sum()
 }
 
 fn solve_2(input: &[&str]) -> usize {
     input
         .iter()
-        .map(|group| {
+        .filter_map(|group| {
             group
                 .split_ascii_whitespace()
-                .map(|ans| ans.chars().collect::<HashSet<_>>())
-                .collect::<SetIntersection<_>>()
-                .len()
+                .map(questions)
+                .reduce(|all, person| all.intersection(&person))
         })
+        .map(|all| all.count_ones())
         .sum()
 }
 
+#[cfg(feature = "input")]
 pub fn part_1() -> usize {
-    solve_1(&INPUT)
+    solve_1(input())
 }
 
+#[cfg(feature = "input")]
 pub fn part_2() -> usize {
-    solve_2(&INPUT)
+    solve_2(input())
+}
+
+pub fn solve_part_1(input: &str) -> Result<usize, Error> {
+    Ok(solve_1(&input.split("\n\n").collect::<Vec<_>>()))
+}
+
+pub fn solve_part_2(input: &str) -> Result<usize, Error> {
+    Ok(solve_2(&input.split("\n\n").collect::<Vec<_>>()))
+}
+
+pub struct Day06;
+
+impl Solution for Day06 {
+    const DAY: u32 = 6;
+
+    type Input<'a> = Vec<&'a str>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+        Ok(input.split("\n\n").collect())
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+        Ok(solve_1(input).into())
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+        Ok(Some(solve_2(input).into()))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     lazy_static! {
@@ -102,11 +124,13 @@ b"
         assert_eq!(solve_2(&INPUT), 6)
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_1(b: &mut Bencher) {
         b.iter(part_1);
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_2(b: &mut Bencher) {
         b.iter(part_2);
@@ -0,0 +1,141 @@
+use common::Rng;
+use std::ops::RangeInclusive;
+
+const ADJECTIVES: [&str; 18] = [
+    "bright", "clear", "dark", "dim", "dotted", "drab", "dull", "faded", "light", "mirrored",
+    "muted", "pale", "plaid", "posh", "shiny", "striped", "vibrant", "wavy",
+];
+
+const COLORS: [&str; 33] = [
+    "aqua",
+    "beige",
+    "black",
+    "blue",
+    "bronze",
+    "brown",
+    "chartreuse",
+    "coral",
+    "crimson",
+    "cyan",
+    "fuchsia",
+    "gold",
+    "gray",
+    "green",
+    "indigo",
+    "lavender",
+    "lime",
+    "magenta",
+    "maroon",
+    "olive",
+    "orange",
+    "plum",
+    "purple",
+    "red",
+    "salmon",
+    "silver",
+    "tan",
+    "teal",
+    "tomato",
+    "turquoise",
+    "violet",
+    "white",
+    "yellow",
+];
+
+/// No bag holds more than this many bags, so that counting them cannot
+/// overflow.
+const LIMIT: usize = 1_000_000_000;
+
+/// `size` distinct colors, "shiny gold" among them: the real adjectives and
+/// colors while they last, then made-up colors.
+fn colors(rng: &mut Rng, size: usize) -> Vec<String> {
+    let mut colors = ADJECTIVES
+        .iter()
+        .flat_map(|adjective| {
+            COLORS
+                .iter()
+                .map(move |color| format!("{} {}", adjective, color))
+        })
+        .filter(|color| color != "shiny gold")
+        .collect::<Vec<_>>();
+    rng.shuffle(&mut colors);
+    colors.truncate(size - 1);
+
+    while colors.len() < size - 1 {
+        let color = (0..rng.range(2, 4))
+            .map(|_| *rng.choose(&["ba", "ko", "ri", "lu", "me", "sa", "to", "vi"]))
+            .collect::<String>();
+        let color = format!("{} {}", rng.choose(&ADJECTIVES), color);
+        if !colors.contains(&color) {
+            colors.push(color);
+        }
+    }
+
+    colors.push("shiny gold".to_string());
+    colors
+}
+
+/// The sizes `generate` supports: shiny gold and at least one other bag.
+pub const SIZES: RangeInclusive<usize> = 2..=usize::MAX;
+
+/// The rules of `size` bag colors (at least 2). The bags are ordered at
+/// random and each one only contains bags that come later, so that no bag
+/// contains itself; shiny gold sits in the middle third of the order.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(*SIZES.start());
+
+    let mut colors = colors(rng, size);
+    let gold = colors.len() - 1;
+    let position = rng.range(size as u64 / 3, (size as u64 * 2 / 3).min(size as u64 - 2)) as usize;
+    colors.swap(gold, position);
+
+    let mut totals = vec![0; size];
+    let mut rules = Vec::with_capacity(size);
+    for i in (0..size).rev() {
+        let mut contents = Vec::new();
+        for _ in 0..rng.below(5).min((size - i - 1) as u64) {
+            let j = rng.range(i as u64 + 1, size as u64 - 1) as usize;
+            let weight = rng.range(1, 5) as usize;
+            let total = totals[i] + weight * (1 + totals[j]);
+
+            if total <= LIMIT && !contents.iter().any(|&(k, _)| k == j) {
+                totals[i] = total;
+                contents.push((j, weight));
+            }
+        }
+
+        let contents = if contents.is_empty() {
+            "no other bags".to_string()
+        } else {
+            contents
+                .iter()
+                .map(|&(j, weight)| {
+                    let plural = if weight == 1 { "" } else { "s" };
+                    format!("{} {} bag{}", weight, colors[j], plural)
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        rules.push(format!("{} bags contain {}.\n", colors[i], contents));
+    }
+    rng.shuffle(&mut rules);
+
+    rules.concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_input() {
+        for (seed, size) in (0..20).zip([2, 10, 600, 2000].iter().cycle()) {
+            let graph = crate::Graph::new(&generate(&mut Rng::new(seed), *size)).unwrap();
+
+            assert_eq!(graph.nodes.len(), *size);
+            assert!(graph.nodes.contains("shiny gold"));
+            assert!(crate::solve_1(&graph).unwrap() < *size);
+            assert!(crate::solve_2(&graph).unwrap() <= LIMIT);
+        }
+    }
+}
@@ -1,17 +1,29 @@
-#![feature(test)]
+#![cfg_attr(feature = "bench", feature(test))]
+
+#[cfg(feature = "bench")]
 extern crate test;
 
+#[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
 
+use common::{Answer, Error, Solution};
 use std::collections::{HashMap, HashSet, VecDeque};
 
-lazy_static! {
-    static ref INPUT: Graph = Graph::new(include_str!("../input"));
+mod generate;
+
+pub use generate::{generate, SIZES};
+
+/// The embedded puzzle input, parsed on first use.
+#[cfg(feature = "input")]
+fn input() -> &'static Graph {
+    static INPUT: std::sync::OnceLock<Graph> = std::sync::OnceLock::new();
+
+    INPUT.get_or_init(|| Graph::new(include_str!("../input")).expect("invalid input"))
 }
 
 #[derive(Debug)]
-struct Graph {
+pub struct Graph {
     nodes: HashSet<String>,
     edges: HashMap<String, HashSet<String>>,
     reverse_edges: HashMap<String, HashSet<String>>,
@@ -19,72 +31,152 @@ struct Graph {
 }
 
 impl Graph {
-    fn new(input: &str) -> Self {
-        let (nodes, edges, reverse_edges, weights) = input
-            .split('\n')
-            .map(|line| {
-                let mut parts = line.split(" contain ");
-                let node = {
-                    let mut p = parts.next().unwrap().split_ascii_whitespace();
-                    format!("{} {}", p.next().unwrap(), p.next().unwrap())
-                };
+    fn new(input: &str) -> Result<Self, Error> {
+        let mut graph = Self {
+            nodes: HashSet::new(),
+            edges: HashMap::new(),
+            reverse_edges: HashMap::new(),
+            weights: HashMap::new(),
+        };
+
+        for line in input.lines() {
+            let (node, neighbors) = Self::parse_rule(line).map_err(|e| e.within(input, line))?;
+            graph.nodes.insert(node.clone());
+
+            for (n, w) in neighbors {
+                graph
+                    .edges
+                    .entry(node.clone())
+                    .or_default()
+                    .insert(n.clone());
+                graph
+                    .reverse_edges
+                    .entry(n.clone())
+                    .or_default()
+                    .insert(node.clone());
+                graph.weights.insert((node.clone(), n), w);
+            }
+        }
+
+        Ok(graph)
+    }
+
+    fn parse_rule(line: &str) -> Result<(String, HashSet<(String, usize)>), Error> {
+        let mut parts = line.splitn(2, " contain ");
+        let (bag, contents) = match (parts.next(), parts.next()) {
+            (Some(bag), Some(contents)) => (bag, contents),
+            _ => {
+                return Err(Error::at(
+                    line,
+                    line,
+                    "expecting `<color> bags contain <contents>`",
+                ))
+            }
+        };
+        let node = color(line, bag, &mut bag.split_ascii_whitespace())?;
+
+        let mut neighbors = HashSet::new();
+        for part in contents.split(',') {
+            if part == "no other bags." || part.is_empty() {
+                continue;
+            }
+
+            let part = part.trim_start();
+            let mut words = part.split_ascii_whitespace();
+            let weight = words
+                .next()
+                .and_then(|weight| weight.parse().ok())
+                .ok_or_else(|| Error::at(line, part, "expecting a number of bags"))?;
+            neighbors.insert((color(line, part, &mut words)?, weight));
+        }
+
+        Ok((node, neighbors))
+    }
 
-                let neighbors: HashSet<(String, usize)> =
-                    parts
-                        .next()
-                        .unwrap()
-                        .split(',')
-                        .fold(HashSet::new(), |mut v, part| {
-                            if part == "no other bags." || part.is_empty() {
-                                v
-                            } else {
-                                let mut parts = part.trim_start().split_ascii_whitespace();
-                                let weight = parts.next().unwrap().parse().expect("invalid number");
-                                let node =
-                                    format!("{} {}", parts.next().unwrap(), parts.next().unwrap());
-                                v.insert((node, weight));
-                                v
-                            }
-                        });
-
-                (node, neighbors)
+    /// The bags directly inside `node` (`reverse` false) or directly holding
+    /// it, with their number, by color.
+    fn neighbors(&self, node: &str, reverse: bool) -> Result<Vec<(usize, &str)>, Error> {
+        if !self.nodes.contains(node) && !self.reverse_edges.contains_key(node) {
+            return Err(Error::new(format!("no {} bags", node)));
+        }
+
+        let edges = if reverse {
+            &self.reverse_edges
+        } else {
+            &self.edges
+        };
+        let mut neighbors = edges
+            .get(node)
+            .into_iter()
+            .flatten()
+            .map(|n| {
+                let edge = if reverse {
+                    (n.clone(), node.to_string())
+                } else {
+                    (node.to_string(), n.clone())
+                };
+                (self.weights[&edge], n.as_str())
             })
-            .fold(
-                (
-                    HashSet::new(),
-                    HashMap::new(),
-                    HashMap::new(),
-                    HashMap::new(),
-                ),
-                |(mut nodes, mut edges, mut reverse_edges, mut weights), (node, neighbors)| {
-                    nodes.insert(node.clone());
-
-                    for (n, w) in &neighbors {
-                        edges
-                            .entry(node.clone())
-                            .or_insert_with(HashSet::new)
-                            .insert(n.clone());
-                        reverse_edges
-                            .entry(n.clone())
-                            .or_insert_with(HashSet::new)
-                            .insert(node.clone());
-                        weights.insert((node.clone(), n.clone()), *w);
-                    }
+            .collect::<Vec<_>>();
+        neighbors.sort_by_key(|&(_, n)| n);
+
+        Ok(neighbors)
+    }
+
+    fn has_cycle<'a>(
+        &'a self,
+        node: &'a str,
+        visiting: &mut HashSet<&'a str>,
+        done: &mut HashSet<&'a str>,
+    ) -> bool {
+        if done.contains(node) {
+            return false;
+        }
+        if !visiting.insert(node) {
+            return true;
+        }
+
+        let cycle = self
+            .edges
+            .get(node)
+            .is_some_and(|neighbors| neighbors.iter().any(|n| self.has_cycle(n, visiting, done)));
 
-                    (nodes, edges, reverse_edges, weights)
-                },
-            );
+        visiting.remove(node);
+        done.insert(node);
+        cycle
+    }
 
-        Self {
-            nodes,
-            edges,
-            reverse_edges,
-            weights,
+    /// Neither part has an answer if a bag ends up inside itself.
+    fn check_acyclic(&self) -> Result<(), Error> {
+        let (mut visiting, mut done) = (HashSet::new(), HashSet::new());
+        if self
+            .edges
+            .keys()
+            .any(|node| self.has_cycle(node, &mut visiting, &mut done))
+        {
+            Err(Error::new("bags contain each other"))
+        } else {
+            Ok(())
         }
     }
 }
 
-fn solve_1(input: &Graph) -> usize {
+/// Reads the two words naming a bag color (e.g. "shiny gold") from `words`,
+/// which come from `part`, a slice of `line`.
+fn color<'a, I: Iterator<Item = &'a str>>(
+    line: &str,
+    part: &str,
+    words: &mut I,
+) -> Result<String, Error> {
+    match (words.next(), words.next()) {
+        (Some(adjective), Some(color)) => Ok(format!("{} {}", adjective, color)),
+        _ => Err(Error::at(line, part, "expecting a bag color")),
+    }
+}
+
+fn solve_1(input: &Graph) -> Result<usize, Error> {
+    input.check_acyclic()?;
+
     let mut visit = {
         let mut queue = VecDeque::new();
         queue.push_back("shiny gold".to_string());
@@ -103,10 +195,12 @@ fn solve_1(input: &Graph) -> usize {
         }
     }
 
-    cover.len()
+    Ok(cover.len())
 }
 
-fn solve_2(input: &Graph) -> usize {
+fn solve_2(input: &Graph) -> Result<usize, Error> {
+    input.check_acyclic()?;
+
     let mut visit = {
         let mut queue = VecDeque::new();
         queue.push_back("shiny gold".to_string());
@@ -139,20 +233,91 @@ fn solve_2(input: &Graph) -> usize {
         }
     }
 
-    total["shiny gold"]
+    total
+        .get("shiny gold")
+        .copied()
+        .ok_or_else(|| Error::new("no shiny gold bag"))
 }
 
+#[cfg(feature = "input")]
 pub fn part_1() -> usize {
-    solve_1(&INPUT)
+    solve_1(input()).expect("invalid input")
 }
 
+#[cfg(feature = "input")]
 pub fn part_2() -> usize {
-    solve_2(&INPUT)
+    solve_2(input()).expect("invalid input")
+}
+
+pub fn solve_part_1(input: &str) -> Result<usize, Error> {
+    Graph::new(input)
+        .and_then(|input| solve_1(&input))
+        .map_err(|e| e.in_day(Day07::DAY))
+}
+
+pub fn solve_part_2(input: &str) -> Result<usize, Error> {
+    Graph::new(input)
+        .and_then(|input| solve_2(&input))
+        .map_err(|e| e.in_day(Day07::DAY))
+}
+
+pub struct Day07;
+
+impl Solution for Day07 {
+    const DAY: u32 = 7;
+
+    type Input<'a> = Graph;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+        Graph::new(input).map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+        solve_1(input)
+            .map(Answer::from)
+            .map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+        solve_2(input)
+            .map(|answer| Some(answer.into()))
+            .map_err(|e| e.in_day(Self::DAY))
+    }
+
+    const COMMANDS: &'static [(&'static str, &'static str)] = &[
+        ("contents <color>", "the bags directly inside a bag"),
+        ("containers <color>", "the bags directly holding a bag"),
+    ];
+
+    fn explore(
+        input: &mut Self::Input<'_>,
+        command: &str,
+        args: &[&str],
+    ) -> Option<Result<String, Error>> {
+        let reverse = match command {
+            "contents" => false,
+            "containers" => true,
+            _ => return None,
+        };
+
+        Some(input.neighbors(&args.join(" "), reverse).map(|neighbors| {
+            if neighbors.is_empty() {
+                "none".to_string()
+            } else {
+                neighbors
+                    .iter()
+                    .map(|(weight, n)| format!("{} {}", weight, n))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+        }))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     lazy_static! {
@@ -166,7 +331,8 @@ dark olive bags contain 3 faded blue bags, 4 dotted black bags.
 vibrant plum bags contain 5 faded blue bags, 6 dotted black bags.
 faded blue bags contain no other bags.
 dotted black bags contain no other bags."
-        );
+        )
+        .expect("invalid input");
         static ref EXAMPLE_2: Graph = Graph::new(
             r"shiny gold bags contain 2 dark red bags.
 dark red bags contain 2 dark orange bags.
@@ -175,29 +341,85 @@ dark yellow bags contain 2 dark green bags.
 dark green bags contain 2 dark blue bags.
 dark blue bags contain 2 dark violet bags.
 dark violet bags contain no other bags."
-        );
+        )
+        .expect("invalid input");
     }
 
     #[test]
     fn same_results_part_1() {
-        assert_eq!(solve_1(&EXAMPLE_1), 4);
+        assert_eq!(solve_1(&EXAMPLE_1), Ok(4));
+    }
+
+    #[test]
+    fn same_results_solve_part_1() {
+        assert_eq!(
+            solve_part_1(
+                "bright white bags contain 1 shiny gold bag.\nshiny gold bags contain no other bags.\n"
+            ),
+            Ok(1)
+        );
     }
 
     #[test]
     fn same_results_part_2_1() {
-        assert_eq!(solve_2(&EXAMPLE_1), 32);
+        assert_eq!(solve_2(&EXAMPLE_1), Ok(32));
     }
 
     #[test]
     fn same_results_part_2_2() {
-        assert_eq!(solve_2(&EXAMPLE_2), 126);
+        assert_eq!(solve_2(&EXAMPLE_2), Ok(126));
+    }
+
+    #[test]
+    fn invalid_rule() {
+        let error = Graph::new("shiny gold bags contain 1 dark olive bag, two vibrant plum bags.")
+            .map(drop)
+            .expect_err("invalid rule");
+
+        assert_eq!(error.position, Some((1, 43)));
+        assert_eq!(error.snippet, "two vibrant plum bags.");
+    }
+
+    #[test]
+    fn cyclic_rules() {
+        let graph = Graph::new(
+            "shiny gold bags contain 1 dark olive bag.\ndark olive bags contain 2 shiny gold bags.",
+        )
+        .expect("invalid input");
+
+        assert!(solve_1(&graph).is_err());
+        assert!(solve_2(&graph).is_err());
+
+        let graph = Graph::new(
+            "shiny gold bags contain 1 dark olive bag.\ndark olive bags contain 2 dark red bags.\ndark red bags contain 1 dark olive bag.",
+        )
+        .expect("invalid input");
+
+        assert!(solve_1(&graph).is_err());
+        assert!(solve_2(&graph).is_err());
+    }
+
+    #[test]
+    fn neighbors() {
+        assert_eq!(
+            EXAMPLE_1.neighbors("shiny gold", false),
+            Ok(vec![(1, "dark olive"), (2, "vibrant plum")])
+        );
+        assert_eq!(
+            EXAMPLE_1.neighbors("shiny gold", true),
+            Ok(vec![(1, "bright white"), (2, "muted yellow")])
+        );
+        assert_eq!(EXAMPLE_1.neighbors("faded blue", false), Ok(vec![]));
+        assert!(EXAMPLE_1.neighbors("shiny blue", false).is_err());
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_1(b: &mut Bencher) {
         b.iter(part_1);
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_2(b: &mut Bencher) {
         b.iter(part_2);
@@ -0,0 +1,128 @@
+use crate::Instruction::{self, *};
+use common::Rng;
+use std::ops::RangeInclusive;
+fn offset(from: usize, to: usize) -> i32 {
+    to as i32 - from as i32
+}
+
+/// Where the program goes after `pc`, or `None` when it terminates.
+fn next(program: &[Instruction], pc: usize) -> Option<usize> {
+    match program.get(pc)? {
+        JMP(value) => Some((pc as i32 + value) as usize),
+        _ => Some(pc + 1),
+    }
+}
+
+/// The sizes `generate` supports: a loop of at least three instructions
+/// and one to end on.
+pub const SIZES: RangeInclusive<usize> = 4..=usize::MAX;
+
+/// A boot code of `size` instructions (at least 4) that loops, and that
+/// terminates if one instruction, and only that one, is swapped.
+///
+/// The first two thirds of the program only ever jump within themselves,
+/// or fall through within themselves (the last of them is a `jmp` that is
+/// never reached), so that the program cannot leave them but through a
+/// single `nop` on the loop, the one to swap, pointing into the last third,
+/// which always runs to the end.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(*SIZES.start());
+    let head = (size * 2 / 3).max(3);
+
+    let program = loop {
+        let mut program = (0..head)
+            .map(|i| {
+                let target = if i + 1 < head && rng.chance(0.7) {
+                    rng.range(i as u64 + 1, (i as u64 + 20).min(head as u64 - 1)) as usize
+                } else {
+                    rng.below(head as u64) as usize
+                };
+
+                match rng.below(10) {
+                    _ if i == head - 1 => JMP(offset(i, target)),
+                    0..=4 => ACC(rng.range(0, 100) as i32 - 50),
+                    5..=6 => NOP(offset(i, target)),
+                    _ => JMP(offset(i, target)),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let (mut visited, mut pc) = (Vec::new(), 0);
+        while !visited.contains(&pc) {
+            visited.push(pc);
+            pc = next(&program, pc).unwrap();
+        }
+
+        let candidates = visited
+            .iter()
+            .filter(|&&pc| !matches!(program[pc], JMP(_)))
+            .copied()
+            .collect::<Vec<_>>();
+        if visited.contains(&(head - 1)) || candidates.is_empty() {
+            continue;
+        }
+
+        let fix = *rng.choose(&candidates);
+        let target = rng.range(head as u64, size as u64) as usize;
+        program[fix] = NOP(offset(fix, target));
+
+        for i in head..size {
+            program.push(match rng.below(10) {
+                0..=5 => ACC(rng.range(0, 100) as i32 - 50),
+                6..=7 => NOP(rng.range(0, 100) as i32 - 50),
+                _ => JMP(rng.range(1, (size - i) as u64) as i32),
+            });
+        }
+
+        break program;
+    };
+
+    program
+        .iter()
+        .map(|instruction| match instruction {
+            NOP(value) => format!("nop {:+}\n", value),
+            ACC(value) => format!("acc {:+}\n", value),
+            JMP(value) => format!("jmp {:+}\n", value),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    /// Whether `program` terminates with the instruction at `swap` swapped.
+    fn terminates(program: &[Instruction], swap: usize) -> bool {
+        let (mut visited, mut pc) = (HashSet::new(), 0);
+
+        while visited.insert(pc) {
+            pc = match (program.get(pc), pc == swap) {
+                (None, _) => return true,
+                (Some(NOP(value)), true) => (pc as i32 + value) as usize,
+                (Some(JMP(_)), true) => pc + 1,
+                _ => next(program, pc).unwrap(),
+            };
+        }
+
+        false
+    }
+
+    #[test]
+    fn single_fix() {
+        for (seed, size) in (0..20).zip([4, 10, 600, 2000].iter().cycle()) {
+            let program = Instruction::parse(&generate(&mut Rng::new(seed), *size)).unwrap();
+
+            assert_eq!(program.len(), *size);
+            assert!(crate::solve_1(&program).is_ok());
+            assert!(crate::solve_2(&program).is_ok());
+            assert_eq!(
+                (0..program.len())
+                    .filter(|&swap| !matches!(program[swap], ACC(_)))
+                    .filter(|&swap| terminates(&program, swap))
+                    .count(),
+                1
+            );
+        }
+    }
+}
@@ -1,49 +1,79 @@
-#![feature(test)]
+#![cfg_attr(feature = "bench", feature(test))]
+
+#[cfg(feature = "bench")]
 extern crate test;
 
+#[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
 
+use common::{Answer, Error, Solution};
 use std::collections::HashSet;
 
-lazy_static! {
-    static ref INPUT: Vec<Instruction> =
-        Instruction::parse(include_str!("../input")).expect("invalid input");
+mod generate;
+
+pub use generate::{generate, SIZES};
+
+/// The embedded puzzle input, parsed on first use.
+#[cfg(feature = "input")]
+fn input() -> &'static Vec<Instruction> {
+    static INPUT: std::sync::OnceLock<Vec<Instruction>> = std::sync::OnceLock::new();
+
+    INPUT.get_or_init(|| Instruction::parse(include_str!("../input")).expect("invalid input"))
 }
 
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug)]
-enum Instruction {
+pub enum Instruction {
     NOP(i32),
     ACC(i32),
     JMP(i32),
 }
 
 impl Instruction {
-    fn parse(input: &str) -> Result<Vec<Instruction>, String> {
-        input.lines().map(|line| line.parse()).collect()
+    fn parse(input: &str) -> Result<Vec<Instruction>, Error> {
+        input
+            .lines()
+            .map(|line| line.parse().map_err(|e: Error| e.within(input, line)))
+            .collect()
     }
 }
 
 impl std::str::FromStr for Instruction {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(line: &str) -> Result<Self, Self::Err> {
         use Instruction::*;
 
         let mut parts = line.split_ascii_whitespace();
-        match (parts.next(), parts.next()) {
-            (Some(opcode), Some(value)) => match (opcode, value.parse()) {
-                ("nop", Ok(value)) => Ok(NOP(value)),
-                ("acc", Ok(value)) => Ok(ACC(value)),
-                ("jmp", Ok(value)) => Ok(JMP(value)),
-                _ => Err(format!("invalid instruction: {}", line)),
-            },
-            _ => Err(format!("invalid instruction: {}", line)),
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(opcode), Some(value), None) => {
+                let value = value
+                    .parse()
+                    .map_err(|_| Error::at(line, value, "invalid argument"))?;
+                match opcode {
+                    "nop" => Ok(NOP(value)),
+                    "acc" => Ok(ACC(value)),
+                    "jmp" => Ok(JMP(value)),
+                    _ => Err(Error::at(line, opcode, "invalid opcode")),
+                }
+            }
+            _ => Err(Error::at(line, line, "expecting `<opcode> <argument>`")),
         }
     }
 }
 
-fn solve_1(input: &[Instruction]) -> i32 {
+/// `acc + value`, or an error naming the instruction at `pc` if it overflows.
+fn accumulate(acc: i32, value: i32, pc: usize) -> Result<i32, Error> {
+    acc.checked_add(value).ok_or_else(|| {
+        Error::new(format!(
+            "the accumulator overflows at instruction {}",
+            pc + 1
+        ))
+    })
+}
+
+fn solve_1(input: &[Instruction]) -> Result<i32, Error> {
     use Instruction::*;
 
     let mut set = HashSet::new();
@@ -52,22 +82,23 @@ fn solve_1(input: &[Instruction]) -> i32 {
 
     while !set.contains(&pc) {
         set.insert(pc);
-        match input[pc] {
-            NOP(_) => pc += 1,
-            ACC(value) => {
-                acc += value;
+        match input.get(pc) {
+            Some(NOP(_)) => pc += 1,
+            Some(ACC(value)) => {
+                acc = accumulate(acc, *value, pc)?;
                 pc += 1;
             }
-            JMP(value) => {
-                pc = (pc as i32 + value) as usize;
+            Some(JMP(value)) => {
+                pc = (pc as i32).wrapping_add(*value) as usize;
             }
+            None => return Err(Error::new("the program terminates without looping")),
         }
     }
 
-    acc
+    Ok(acc)
 }
 
-fn solve_2(input: &[Instruction]) -> i32 {
+fn solve_2(input: &[Instruction]) -> Result<i32, Error> {
     use Instruction::*;
 
     let mut change_idx = 0;
@@ -83,7 +114,7 @@ fn solve_2(input: &[Instruction]) -> i32 {
             set.insert(pc);
 
             let instruction = match (input.get(pc), changed_pc) {
-                (None, _) => return acc,
+                (None, _) => return Ok(acc),
                 (Some(ACC(value)), _) => ACC(*value),
                 (Some(NOP(value)), None) => {
                     if count == change_idx {
@@ -122,30 +153,74 @@ fn solve_2(input: &[Instruction]) -> i32 {
             match instruction {
                 NOP(_) => pc += 1,
                 ACC(value) => {
-                    acc += value;
+                    acc = accumulate(acc, value, pc)?;
                     pc += 1;
                 }
                 JMP(value) => {
-                    pc = (pc as i32 + value) as usize;
+                    pc = (pc as i32).wrapping_add(value) as usize;
                 }
             }
         }
 
+        if changed_pc.is_none() {
+            return Err(Error::new(
+                "no single nop/jmp swap makes the program terminate",
+            ));
+        }
         change_idx += 1;
     }
 }
 
+#[cfg(feature = "input")]
 pub fn part_1() -> i32 {
-    solve_1(&INPUT)
+    solve_1(input()).expect("invalid input")
 }
 
+#[cfg(feature = "input")]
 pub fn part_2() -> i32 {
-    solve_2(&INPUT)
+    solve_2(input()).expect("invalid input")
+}
+
+pub fn solve_part_1(input: &str) -> Result<i32, Error> {
+    Instruction::parse(input)
+        .and_then(|input| solve_1(&input))
+        .map_err(|e| e.in_day(Day08::DAY))
+}
+
+pub fn solve_part_2(input: &str) -> Result<i32, Error> {
+    Instruction::parse(input)
+        .and_then(|input| solve_2(&input))
+        .map_err(|e| e.in_day(Day08::DAY))
+}
+
+pub struct Day08;
+
+impl Solution for Day08 {
+    const DAY: u32 = 8;
+
+    type Input<'a> = Vec<Instruction>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+        Instruction::parse(input).map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+        solve_1(input)
+            .map(Answer::from)
+            .map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+        solve_2(input)
+            .map(|answer| Some(answer.into()))
+            .map_err(|e| e.in_day(Self::DAY))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     lazy_static! {
@@ -165,19 +240,45 @@ acc +6"
 
     #[test]
     fn same_results_part_1() {
-        assert_eq!(solve_1(&INPUT), 5);
+        assert_eq!(solve_1(&INPUT), Ok(5));
     }
 
     #[test]
     fn same_results_part_2() {
-        assert_eq!(solve_2(&INPUT), 8);
+        assert_eq!(solve_2(&INPUT), Ok(8));
+    }
+
+    #[test]
+    fn invalid_instruction() {
+        let error = Instruction::parse("nop +0\nacc +1\nfoo +4").unwrap_err();
+        assert_eq!(error.position, Some((3, 1)));
+        assert_eq!(error.snippet, "foo");
+
+        let error = Instruction::parse("nop +0\njmp x4").unwrap_err();
+        assert_eq!(error.position, Some((2, 5)));
+        assert_eq!(error.snippet, "x4");
+    }
+
+    #[test]
+    fn accumulator_overflow() {
+        assert!(solve_part_1("acc +2147483647\nacc +1\njmp +0").is_err());
+        assert!(solve_part_2("acc -2147483648\nacc -1\njmp +0").is_err());
+    }
+
+    #[test]
+    fn unfixable_program() {
+        let input = Instruction::parse("nop +0\njmp -1\njmp -2").expect("invalid input");
+
+        assert!(solve_2(&input).is_err());
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_1(b: &mut Bencher) {
         b.iter(part_1);
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_2(b: &mut Bencher) {
         b.iter(part_2);
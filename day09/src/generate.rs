@@ -0,0 +1,98 @@
+use common::Rng;
+use std::ops::RangeInclusive;
+
+const PREAMBLE: usize = 25;
+
+/// The sizes `generate` supports: the preamble, a valid number, a range
+/// and the invalid number need at least 28.
+pub const SIZES: RangeInclusive<usize> = PREAMBLE + 3..=usize::MAX;
+
+/// A list of `size` numbers (at least 28) after a preamble of 25: all valid
+/// up to the invalid one, which is the sum of a single contiguous range of
+/// numbers before it, and followed by larger numbers.
+///
+/// Each valid number is the sum of two of the smallest numbers before it,
+/// which keeps the numbers from growing too fast: about 2^40 times the
+/// preamble after 1000 of them, so the invalid number comes at most 1000
+/// numbers after the preamble.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(*SIZES.start());
+    let position = (size - 1).min(PREAMBLE + 1000);
+
+    let mut numbers = (1..=2 * PREAMBLE as u64).collect::<Vec<_>>();
+    rng.shuffle(&mut numbers);
+    numbers.truncate(PREAMBLE);
+
+    while numbers.len() < position {
+        let mut window = numbers[numbers.len() - PREAMBLE..].to_vec();
+        window.sort_unstable();
+        window.dedup();
+
+        let i = rng.below(3) as usize;
+        let j = rng.range(i as u64 + 1, 6) as usize;
+        numbers.push(window[i] + window[j]);
+    }
+
+    let invalid = loop {
+        let start = rng.range(1, position as u64 - 2) as usize;
+        let end = rng.range(start as u64 + 2, (start as u64 + 17).min(position as u64)) as usize;
+        let invalid = numbers[start..end].iter().sum::<u64>();
+
+        let window = &numbers[position - PREAMBLE..];
+        let is_sum = window
+            .iter()
+            .enumerate()
+            .any(|(i, a)| window[i..].iter().any(|b| a + b == invalid));
+
+        if !is_sum && ranges(&numbers, invalid) == vec![(start, end)] {
+            break invalid;
+        }
+    };
+    numbers.push(invalid);
+
+    while numbers.len() < size {
+        numbers.push(rng.range(invalid + 1, 2 * invalid));
+    }
+
+    numbers
+        .iter()
+        .map(|number| format!("{}\n", number))
+        .collect()
+}
+
+/// The ranges of `numbers` that sum to `target`, as `start..end`.
+fn ranges(numbers: &[u64], target: u64) -> Vec<(usize, usize)> {
+    let (mut ranges, mut start, mut sum) = (Vec::new(), 0, 0);
+
+    for (end, number) in numbers.iter().enumerate() {
+        sum += number;
+        while sum > target {
+            sum -= numbers[start];
+            start += 1;
+        }
+        if sum == target {
+            ranges.push((start, end + 1));
+        }
+    }
+
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_range() {
+        for (seed, size) in (0..20).zip([28, 100, 1000, 1500].iter().cycle()) {
+            let numbers = crate::parse(&generate(&mut Rng::new(seed), *size)).unwrap();
+            let invalid = crate::solve_1(&numbers, PREAMBLE).unwrap();
+            let ranges = ranges(&numbers, invalid);
+
+            assert_eq!(numbers.len(), *size);
+            assert_eq!(ranges.len(), 2);
+            assert_eq!(numbers[ranges[1].0], invalid);
+            assert!(crate::solve_2(&numbers, PREAMBLE).is_ok());
+        }
+    }
+}
@@ -1,21 +1,37 @@
-#![feature(test)]
+#![cfg_attr(feature = "bench", feature(test))]
+
+#[cfg(feature = "bench")]
 extern crate test;
 
+#[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
 
-lazy_static! {
-    static ref INPUT: Vec<u64> = parse(include_str!("../input"));
+use common::{Answer, Error, Solution};
+
+mod generate;
+
+pub use generate::{generate, SIZES};
+
+/// The embedded puzzle input, parsed on first use.
+#[cfg(feature = "input")]
+fn input() -> &'static Vec<u64> {
+    static INPUT: std::sync::OnceLock<Vec<u64>> = std::sync::OnceLock::new();
+
+    INPUT.get_or_init(|| parse(include_str!("../input")).expect("invalid input"))
 }
 
-fn parse(input: &str) -> Vec<u64> {
+fn parse(input: &str) -> Result<Vec<u64>, Error> {
     input
         .lines()
-        .map(|line| line.parse().expect("invalid number"))
+        .map(|line| {
+            line.parse()
+                .map_err(|_| Error::at(input, line, "invalid number"))
+        })
         .collect()
 }
 
-fn solve_1(input: &[u64], length: usize) -> u64 {
+fn solve_1(input: &[u64], length: usize) -> Result<u64, Error> {
     input
         .windows(length + 1)
         .try_fold((), |_, seq| {
@@ -25,7 +41,7 @@ fn solve_1(input: &[u64], length: usize) -> u64 {
 
             'outer: for (i, a) in seq.iter().enumerate().take(seq.len() - 2) {
                 for b in seq.iter().take(seq.len() - 1).skip(i) {
-                    if target == a + b {
+                    if a.checked_add(*b) == Some(target) {
                         found = true;
                         break 'outer;
                     }
@@ -38,21 +54,30 @@ fn solve_1(input: &[u64], length: usize) -> u64 {
                 Err(target)
             }
         })
-        .expect_err("not found")
+        .err()
+        .ok_or_else(|| {
+            Error::new(format!(
+                "every number is the sum of two of the {} before it",
+                length
+            ))
+        })
 }
 
-fn solve_2(input: &[u64], length: usize) -> u64 {
-    let target = solve_1(input, length);
+fn solve_2(input: &[u64], length: usize) -> Result<u64, Error> {
+    let target = solve_1(input, length)?;
 
     let sums = input
         .iter()
-        .scan(0, |s, value| {
-            *s += value;
+        .scan(0u64, |s, value| {
+            *s = s.checked_add(*value)?;
             Some(*s)
         })
         .collect::<Vec<_>>();
+    if sums.len() < input.len() {
+        return Err(Error::new("the sum of the numbers overflows"));
+    }
 
-    for (i, a) in sums.iter().enumerate().take(sums.len() - 1) {
+    for (i, a) in sums.iter().enumerate().take(sums.len().saturating_sub(1)) {
         for (j, b) in sums.iter().enumerate().skip(i + 1) {
             if target == b - a {
                 let (min, max) = input
@@ -62,25 +87,69 @@ fn solve_2(input: &[u64], length: usize) -> u64 {
                     .fold((u64::MAX, u64::MIN), |(min, max), &value| {
                         (std::cmp::min(min, value), std::cmp::max(max, value))
                     });
-                return min + max;
+                return min
+                    .checked_add(max)
+                    .ok_or_else(|| Error::new("the sum of the min and max overflows"));
             }
         }
     }
 
-    unimplemented!()
+    Err(Error::new(format!(
+        "no contiguous range sums to {}",
+        target
+    )))
 }
 
+#[cfg(feature = "input")]
 pub fn part_1() -> u64 {
-    solve_1(&INPUT, 25)
+    solve_1(input(), 25).expect("invalid input")
 }
 
+#[cfg(feature = "input")]
 pub fn part_2() -> u64 {
-    solve_2(&INPUT, 25)
+    solve_2(input(), 25).expect("invalid input")
+}
+
+pub fn solve_part_1(input: &str) -> Result<u64, Error> {
+    parse(input)
+        .and_then(|input| solve_1(&input, 25))
+        .map_err(|e| e.in_day(Day09::DAY))
+}
+
+pub fn solve_part_2(input: &str) -> Result<u64, Error> {
+    parse(input)
+        .and_then(|input| solve_2(&input, 25))
+        .map_err(|e| e.in_day(Day09::DAY))
+}
+
+pub struct Day09;
+
+impl Solution for Day09 {
+    const DAY: u32 = 9;
+
+    type Input<'a> = Vec<u64>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+        parse(input).map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+        solve_1(input, 25)
+            .map(Answer::from)
+            .map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+        solve_2(input, 25)
+            .map(|answer| Some(answer.into()))
+            .map_err(|e| e.in_day(Self::DAY))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     lazy_static! {
@@ -105,24 +174,34 @@ mod tests {
 277
 309
 576"
-        );
+        )
+        .expect("invalid input");
     }
 
     #[test]
     fn same_results_part_1() {
-        assert_eq!(solve_1(&INPUT, 5), 127);
+        assert_eq!(solve_1(&INPUT, 5), Ok(127));
     }
 
     #[test]
     fn same_results_part_2() {
-        assert_eq!(solve_2(&INPUT, 5), 62);
+        assert_eq!(solve_2(&INPUT, 5), Ok(62));
+    }
+
+    #[test]
+    fn overflow() {
+        let input = [u64::MAX, u64::MAX, 5];
+        assert_eq!(solve_1(&input, 2), Ok(5));
+        assert!(solve_2(&input, 2).is_err());
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_1(b: &mut Bencher) {
         b.iter(part_1);
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_2(b: &mut Bencher) {
         b.iter(part_2);
@@ -0,0 +1,50 @@
+use common::Rng;
+
+/// The number of arrangements of a run of up to four differences of 1.
+const ARRANGEMENTS: [u128; 5] = [1, 1, 2, 4, 7];
+
+/// `size` adapters, in random order, as runs of up to four differences of 1
+/// separated by differences of 3, the way the puzzle inputs are.
+///
+/// The number of arrangements is the product of those of each run, so runs
+/// longer than one difference stop once it gets past 2^100, to leave the
+/// answer room in a `u128`.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let (mut adapters, mut joltage, mut arrangements) = (Vec::new(), 0, 1u128);
+
+    while adapters.len() < size {
+        let mut run = rng.range(1, 4) as usize;
+        if arrangements.saturating_mul(ARRANGEMENTS[run]) >= 1 << 100 {
+            run = 1;
+        }
+        arrangements *= ARRANGEMENTS[run];
+
+        for _ in 0..run.min(size - adapters.len()) {
+            joltage += 1;
+            adapters.push(joltage);
+        }
+        joltage += 2;
+    }
+
+    rng.shuffle(&mut adapters);
+    adapters
+        .iter()
+        .map(|adapter| format!("{}\n", adapter))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_input() {
+        for (seed, size) in (0..10).zip([1, 50, 1000].iter().cycle()) {
+            let input = crate::parse(&generate(&mut Rng::new(seed), *size)).unwrap();
+
+            assert_eq!(input.len(), *size);
+            assert!(crate::differences(&input).is_ok());
+            assert!(crate::solve_2(&input).unwrap() < 1 << 100);
+        }
+    }
+}
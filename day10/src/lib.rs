@@ -1,37 +1,57 @@
-#![feature(test)]
+#![cfg_attr(feature = "bench", feature(test))]
+
+#[cfg(feature = "bench")]
 extern crate test;
 
+#[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
 
+use common::{Answer, Error, Solution};
 use itertools::Itertools;
 use std::collections::HashSet;
 
-lazy_static! {
-    static ref INPUT: Vec<u32> = parse(include_str!("../input"));
+mod generate;
+
+pub use generate::generate;
+
+/// The embedded puzzle input, parsed on first use.
+#[cfg(feature = "input")]
+fn input() -> &'static Vec<u32> {
+    static INPUT: std::sync::OnceLock<Vec<u32>> = std::sync::OnceLock::new();
+
+    INPUT.get_or_init(|| parse(include_str!("../input")).expect("invalid input"))
 }
 
-fn parse(input: &str) -> Vec<u32> {
+fn parse(input: &str) -> Result<Vec<u32>, Error> {
     input
         .lines()
-        .map(|line| line.parse().expect("invalid number"))
+        .map(|line| {
+            line.parse()
+                .map_err(|_| Error::at(input, line, "invalid number"))
+        })
         .collect()
 }
 
-fn solve_1(input: &[u32]) -> [u32; 3] {
-    let mut adapters = input.iter().copied().collect::<Vec<u32>>();
+fn solve_1(input: &[u32]) -> Result<[u32; 3], Error> {
+    let mut adapters = input.to_vec();
 
     adapters.sort_unstable();
 
     let [a, b, c] = adapters
         .windows(2)
-        .map(|d| d[1] - d[0])
-        .fold([0, 0, 0], |mut d, v| {
-            d[v as usize - 1] += 1;
-            d
-        });
+        .try_fold([0, 0, 0], |mut d, w| match w[1] - w[0] {
+            v @ 1..=3 => {
+                d[v as usize - 1] += 1;
+                Ok(d)
+            }
+            v => Err(Error::new(format!(
+                "adapters {} and {} differ by {} jolts",
+                w[0], w[1], v
+            ))),
+        })?;
 
-    [a + 1, b, c + 1]
+    Ok([a + 1, b, c + 1])
 }
 
 fn solve_2_r(adapters: &[u32], removed: HashSet<usize>) -> u128 {
@@ -60,16 +80,29 @@ fn solve_2_r(adapters: &[u32], removed: HashSet<usize>) -> u128 {
     }
 }
 
-fn solve_2(input: &[u32]) -> u128 {
+fn solve_2(input: &[u32]) -> Result<u128, Error> {
+    let max = input
+        .iter()
+        .max()
+        .ok_or_else(|| Error::new("no adapters"))?;
     let mut adapters = input
         .iter()
         .copied()
         .chain(std::iter::once(0))
-        .chain(std::iter::once(input.iter().max().unwrap() + 3))
+        .chain(std::iter::once(max.checked_add(3).ok_or_else(|| {
+            Error::new(format!("the device is 3 jolts above {}, too high", max))
+        })?))
         .collect::<Vec<_>>();
 
     adapters.sort_unstable();
 
+    if let Some(w) = adapters.windows(2).find(|w| w[0] == w[1]) {
+        return Err(Error::new(format!(
+            "adapters {} and {} differ by 0 jolts",
+            w[0], w[1]
+        )));
+    }
+
     std::iter::once(0)
         .chain(adapters.windows(2).enumerate().filter_map(|(i, v)| {
             if v[1] - v[0] == 3 {
@@ -80,22 +113,96 @@ fn solve_2(input: &[u32]) -> u128 {
         }))
         .tuple_windows()
         .map(|(a, b)| solve_2_r(&adapters[a..=b], HashSet::new()))
-        .product()
+        .try_fold(1u128, |arrangements, n| arrangements.checked_mul(n))
+        .ok_or_else(|| Error::new("the number of arrangements overflows"))
 }
 
-pub fn part_1() -> u32 {
-    let r = solve_1(&INPUT);
+/// Adapters that are not 1 to 3 jolts above the next lower one (or the
+/// outlet), which neither part can chain.
+fn lint(text: &str) -> Vec<Error> {
+    let mut adapters = text
+        .lines()
+        .filter_map(|line| Some((line.parse::<u32>().ok()?, line)))
+        .collect::<Vec<_>>();
+    adapters.sort_by_key(|&(jolts, _)| jolts);
+
+    let mut below = (0, "the outlet".to_string());
+    let mut lints = Vec::new();
+    for (jolts, line) in adapters {
+        let difference = jolts - below.0;
+        if !(1..=3).contains(&difference) {
+            lints.push(Error::at(
+                text,
+                line,
+                format!("{} jolts above {}, expecting 1 to 3", difference, below.1),
+            ));
+        }
+        below = (jolts, format!("adapter {}", jolts));
+    }
 
-    r[0] * r[2]
+    lints
 }
 
+fn differences(input: &[u32]) -> Result<u32, Error> {
+    let r = solve_1(input)?;
+
+    Ok(r[0] * r[2])
+}
+
+#[cfg(feature = "input")]
+pub fn part_1() -> u32 {
+    differences(input()).expect("invalid input")
+}
+
+#[cfg(feature = "input")]
 pub fn part_2() -> u128 {
-    solve_2(&INPUT)
+    solve_2(input()).expect("invalid input")
+}
+
+pub fn solve_part_1(input: &str) -> Result<u32, Error> {
+    parse(input)
+        .and_then(|input| differences(&input))
+        .map_err(|e| e.in_day(Day10::DAY))
+}
+
+pub fn solve_part_2(input: &str) -> Result<u128, Error> {
+    parse(input)
+        .and_then(|input| solve_2(&input))
+        .map_err(|e| e.in_day(Day10::DAY))
+}
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u32 = 10;
+
+    type Input<'a> = Vec<u32>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+        parse(input).map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+        differences(input)
+            .map(Answer::from)
+            .map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+        solve_2(input)
+            .map(|answer| Some(answer.into()))
+            .map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn lint(text: &str, _input: &Self::Input<'_>) -> Vec<Error> {
+        lint(text)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     lazy_static! {
@@ -111,7 +218,8 @@ mod tests {
 6
 12
 4",
-        );
+        )
+        .expect("invalid input");
         static ref INPUT_2: Vec<u32> = parse(
             r"28
 33
@@ -144,43 +252,79 @@ mod tests {
 34
 10
 3",
-        );
+        )
+        .expect("invalid input");
     }
 
     #[test]
     fn same_results_part_1_1() {
-        assert_eq!(solve_1(&INPUT_1), [7, 0, 5]);
+        assert_eq!(solve_1(&INPUT_1), Ok([7, 0, 5]));
     }
 
     #[test]
     fn same_results_part_1_2() {
-        assert_eq!(solve_1(&INPUT_2), [22, 0, 10]);
+        assert_eq!(solve_1(&INPUT_2), Ok([22, 0, 10]));
     }
 
     #[test]
     fn same_results_part_2_1() {
-        assert_eq!(solve_2(&INPUT_1), 8);
+        assert_eq!(solve_2(&INPUT_1), Ok(8));
     }
 
     #[test]
     fn same_results_part_2_2() {
-        assert_eq!(solve_2(&INPUT_2), 19208);
+        assert_eq!(solve_2(&INPUT_2), Ok(19208));
     }
 
     #[test]
     fn test_solve_2_r_split() {
         assert_eq!(
-            solve_2_r(&[1, 4], HashSet::new())
-                * solve_2_r(&[4, 5, 7, 8, 9], HashSet::new()),
+            solve_2_r(&[1, 4], HashSet::new()) * solve_2_r(&[4, 5, 7, 8, 9], HashSet::new()),
             5
         );
     }
 
+    #[test]
+    fn duplicate_adapters() {
+        assert!(solve_1(&[1, 4, 4, 5]).is_err());
+    }
+
+    #[test]
+    fn duplicate_adapters_part_2() {
+        let error =
+            solve_2(&[1, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16]).unwrap_err();
+        assert_eq!(error.message, "adapters 1 and 1 differ by 0 jolts");
+    }
+
+    #[test]
+    fn device_overflow() {
+        assert!(solve_2(&[1, u32::MAX]).is_err());
+    }
+
+    #[test]
+    fn lint_gaps() {
+        let lints = lint("5\n1\n4\n4\n9");
+
+        assert_eq!(
+            lints
+                .iter()
+                .map(|lint| (lint.line(), lint.message.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Some(4), "0 jolts above adapter 4, expecting 1 to 3"),
+                (Some(5), "4 jolts above adapter 5, expecting 1 to 3"),
+            ]
+        );
+        assert!(lint(include_str!("../input")).is_empty());
+    }
+
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_1(b: &mut Bencher) {
         b.iter(part_1);
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_2(b: &mut Bencher) {
         b.iter(part_2);
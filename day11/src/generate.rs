@@ -0,0 +1,60 @@
+use crate::SeatingSystem;
+use common::Rng;
+
+/// A `size`×`size` layout of empty seats, with about one position in six
+/// being floor, like the puzzle inputs.
+///
+/// Some layouts never settle, but end up alternating between two states,
+/// which the solvers would loop on forever: those are drawn again.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+
+    loop {
+        let layout = (0..size)
+            .map(|_| {
+                let mut row = (0..size)
+                    .map(|_| if rng.chance(1.0 / 6.0) { '.' } else { 'L' })
+                    .collect::<String>();
+                row.push('\n');
+                row
+            })
+            .collect::<String>();
+
+        let system = layout.parse::<SeatingSystem>().expect("invalid layout");
+        if settles(&system, SeatingSystem::evolve_near, size)
+            && settles(&system, SeatingSystem::evolve_range, size)
+        {
+            return layout;
+        }
+    }
+}
+
+/// Whether `system` settles within `10 * size` rounds, far more than any
+/// layout that does settle takes.
+fn settles<F: Fn(&mut SeatingSystem)>(system: &SeatingSystem, evolve: F, size: usize) -> bool {
+    let mut system = system.clone();
+
+    (0..10 * size).any(|_| {
+        let previous = system.clone();
+        evolve(&mut system);
+        system == previous
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_input() {
+        for seed in 0..5 {
+            let input = generate(&mut Rng::new(seed), 30)
+                .parse::<SeatingSystem>()
+                .unwrap();
+
+            assert_eq!((input.0.width(), input.0.height()), (30, 30));
+            assert!(crate::solve_1(&input) > 0);
+            assert!(crate::solve_2(&input) > 0);
+        }
+    }
+}
@@ -1,197 +1,114 @@
-#![feature(test)]
+#![cfg_attr(feature = "bench", feature(test))]
+
+#[cfg(feature = "bench")]
 extern crate test;
 
+#[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
 
-lazy_static! {
-    static ref INPUT: SeatingSystem = include_str!("../input").parse().expect("invalid input");
+use common::{Answer, Error, Solution};
+use grid::{Grid, Position, NEIGHBOURS_8};
+
+mod generate;
+
+pub use generate::generate;
+
+/// The embedded puzzle input, parsed on first use.
+#[cfg(feature = "input")]
+fn input() -> &'static SeatingSystem {
+    static INPUT: std::sync::OnceLock<SeatingSystem> = std::sync::OnceLock::new();
+
+    INPUT.get_or_init(|| include_str!("../input").parse().expect("invalid input"))
 }
 
 #[derive(PartialEq, Clone, Copy)]
-enum PositionType {
+pub enum PositionType {
     Floor,
     EmptySeat,
     OccupiedSeat,
 }
 
 #[derive(PartialEq, Clone)]
-struct SeatingSystem {
-    width: usize,
-    height: usize,
-    layout: Vec<Vec<PositionType>>,
-}
+pub struct SeatingSystem(Grid<PositionType>);
 
 impl SeatingSystem {
     fn count_occupied_seats(&self) -> usize {
-        self.layout
-            .iter()
-            .map(|v| {
-                v.iter()
-                    .filter(|&&c| c == PositionType::OccupiedSeat)
-                    .count()
-            })
-            .sum()
+        self.0
+            .cells()
+            .filter(|&&c| c == PositionType::OccupiedSeat)
+            .count()
     }
 
-    fn evolve_near(&mut self) {
+    /// The next generation, with `count` the occupied seats that a seat of
+    /// the current layout sees.
+    fn evolve<F: Fn(&Grid<PositionType>, Position) -> usize>(&mut self, count: F, crowded: usize) {
         use PositionType::*;
 
-        const DELTAS: [(isize, isize); 8] = [
-            (-1, -1),
-            (0, -1),
-            (1, -1),
-            (-1, 0),
-            (1, 0),
-            (-1, 1),
-            (0, 1),
-            (1, 1),
-        ];
-
-        self.layout = self
-            .layout
-            .iter()
-            .enumerate()
-            .map(|(y, v)| {
-                v.iter()
-                    .enumerate()
-                    .map(|(x, c)| {
-                        let mut count_mem = None;
-                        let mut count = || -> usize {
-                            if let Some(count) = count_mem {
-                                count
-                            } else {
-                                let c = DELTAS
-                                    .iter()
-                                    .filter_map(|(dx, dy)| {
-                                        match self.get((x as isize + dx, y as isize + dy)) {
-                                            Some(OccupiedSeat) => Some(()),
-                                            _ => None,
-                                        }
-                                    })
-                                    .count();
-                                
-                                count_mem = Some(c);
-                                
-                                c
-                            }
-                        };
-
-                        match c {
-                            EmptySeat if count() == 0 => OccupiedSeat,
-                            OccupiedSeat if count() >= 4 => EmptySeat,
-                            _ => *c,
-                        }
-                    })
-                    .collect()
-            })
-            .collect();
+        let layout = &self.0;
+        self.0 = layout.map(|position, c| match c {
+            EmptySeat if count(layout, position) == 0 => OccupiedSeat,
+            OccupiedSeat if count(layout, position) >= crowded => EmptySeat,
+            _ => *c,
+        });
     }
 
-    fn evolve_range(&mut self) {
-        use PositionType::*;
+    fn evolve_near(&mut self) {
+        self.evolve(
+            |layout, position| {
+                layout
+                    .neighbours(position, &NEIGHBOURS_8)
+                    .filter(|(_, &c)| c == PositionType::OccupiedSeat)
+                    .count()
+            },
+            4,
+        );
+    }
 
-        const DIRECTIONS: [(isize, isize); 8] = [
-            (-1, -1),
-            (0, -1),
-            (1, -1),
-            (-1, 0),
-            (1, 0),
-            (-1, 1),
-            (0, 1),
-            (1, 1),
-        ];
-
-        self.layout = self
-            .layout
-            .iter()
-            .enumerate()
-            .map(|(y, v)| {
-                v.iter()
-                    .enumerate()
-                    .map(|(x, c)| {
-                        let mut count_mem = None;
-                        let mut count = || -> usize {
-                            if let Some(count) = count_mem {
-                                count
-                            } else {
-                                let c = DIRECTIONS
-                                    .iter()
-                                    .map(|(dx, dy)| {
-                                        (1..)
-                                            .try_fold((), |_, d| {
-                                                match self
-                                                    .get((x as isize + dx * d, y as isize + dy * d))
-                                                {
-                                                    Some(OccupiedSeat) => Err(1),
-                                                    Some(EmptySeat) | None => Err(0),
-                                                    _ => Ok(()),
-                                                }
-                                            })
-                                            .expect_err("invalid")
-                                    })
-                                    .sum();
-
-                                count_mem = Some(c);
-
-                                c
-                            }
-                        };
-
-                        match c {
-                            EmptySeat if count() == 0 => OccupiedSeat,
-                            OccupiedSeat if count() >= 5 => EmptySeat,
-                            _ => *c,
-                        }
+    fn evolve_range(&mut self) {
+        self.evolve(
+            |layout, position| {
+                NEIGHBOURS_8
+                    .iter()
+                    .filter(|&&direction| {
+                        layout
+                            .ray(position, direction)
+                            .map(|(_, &c)| c)
+                            .find(|&c| c != PositionType::Floor)
+                            == Some(PositionType::OccupiedSeat)
                     })
-                    .collect()
-            })
-            .collect();
+                    .count()
+            },
+            5,
+        );
     }
+}
 
-    #[inline]
-    fn get(&self, (x, y): (isize, isize)) -> Option<PositionType> {
-        if x < 0 || y < 0 {
-            None
-        } else {
-            self.layout
-                .get(y as usize)
-                .and_then(|v| v.get(x as usize).cloned())
-        }
+impl std::fmt::Display for SeatingSystem {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use PositionType::*;
+
+        fmt.write_str(&self.0.render(|c| match c {
+            Floor => '.',
+            EmptySeat => 'L',
+            OccupiedSeat => '#',
+        }))
     }
 }
 
 impl std::str::FromStr for SeatingSystem {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
         use PositionType::*;
 
-        let layout: Vec<Vec<PositionType>> = input
-            .lines()
-            .map(|line| {
-                line.chars()
-                    .map(|c| match c {
-                        '.' => Ok(Floor),
-                        '#' => Ok(OccupiedSeat),
-                        'L' => Ok(EmptySeat),
-                        _ => Err(format!("invalid position: {}", c)),
-                    })
-                    .collect::<Result<_, Self::Err>>()
-            })
-            .collect::<Result<_, Self::Err>>()?;
-
-        let width = layout
-            .get(0)
-            .ok_or_else(|| "invalid layout: no rows".to_string())?
-            .len();
-        let height = layout.len();
-
-        Ok(Self {
-            layout,
-            width,
-            height,
+        Grid::parse(input, "invalid position", |c| match c {
+            '.' => Some(Floor),
+            '#' => Some(OccupiedSeat),
+            'L' => Some(EmptySeat),
+            _ => None,
         })
+        .map(Self)
     }
 }
 
@@ -218,17 +135,123 @@ fn solve_2(seating_system: &SeatingSystem) -> usize {
     solve(seating_system, SeatingSystem::evolve_range)
 }
 
+#[cfg(feature = "input")]
 pub fn part_1() -> usize {
-    solve_1(&INPUT)
+    solve_1(input())
 }
 
+#[cfg(feature = "input")]
 pub fn part_2() -> usize {
-    solve_2(&INPUT)
+    solve_2(input())
+}
+
+pub fn solve_part_1(input: &str) -> Result<usize, Error> {
+    input
+        .parse::<SeatingSystem>()
+        .map(|input| solve_1(&input))
+        .map_err(|e| e.in_day(Day11::DAY))
+}
+
+pub fn solve_part_2(input: &str) -> Result<usize, Error> {
+    input
+        .parse::<SeatingSystem>()
+        .map(|input| solve_2(&input))
+        .map_err(|e| e.in_day(Day11::DAY))
+}
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    const DAY: u32 = 11;
+
+    type Input<'a> = SeatingSystem;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+        input
+            .parse::<SeatingSystem>()
+            .map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+        Ok(solve_1(input).into())
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+        Ok(Some(solve_2(input).into()))
+    }
+
+    fn generations(
+        input: &Self::Input<'_>,
+        part: usize,
+    ) -> Option<Box<dyn Iterator<Item = String>>> {
+        let evolve = match part {
+            1 => SeatingSystem::evolve_near,
+            2 => SeatingSystem::evolve_range,
+            _ => return None,
+        };
+
+        let mut state = Some(input.clone());
+        Some(Box::new(std::iter::from_fn(move || {
+            let current = state.take()?;
+
+            let mut next = current.clone();
+            evolve(&mut next);
+            if next != current {
+                state = Some(next);
+            }
+
+            Some(current.to_string())
+        })))
+    }
+
+    const COMMANDS: &'static [(&'static str, &'static str)] = &[
+        (
+            "step <near|range> [n]",
+            "evolve n rounds (default 1) with the rules of part 1 or 2",
+        ),
+        ("render", "print the layout"),
+    ];
+
+    fn explore(
+        input: &mut Self::Input<'_>,
+        command: &str,
+        args: &[&str],
+    ) -> Option<Result<String, Error>> {
+        let (rule, rounds) = match (command, args) {
+            ("render", []) => return Some(Ok(input.to_string())),
+            ("step", [rule]) => (*rule, "1"),
+            ("step", [rule, rounds]) => (*rule, *rounds),
+            ("step", _) => return Some(Err(Error::new("expecting `step <near|range> [n]`"))),
+            _ => return None,
+        };
+
+        let evolve = match rule {
+            "near" => SeatingSystem::evolve_near,
+            "range" => SeatingSystem::evolve_range,
+            _ => return Some(Err(Error::new(format!("invalid rule: {}", rule)))),
+        };
+        let rounds = match rounds.parse::<usize>() {
+            Ok(rounds) => rounds,
+            Err(_) => return Some(Err(Error::new(format!("invalid rounds: {}", rounds)))),
+        };
+
+        let previous = input.clone();
+        for _ in 0..rounds {
+            evolve(input);
+        }
+
+        Some(Ok(format!(
+            "{} occupied seats{}",
+            input.count_occupied_seats(),
+            if *input == previous { ", settled" } else { "" }
+        )))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     lazy_static! {
@@ -256,11 +279,54 @@ L.LLLLL.LL"
         assert_eq!(solve_2(&INPUT), 26);
     }
 
+    #[test]
+    fn invalid_position() {
+        let error = "L.LL\nL.#x"
+            .parse::<SeatingSystem>()
+            .map(drop)
+            .expect_err("invalid position");
+
+        assert_eq!(error.position, Some((2, 4)));
+        assert_eq!(error.snippet, "x");
+    }
+
+    #[test]
+    fn generations() {
+        let frames = Day11::generations(&INPUT, 1)
+            .expect("no generations")
+            .collect::<Vec<_>>();
+
+        assert_eq!(frames.len(), 6);
+        assert_eq!(frames[0], INPUT.to_string());
+        assert_eq!(frames[5].matches('#').count(), 37);
+    }
+
+    #[test]
+    fn step() {
+        let mut input = INPUT.clone();
+
+        assert_eq!(
+            Day11::explore(&mut input, "step", &["near", "2"]),
+            Some(Ok("20 occupied seats".to_string()))
+        );
+        assert_eq!(input.to_string().lines().next(), Some("#.LL.L#.##"));
+        assert_eq!(
+            Day11::explore(&mut input, "step", &["near", "10"]),
+            Some(Ok("37 occupied seats".to_string()))
+        );
+        assert_eq!(
+            Day11::explore(&mut input, "step", &["near"]),
+            Some(Ok("37 occupied seats, settled".to_string()))
+        );
+    }
+
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_1(b: &mut Bencher) {
         b.iter(part_1);
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_2(b: &mut Bencher) {
         b.iter(part_2);
@@ -0,0 +1,35 @@
+use common::Rng;
+
+/// `size` navigation instructions, in the proportions of the puzzle inputs:
+/// mostly moves and `F`, with a rotation about every fourth instruction.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let action = *rng.choose(b"NSEWLRFFF") as char;
+            let value = match action {
+                'L' | 'R' => 90 * rng.range(1, 3),
+                'F' => rng.range(1, 100),
+                _ => rng.range(1, 5),
+            };
+
+            format!("{}{}\n", action, value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Move;
+
+    #[test]
+    fn valid_input() {
+        for seed in 0..10 {
+            let moves = Move::parse(&generate(&mut Rng::new(seed), 800)).unwrap();
+
+            assert_eq!(moves.len(), 800);
+            assert!(crate::solve_1(&moves) > 0);
+            assert!(crate::solve_2(&moves) > 0);
+        }
+    }
+}
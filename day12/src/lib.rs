@@ -1,16 +1,29 @@
-#![feature(test)]
+#![cfg_attr(feature = "bench", feature(test))]
+
+#[cfg(feature = "bench")]
 extern crate test;
 
+#[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
 
-lazy_static! {
-    static ref INPUT: Vec<Move> = Move::parse(include_str!("../input")).expect("invalid input");
+use common::{Answer, Error, Picture, Solution, Svg};
+
+mod generate;
+
+pub use generate::generate;
+
+/// The embedded puzzle input, parsed on first use.
+#[cfg(feature = "input")]
+fn input() -> &'static Vec<Move> {
+    static INPUT: std::sync::OnceLock<Vec<Move>> = std::sync::OnceLock::new();
+
+    INPUT.get_or_init(|| Move::parse(include_str!("../input")).expect("invalid input"))
 }
 
 type Integer = i128;
 
-enum Move {
+pub enum Move {
     North(i32),
     South(i32),
     East(i32),
@@ -21,20 +34,32 @@ enum Move {
 }
 
 impl Move {
-    fn parse(input: &str) -> Result<Vec<Move>, String> {
-        input.lines().map(|line| line.parse()).collect()
+    fn parse(input: &str) -> Result<Vec<Move>, Error> {
+        input
+            .lines()
+            .map(|line| line.parse().map_err(|e: Error| e.within(input, line)))
+            .collect()
     }
 }
 
 impl std::str::FromStr for Move {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(line: &str) -> Result<Self, Self::Err> {
         use Move::*;
 
-        let (m, d) = line.split_at(1);
+        let first = line
+            .chars()
+            .next()
+            .ok_or_else(|| Error::new("expecting a move"))?;
+        let (m, d) = line.split_at(first.len_utf8());
         match (m, d.parse::<i32>()) {
-            (_, Err(_)) => Err(format!("invalid number: {}", d)),
+            (_, Err(_)) => Err(Error::at(line, d, "invalid number")),
+            ("L", Ok(v)) | ("R", Ok(v)) if v < 0 || v % 90 != 0 => Err(Error::at(
+                line,
+                d,
+                "expecting a rotation by a multiple of 90 degrees",
+            )),
             ("N", Ok(v)) => Ok(North(v)),
             ("S", Ok(v)) => Ok(South(v)),
             ("E", Ok(v)) => Ok(East(v)),
@@ -42,7 +67,7 @@ impl std::str::FromStr for Move {
             ("L", Ok(v)) => Ok(RotateLeft(v)),
             ("R", Ok(v)) => Ok(RotateRight(v)),
             ("F", Ok(v)) => Ok(Forward(v)),
-            (_, _) => Err(format!("invalid move: {}", m)),
+            (_, _) => Err(Error::at(line, m, "invalid move")),
         }
     }
 }
@@ -93,19 +118,15 @@ impl Action for Facing {
     }
 }
 
+#[derive(Clone, Copy)]
 struct Waypoint(Integer, Integer);
 
 impl Waypoint {
     fn rotate(&self, v: i32) -> Self {
-        let (cosv, sinv) = (
-            [1, 0, -1, 0][((v / 90 + 4) % 4) as usize],
-            [0, 1, 0, -1][((v / 90 + 4) % 4) as usize],
-        );
+        let quarter = (v / 90).rem_euclid(4) as usize;
+        let (cosv, sinv) = ([1, 0, -1, 0][quarter], [0, 1, 0, -1][quarter]);
 
-        Waypoint(
-            self.0 * cosv - self.1 * sinv,
-            self.0 * sinv + self.1 * cosv,
-        )
+        Waypoint(self.0 * cosv - self.1 * sinv, self.0 * sinv + self.1 * cosv)
     }
 }
 
@@ -123,54 +144,162 @@ impl Action for Waypoint {
     }
 }
 
-fn solve_1(moves: &[Move]) -> Integer {
-    let ((x, y), _) = moves.iter().fold(
-        ((Integer::from(0), Integer::from(0)), Facing::East),
-        |((x, y), f), m| match m {
-            Move::North(v) => ((x, y + Integer::from(*v)), f),
-            Move::South(v) => ((x, y - Integer::from(*v)), f),
-            Move::East(v) => ((x + Integer::from(*v), y), f),
-            Move::West(v) => ((x - Integer::from(*v), y), f),
-            Move::RotateLeft(v) => ((x, y), f.rotate_left(*v)),
-            Move::RotateRight(v) => ((x, y), f.rotate_right(*v)),
-            Move::Forward(v) => (f.forward((x, y), *v), f),
-        },
-    );
+/// The positions of the ship, from the start and after every move.
+fn track_1(moves: &[Move]) -> Vec<(Integer, Integer)> {
+    std::iter::once((0, 0))
+        .chain(moves.iter().scan(
+            ((Integer::from(0), Integer::from(0)), Facing::East),
+            |((x, y), f), m| {
+                let (position, facing) = match m {
+                    Move::North(v) => ((*x, *y + Integer::from(*v)), *f),
+                    Move::South(v) => ((*x, *y - Integer::from(*v)), *f),
+                    Move::East(v) => ((*x + Integer::from(*v), *y), *f),
+                    Move::West(v) => ((*x - Integer::from(*v), *y), *f),
+                    Move::RotateLeft(v) => ((*x, *y), f.rotate_left(*v)),
+                    Move::RotateRight(v) => ((*x, *y), f.rotate_right(*v)),
+                    Move::Forward(v) => (f.forward((*x, *y), *v), *f),
+                };
+                *f = facing;
+                (*x, *y) = position;
+                Some(position)
+            },
+        ))
+        .collect()
+}
+
+/// The positions of the ship steered by the waypoint, from the start and
+/// after every move.
+fn track_2(moves: &[Move]) -> Vec<(Integer, Integer)> {
+    std::iter::once((0, 0))
+        .chain(moves.iter().scan(
+            (
+                (Integer::from(0), Integer::from(0)),
+                Waypoint(Integer::from(10), Integer::from(1)),
+            ),
+            |((x, y), waypoint), m| {
+                let Waypoint(wx, wy) = *waypoint;
+                *waypoint = match m {
+                    Move::North(v) => Waypoint(wx, wy + Integer::from(*v)),
+                    Move::South(v) => Waypoint(wx, wy - Integer::from(*v)),
+                    Move::East(v) => Waypoint(wx + Integer::from(*v), wy),
+                    Move::West(v) => Waypoint(wx - Integer::from(*v), wy),
+                    Move::RotateLeft(v) => waypoint.rotate_left(*v),
+                    Move::RotateRight(v) => waypoint.rotate_right(*v),
+                    Move::Forward(v) => {
+                        (*x, *y) = waypoint.forward((*x, *y), *v);
+                        Waypoint(wx, wy)
+                    }
+                };
+                Some((*x, *y))
+            },
+        ))
+        .collect()
+}
 
-    Integer::abs(x) + Integer::abs(y)
+fn distance(track: &[(Integer, Integer)]) -> Integer {
+    track
+        .last()
+        .map_or(0, |(x, y)| Integer::abs(*x) + Integer::abs(*y))
+}
+
+fn solve_1(moves: &[Move]) -> Integer {
+    distance(&track_1(moves))
 }
 
 fn solve_2(moves: &[Move]) -> Integer {
-    let ((x, y), _) = moves.iter().fold(
-        (
-            (Integer::from(0), Integer::from(0)),
-            Waypoint(Integer::from(10), Integer::from(1)),
-        ),
-        |((x, y), Waypoint(wx, wy)), m| match m {
-            Move::North(v) => ((x, y), Waypoint(wx, wy + Integer::from(*v))),
-            Move::South(v) => ((x, y), Waypoint(wx, wy - Integer::from(*v))),
-            Move::East(v) => ((x, y), Waypoint(wx + Integer::from(*v), wy)),
-            Move::West(v) => ((x, y), Waypoint(wx - Integer::from(*v), wy)),
-            Move::RotateLeft(v) => ((x, y), Waypoint(wx, wy).rotate_left(*v)),
-            Move::RotateRight(v) => ((x, y), Waypoint(wx, wy).rotate_right(*v)),
-            Move::Forward(v) => (Waypoint(wx, wy).forward((x, y), *v), Waypoint(wx, wy)),
-        },
+    distance(&track_2(moves))
+}
+
+/// The track of the ship, north up, from a green dot to a red one.
+fn picture(track: &[(Integer, Integer)]) -> Svg {
+    let points = track
+        .iter()
+        .map(|&(x, y)| (x as f64, -y as f64))
+        .collect::<Vec<_>>();
+
+    let bounds = |f: fn(&(f64, f64)) -> f64| {
+        points
+            .iter()
+            .map(f)
+            .fold((0.0, 0.0), |(min, max): (f64, f64), v| {
+                (min.min(v), max.max(v))
+            })
+    };
+    let ((min_x, max_x), (min_y, max_y)) = (bounds(|p| p.0), bounds(|p| p.1));
+    let size = (max_x - min_x).max(max_y - min_y).max(1.0);
+    let margin = size / 20.0;
+
+    let mut svg = Svg::new(
+        min_x - margin,
+        min_y - margin,
+        max_x - min_x + 2.0 * margin,
+        max_y - min_y + 2.0 * margin,
     );
+    svg.polyline(&points, (21, 101, 192), size / 400.0);
+    if let (Some(&start), Some(&end)) = (points.first(), points.last()) {
+        svg.circle(start, size / 100.0, (67, 160, 71));
+        svg.circle(end, size / 100.0, (229, 57, 53));
+    }
 
-    Integer::abs(x) + Integer::abs(y)
+    svg
 }
 
+#[cfg(feature = "input")]
 pub fn part_1() -> Integer {
-    solve_1(&INPUT)
+    solve_1(input())
 }
 
+#[cfg(feature = "input")]
 pub fn part_2() -> Integer {
-    solve_2(&INPUT)
+    solve_2(input())
+}
+
+pub fn solve_part_1(input: &str) -> Result<Integer, Error> {
+    Move::parse(input)
+        .map(|input| solve_1(&input))
+        .map_err(|e| e.in_day(Day12::DAY))
+}
+
+pub fn solve_part_2(input: &str) -> Result<Integer, Error> {
+    Move::parse(input)
+        .map(|input| solve_2(&input))
+        .map_err(|e| e.in_day(Day12::DAY))
+}
+
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u32 = 12;
+
+    type Input<'a> = Vec<Move>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+        Move::parse(input).map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+        Ok(solve_1(input).into())
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+        Ok(Some(solve_2(input).into()))
+    }
+
+    fn picture(input: &Self::Input<'_>, part: usize) -> Option<Result<Picture, Error>> {
+        let track = if part == 1 {
+            track_1(input)
+        } else {
+            track_2(input)
+        };
+
+        Some(Ok(Picture::Vector(picture(&track))))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     lazy_static! {
@@ -194,11 +323,68 @@ F11"
         assert_eq!(solve_2(&INPUT), 286);
     }
 
+    #[test]
+    fn invalid_move() {
+        let error = Move::parse("F10\nX3").map(drop).expect_err("invalid move");
+        assert_eq!(error.position, Some((2, 1)));
+        assert_eq!(error.snippet, "X");
+
+        let error = Move::parse("F10\nN3\nR45")
+            .map(drop)
+            .expect_err("invalid rotation");
+        assert_eq!(error.position, Some((3, 2)));
+        assert_eq!(error.snippet, "45");
+
+        assert!(Move::parse("F10\n\u{e9}3").is_err());
+    }
+
+    #[test]
+    fn full_turns() {
+        for (turns, same) in [
+            ("R450", "R90"),
+            ("L450", "L90"),
+            ("R360", "R0"),
+            ("L360", "L0"),
+            ("R810", "L270"),
+            ("L720", "R720"),
+        ] {
+            let moves = Move::parse(&format!("F10\nN3\n{}\nF7", turns)).expect("invalid input");
+            let same = Move::parse(&format!("F10\nN3\n{}\nF7", same)).expect("invalid input");
+
+            assert_eq!(track_1(&moves), track_1(&same));
+            assert_eq!(track_2(&moves), track_2(&same));
+        }
+    }
+
+    #[test]
+    fn tracks() {
+        assert_eq!(
+            track_1(&INPUT),
+            vec![(0, 0), (10, 0), (10, 3), (17, 3), (17, 3), (17, -8)]
+        );
+        assert_eq!(
+            track_2(&INPUT),
+            vec![
+                (0, 0),
+                (100, 10),
+                (100, 10),
+                (170, 38),
+                (170, 38),
+                (214, -72)
+            ]
+        );
+
+        let svg = picture(&track_1(&INPUT)).to_string();
+        assert!(svg.contains(r#"points="0,0 10,0 10,-3 17,-3 17,-3 17,8""#));
+    }
+
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_1(b: &mut Bencher) {
         b.iter(part_1);
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_2(b: &mut Bencher) {
         b.iter(part_2);
@@ -0,0 +1,78 @@
+use common::Rng;
+use std::ops::RangeInclusive;
+
+/// Bus ids are distinct primes below this, so that they are pairwise coprime.
+const MAX_ID: u64 = 1000;
+
+/// The sizes `generate` supports: at least one entry, the bus of part 1.
+pub const SIZES: RangeInclusive<usize> = 1..=usize::MAX;
+
+/// A timestamp and a list of `size` entries (at least 1), about one in
+/// eight of them a bus, up to nine of them like the puzzle inputs.
+///
+/// With nine ids below 1000, the earliest timestamp of part 2 is below
+/// 10^27, well within an `i128`. The timestamp of part 1 is drawn again
+/// until a single bus comes first.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(*SIZES.start());
+    let mut ids = (2..MAX_ID).filter(|&n| is_prime(n)).collect::<Vec<_>>();
+    rng.shuffle(&mut ids);
+    ids.truncate((size / 8).clamp(1, 9));
+
+    let mut positions = (1..size).collect::<Vec<_>>();
+    rng.shuffle(&mut positions);
+
+    let mut entries = vec!["x".to_string(); size];
+    for (&id, &position) in ids.iter().zip(std::iter::once(&0).chain(&positions)) {
+        entries[position] = id.to_string();
+    }
+
+    let timestamp = loop {
+        let timestamp = rng.range(100_000, 1_000_000);
+        let mut waits = ids.iter().map(|id| id - timestamp % id).collect::<Vec<_>>();
+        waits.sort_unstable();
+
+        if waits.len() == 1 || waits[0] < waits[1] {
+            break timestamp;
+        }
+    };
+
+    format!("{}\n{}\n", timestamp, entries.join(","))
+}
+
+fn is_prime(n: u64) -> bool {
+    n > 1
+        && (2..)
+            .take_while(|d| d * d <= n)
+            .all(|d| !n.is_multiple_of(d))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Info, InfoPart};
+
+    #[test]
+    fn valid_input() {
+        for (seed, size) in (0..10).zip([1, 10, 100, 10_000].iter().cycle()) {
+            let info = generate(&mut Rng::new(seed), *size)
+                .parse::<Info>()
+                .unwrap();
+            let buses = info
+                .ids
+                .iter()
+                .enumerate()
+                .filter_map(|(i, id)| match id {
+                    InfoPart::Value(id) => Some((i as i128, *id)),
+                    InfoPart::X => None,
+                })
+                .collect::<Vec<_>>();
+            let timestamp = crate::solve_2(&info.ids).unwrap();
+
+            assert_eq!(info.ids.len(), *size);
+            assert!(crate::earliest_bus(&info).is_ok());
+            assert!(timestamp < buses.iter().map(|(_, id)| id).product());
+            assert!(buses.iter().all(|(i, id)| (timestamp + i) % id == 0));
+        }
+    }
+}
@@ -1,39 +1,46 @@
-#![feature(test)]
-#![feature(destructuring_assignment)]
+#![cfg_attr(feature = "bench", feature(test))]
+
+#[cfg(feature = "bench")]
 extern crate test;
 
+#[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
 
+use common::{num, Answer, Error, Solution};
+
+mod generate;
+
+pub use generate::{generate, SIZES};
+
 #[derive(PartialEq)]
-enum InfoPart {
+pub enum InfoPart {
     Value(i128),
     X,
 }
 
 impl std::str::FromStr for InfoPart {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
         if input == "x" {
             Ok(InfoPart::X)
         } else {
-            Ok(InfoPart::Value(
-                input
-                    .parse()
-                    .map_err(|_| format!("invalid id: {}", input))?,
-            ))
+            match input.parse() {
+                Ok(id) if id > 0 => Ok(InfoPart::Value(id)),
+                _ => Err(Error::at(input, input, "invalid id")),
+            }
         }
     }
 }
 
-struct Info {
+pub struct Info {
     timestamp: Option<i128>,
     ids: Vec<InfoPart>,
 }
 
 impl std::str::FromStr for Info {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
         let lines = input.lines().collect::<Vec<_>>();
@@ -42,7 +49,7 @@ impl std::str::FromStr for Info {
             Some(
                 lines[0]
                     .parse()
-                    .map_err(|_| format!("invalid timestamp: {}", lines[0]))?,
+                    .map_err(|_| Error::at(input, lines[0], "invalid timestamp"))?,
             )
         } else {
             None
@@ -50,34 +57,26 @@ impl std::str::FromStr for Info {
 
         let ids = lines
             .last()
-            .ok_or_else(|| "invalid input".to_string())
-            .and_then(|v| v.split(',').map(|v| v.parse::<InfoPart>()).collect())?;
+            .ok_or_else(|| Error::new("expecting a list of bus ids"))
+            .and_then(|v| {
+                v.split(',')
+                    .map(|v| v.parse::<InfoPart>().map_err(|e| e.within(input, v)))
+                    .collect()
+            })?;
 
         Ok(Info { timestamp, ids })
     }
 }
 
-lazy_static! {
-    static ref INPUT: Info = include_str!("../input").parse().expect("invalid input");
-}
-
-#[allow(clippy::many_single_char_names)]
-fn gcdex(a: i128, b: i128) -> (i128, (i128, i128)) {
-    let (mut old_r, mut r) = (a, b);
-    let (mut old_s, mut s) = (1, 0);
-    let (mut old_t, mut t) = (0, 1);
-
-    while r != 0 {
-        let q = old_r / r;
-        (old_r, r) = (r, old_r - q * r);
-        (old_s, s) = (s, old_s - q * s);
-        (old_t, t) = (t, old_t - q * t);
-    }
+/// The embedded puzzle input, parsed on first use.
+#[cfg(feature = "input")]
+fn input() -> &'static Info {
+    static INPUT: std::sync::OnceLock<Info> = std::sync::OnceLock::new();
 
-    (old_r, (old_s, old_t))
+    INPUT.get_or_init(|| include_str!("../input").parse().expect("invalid input"))
 }
 
-fn solve_1(timestamp: i128, ids: &[InfoPart]) -> i128 {
+fn solve_1(timestamp: i128, ids: &[InfoPart]) -> Result<i128, Error> {
     let (id, time) = ids
         .iter()
         .filter_map(|id| {
@@ -89,53 +88,129 @@ fn solve_1(timestamp: i128, ids: &[InfoPart]) -> i128 {
         })
         .map(|id| (id, id - timestamp % id))
         .min_by_key(|(_, v)| *v)
-        .unwrap();
+        .ok_or_else(|| Error::new("no bus in service"))?;
 
-    id * time
+    Ok(id * time)
 }
 
-fn solve_2(ids: &[InfoPart]) -> i128 {
-    let exprs: Vec<(i128, i128)> = ids
+fn solve_2(ids: &[InfoPart]) -> Result<i128, Error> {
+    let congruences = ids
         .iter()
         .enumerate()
         .filter_map(|(i, id)| {
             if let InfoPart::Value(id) = id {
-                Some((i as i128, *id))
+                Some((-(i as i128), *id))
             } else {
                 None
             }
         })
-        .collect();
+        .collect::<Vec<_>>();
 
-    let n = exprs.iter().map(|(_, v)| v).product::<i128>();
+    num::crt(&congruences)
+        .map(|(t, _)| t)
+        .ok_or_else(|| Error::new("no timestamp fits every bus"))
+}
 
-    let mut v = exprs
-        .iter()
-        .map(|(i, v)| {
-            let ni = n / v;
-            let (_, (mi, _)) = gcdex(ni, *v);
-            -i * mi * ni % n
-        })
-        .sum::<i128>();
+/// Pairs of bus ids sharing a factor: the puzzle promises pairwise coprime
+/// ids, and part 2 has no answer when such a pair wants different
+/// remainders.
+fn lint(text: &str) -> Vec<Error> {
+    let ids = text
+        .lines()
+        .last()
+        .unwrap_or_default()
+        .split(',')
+        .enumerate()
+        .filter_map(|(i, id)| Some((i as i128, id.parse::<i128>().ok()?, id)))
+        .collect::<Vec<_>>();
+
+    let mut lints = Vec::new();
+    for (j, &(offset_b, b, id)) in ids.iter().enumerate() {
+        for &(offset_a, a, _) in &ids[..j] {
+            let (factor, _, _) = num::gcd_ex(a, b);
+            if factor == 1 {
+                continue;
+            }
 
-    while v < 0 {
-        v += n;
+            let message = if num::crt(&[(-offset_a, a), (-offset_b, b)]).is_some() {
+                format!("shares the factor {} with bus {}", factor, a)
+            } else {
+                format!(
+                    "shares the factor {} with bus {} and no timestamp fits both",
+                    factor, a
+                )
+            };
+            lints.push(Error::at(text, id, message));
+        }
     }
 
-    v % n
+    lints
+}
+
+fn earliest_bus(info: &Info) -> Result<i128, Error> {
+    let timestamp = info
+        .timestamp
+        .ok_or_else(|| Error::new("missing timestamp"))?;
+
+    solve_1(timestamp, &info.ids)
 }
 
+#[cfg(feature = "input")]
 pub fn part_1() -> i128 {
-    solve_1(INPUT.timestamp.expect("no timestamp"), &INPUT.ids)
+    earliest_bus(input()).expect("invalid input")
 }
 
+#[cfg(feature = "input")]
 pub fn part_2() -> i128 {
-    solve_2(&INPUT.ids)
+    solve_2(&input().ids).expect("invalid input")
+}
+
+pub fn solve_part_1(input: &str) -> Result<i128, Error> {
+    input
+        .parse::<Info>()
+        .and_then(|input| earliest_bus(&input))
+        .map_err(|e| e.in_day(Day13::DAY))
+}
+
+pub fn solve_part_2(input: &str) -> Result<i128, Error> {
+    input
+        .parse::<Info>()
+        .and_then(|input| solve_2(&input.ids))
+        .map_err(|e| e.in_day(Day13::DAY))
+}
+
+pub struct Day13;
+
+impl Solution for Day13 {
+    const DAY: u32 = 13;
+
+    type Input<'a> = Info;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+        input.parse::<Info>().map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+        earliest_bus(input)
+            .map(Answer::from)
+            .map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+        solve_2(&input.ids)
+            .map(|answer| Some(answer.into()))
+            .map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn lint(text: &str, _input: &Self::Input<'_>) -> Vec<Error> {
+        lint(text)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     lazy_static! {
@@ -149,65 +224,106 @@ mod tests {
     fn same_results_part_1() {
         assert_eq!(
             solve_1(INPUT.timestamp.expect("no timestamp"), &INPUT.ids),
-            295
+            Ok(295)
         );
     }
 
     #[test]
     fn same_results_part_2() {
-        assert_eq!(solve_2(&INPUT.ids), 1068781);
+        assert_eq!(solve_2(&INPUT.ids), Ok(1068781));
     }
 
     #[test]
     fn same_results_part_2_1() {
         let info = "17,x,13,19".parse::<Info>().unwrap();
 
-        assert_eq!(solve_2(&info.ids), 3417);
+        assert_eq!(solve_2(&info.ids), Ok(3417));
     }
 
     #[test]
     fn same_results_part_2_2() {
         let info = "67,7,59,61".parse::<Info>().unwrap();
 
-        assert_eq!(solve_2(&info.ids), 754018);
+        assert_eq!(solve_2(&info.ids), Ok(754018));
     }
 
     #[test]
     fn same_results_part_2_3() {
         let info = "67,x,7,59,61".parse::<Info>().unwrap();
 
-        assert_eq!(solve_2(&info.ids), 779210);
+        assert_eq!(solve_2(&info.ids), Ok(779210));
     }
 
     #[test]
     fn same_results_part_2_4() {
         let info = "67,7,x,59,61".parse::<Info>().unwrap();
 
-        assert_eq!(solve_2(&info.ids), 1261476);
+        assert_eq!(solve_2(&info.ids), Ok(1261476));
     }
 
     #[test]
     fn same_results_part_2_5() {
         let info = "1789,37,47,1889".parse::<Info>().unwrap();
 
-        assert_eq!(solve_2(&info.ids), 1202161486);
+        assert_eq!(solve_2(&info.ids), Ok(1202161486));
     }
 
     #[test]
-    fn test_gcdex_1() {
-        assert_eq!(gcdex(13, 19), (1, (3, -2)));
+    fn shared_ids() {
+        let info = "6,x,4".parse::<Info>().unwrap();
+
+        assert_eq!(solve_2(&info.ids), Ok(6));
+
+        let info = "6,4".parse::<Info>().unwrap();
+
+        assert!(solve_2(&info.ids).is_err());
+    }
+
+    #[test]
+    fn lint_shared_factors() {
+        let lints = lint("939\n6,x,4,x,5,9");
+
+        assert_eq!(
+            lints
+                .iter()
+                .map(|lint| (lint.column(), lint.message.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Some(5), "shares the factor 2 with bus 6"),
+                (
+                    Some(11),
+                    "shares the factor 3 with bus 6 and no timestamp fits both"
+                ),
+            ]
+        );
+        assert!(lint(include_str!("../input")).is_empty());
+    }
+
+    #[test]
+    fn invalid_id() {
+        let error = "939\n7,13,x,0,59"
+            .parse::<Info>()
+            .map(drop)
+            .expect_err("invalid id");
+
+        assert_eq!(error.position, Some((2, 8)));
+        assert_eq!(error.snippet, "0");
     }
 
     #[test]
-    fn test_gcdex_2() {
-        assert_eq!(gcdex(937, 397), (1, (-186, 439)));
+    fn missing_timestamp() {
+        let info = "7,13,x,x,59".parse::<Info>().expect("invalid input");
+
+        assert!(earliest_bus(&info).is_err());
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_1(b: &mut Bencher) {
         b.iter(part_1);
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_2(b: &mut Bencher) {
         b.iter(part_2);
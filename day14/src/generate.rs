@@ -0,0 +1,56 @@
+use common::Rng;
+use std::ops::RangeInclusive;
+
+/// The sizes `generate` supports: a mask and a write at least.
+pub const SIZES: RangeInclusive<usize> = 2..=usize::MAX;
+
+/// A program of `size` lines (at least 2), the first one a mask, each mask
+/// followed by a few writes, like the puzzle inputs.
+///
+/// Masks have up to nine floating bits, so that part 2 writes at most 512
+/// addresses per instruction.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(*SIZES.start());
+    let mut lines = Vec::with_capacity(size);
+
+    while lines.len() < size {
+        let mut mask = (0..36)
+            .map(|_| *rng.choose(b"01") as char)
+            .collect::<Vec<_>>();
+        for _ in 0..rng.range(3, 9) {
+            mask[rng.below(36) as usize] = 'X';
+        }
+        lines.push(format!("mask = {}", mask.into_iter().collect::<String>()));
+
+        // never leave room for a single line, which could not be a mask
+        let left = size - lines.len();
+        let mut writes = (rng.range(1, 6) as usize).min(left);
+        if left - writes == 1 {
+            writes += 1;
+        }
+
+        for _ in 0..writes {
+            let (address, value) = (rng.below(65_536), rng.below(1 << 30));
+            lines.push(format!("mem[{}] = {}", address, value));
+        }
+    }
+
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_input() {
+        for (seed, size) in (0..10).zip([2, 100, 500].iter().cycle()) {
+            let text = generate(&mut Rng::new(seed), *size);
+            let input = crate::parse(&text).unwrap();
+
+            assert_eq!(input.instructions.len(), *size);
+            assert!(crate::solve_1(&input).is_ok());
+            assert!(crate::solve_2(&input).is_ok());
+        }
+    }
+}
@@ -1,34 +1,77 @@
-#![feature(test)]
-extern crate test;
+#![cfg_attr(feature = "bench", feature(test))]
 
-#[macro_use]
-extern crate lazy_static;
+#[cfg(feature = "bench")]
+extern crate test;
 
+use common::{Answer, Error, Solution};
 use regex::Regex;
 use std::collections::HashMap;
 
-lazy_static! {
-    static ref INPUT: Vec<Instruction> = parse(include_str!("../input")).expect("invalid input");
-    static ref MASK_RE: Regex = Regex::new(r"mask = ([X01]{36})").unwrap();
-    static ref MEM_RE: Regex = Regex::new(r"mem\[(\d+)\] = (\d+)").unwrap();
+mod generate;
+
+pub use generate::{generate, SIZES};
+
+/// A regex compiled on first use.
+macro_rules! regex {
+    ($pattern:expr) => {{
+        static RE: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+        RE.get_or_init(|| Regex::new($pattern).unwrap())
+    }};
 }
 
-fn parse(input: &str) -> Result<Vec<Instruction>, String> {
-    input.lines().map(|line| line.parse()).collect()
+/// The embedded puzzle input, parsed on first use.
+#[cfg(feature = "input")]
+fn input() -> &'static Program<'static> {
+    static INPUT: std::sync::OnceLock<Program> = std::sync::OnceLock::new();
+
+    INPUT.get_or_init(|| parse(include_str!("../input")).expect("invalid input"))
 }
 
-enum Instruction {
+/// The most floating bits a mask can have, so that part 2 writes at most
+/// 65536 addresses for each `mem` instruction.
+pub const MAX_FLOATING: u32 = 16;
+
+/// The instructions, each with the line of `text` it comes from, so that
+/// the errors of the solvers can point at it.
+pub struct Program<'a> {
+    text: &'a str,
+    instructions: Vec<(&'a str, Instruction)>,
+}
+
+impl<'a> Program<'a> {
+    fn error(&self, line: &str, message: &str) -> Error {
+        Error::at(self.text, line, message)
+    }
+}
+
+fn parse(input: &str) -> Result<Program<'_>, Error> {
+    let instructions = input
+        .lines()
+        .map(|line| {
+            line.parse()
+                .map(|instruction| (line, instruction))
+                .map_err(|e: Error| e.within(input, line))
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(Program {
+        text: input,
+        instructions,
+    })
+}
+
+pub enum Instruction {
     Mask(u64, u64, u64),
     Mem(u64, u64),
 }
 
 impl std::str::FromStr for Instruction {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
         use Instruction::*;
 
-        if let Some(cap) = MASK_RE.captures(input) {
+        if let Some(cap) = regex!(r"^mask = ([X01]{36})$").captures(input) {
             let (mask_and, mask_or, mask_floating) = cap[1].chars().fold(
                 (0x0, 0x0, 0x0),
                 |(mask_and, mask_or, mask_floating), c| match c {
@@ -47,47 +90,64 @@ impl std::str::FromStr for Instruction {
                 },
             );
             Ok(Mask(mask_and, mask_or, mask_floating))
-        } else if let Some(cap) = MEM_RE.captures(input) {
+        } else if let Some(cap) = regex!(r"^mem\[(\d+)\] = (\d+)$").captures(input) {
+            let number = |i, message| {
+                let m = cap.get(i).map_or("", |m| m.as_str());
+                m.parse().map_err(|_| Error::at(input, m, message))
+            };
+
             Ok(Mem(
-                cap[1]
-                    .parse()
-                    .map_err(|_| "invalid mem address".to_string())?,
-                cap[2].parse().map_err(|_| "invalid number".to_string())?,
+                number(1, "invalid mem address")?,
+                number(2, "invalid number")?,
             ))
         } else {
-            Err(format!("invalid instruction: {}", input))
+            Err(Error::at(
+                input,
+                input,
+                "expecting `mask = <36 bits>` or `mem[<address>] = <value>`",
+            ))
         }
     }
 }
 
-fn solve_1(instructions: &[Instruction]) -> u64 {
+/// The sum of the values left in memory.
+fn sum<'a, I: Iterator<Item = &'a u64>>(mut values: I) -> Result<u64, Error> {
+    values
+        .try_fold(0u64, |sum, value| sum.checked_add(*value))
+        .ok_or_else(|| Error::new("the sum of the values in memory overflows"))
+}
+
+fn solve_1(program: &Program) -> Result<u64, Error> {
     use Instruction::*;
 
-    let (memory, _) = instructions.iter().fold(
+    let (memory, _) = program.instructions.iter().try_fold(
         (HashMap::new(), None),
-        |(mut memory, mask), instruction| match (instruction, mask) {
+        |(mut memory, mask), (line, instruction)| match (instruction, mask) {
             (Mask(mask_and, mask_or, mask_floating), _) => {
-                (memory, Some(Mask(*mask_and, *mask_or, *mask_floating)))
+                Ok((memory, Some(Mask(*mask_and, *mask_or, *mask_floating))))
             }
             (Mem(address, value), Some(Mask(mask_and, mask_or, mask_floating))) => {
                 memory.insert(address, value & mask_and | mask_or);
-                (memory, Some(Mask(mask_and, mask_or, mask_floating)))
+                Ok((memory, Some(Mask(mask_and, mask_or, mask_floating))))
             }
-            _ => unreachable!("invalid state, mask undefined"),
+            _ => Err(program.error(line, "mem instruction before any mask")),
         },
-    );
+    )?;
 
-    memory.values().sum()
+    sum(memory.values())
 }
 
-fn solve_2(instructions: &[Instruction]) -> u64 {
+fn solve_2(program: &Program) -> Result<u64, Error> {
     use Instruction::*;
 
-    let (memory, _) = instructions.iter().fold(
+    let (memory, _) = program.instructions.iter().try_fold(
         (HashMap::new(), None),
-        |(mut memory, mask), instruction| match (instruction, mask) {
+        |(mut memory, mask), (line, instruction)| match (instruction, mask) {
+            (Mask(_, _, mask_floating), _) if mask_floating.count_ones() > MAX_FLOATING => {
+                Err(program.error(line, &format!("more than {} floating bits", MAX_FLOATING)))
+            }
             (Mask(mask_and, mask_or, mask_floating), _) => {
-                (memory, Some(Mask(*mask_and, *mask_or, *mask_floating)))
+                Ok((memory, Some(Mask(*mask_and, *mask_or, *mask_floating))))
             }
             (
                 Mem(current_address, current_value),
@@ -95,9 +155,9 @@ fn solve_2(instructions: &[Instruction]) -> u64 {
             ) => {
                 let current_address = current_address & !current_mask_and | current_mask_or;
                 let c = current_mask_floating.count_ones();
-                for i in 0..(1 << c) {
+                for i in 0..(1u64 << c) {
                     let (mut a, mut j, mut k) = (0, 1, 1);
-                    while k < (1 << c) {
+                    while k < (1u64 << c) {
                         if current_mask_floating & j != 0 {
                             if i & k != 0 {
                                 a |= j;
@@ -110,33 +170,86 @@ fn solve_2(instructions: &[Instruction]) -> u64 {
                     memory.insert(current_address | a, *current_value);
                 }
 
-                (
+                Ok((
                     memory,
                     Some(Mask(
                         current_mask_and,
                         current_mask_or,
                         current_mask_floating,
                     )),
-                )
+                ))
             }
-            _ => unreachable!("invalid state, mask undefined"),
+            _ => Err(program.error(line, "mem instruction before any mask")),
         },
-    );
+    )?;
+
+    sum(memory.values())
+}
 
-    memory.values().sum()
+/// The `mem` writes before the first mask, which neither part can apply.
+fn lint(program: &Program) -> Vec<Error> {
+    program
+        .instructions
+        .iter()
+        .take_while(|(_, instruction)| !matches!(instruction, Instruction::Mask(..)))
+        .map(|(line, _)| program.error(line, "mem instruction before any mask"))
+        .collect()
 }
 
+#[cfg(feature = "input")]
 pub fn part_1() -> u64 {
-    solve_1(&INPUT)
+    solve_1(input()).expect("invalid input")
 }
 
+#[cfg(feature = "input")]
 pub fn part_2() -> u64 {
-    solve_2(&INPUT)
+    solve_2(input()).expect("invalid input")
+}
+
+pub fn solve_part_1(input: &str) -> Result<u64, Error> {
+    parse(input)
+        .and_then(|input| solve_1(&input))
+        .map_err(|e| e.in_day(Day14::DAY))
+}
+
+pub fn solve_part_2(input: &str) -> Result<u64, Error> {
+    parse(input)
+        .and_then(|input| solve_2(&input))
+        .map_err(|e| e.in_day(Day14::DAY))
+}
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: u32 = 14;
+
+    type Input<'a> = Program<'a>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+        parse(input).map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+        solve_1(input)
+            .map(Answer::from)
+            .map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+        solve_2(input)
+            .map(|answer| Some(answer.into()))
+            .map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn lint(_text: &str, input: &Self::Input<'_>) -> Vec<Error> {
+        lint(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     #[test]
@@ -149,7 +262,7 @@ mem[8] = 0",
         )
         .expect("invalid input");
 
-        assert_eq!(solve_1(&input), 165);
+        assert_eq!(solve_1(&input), Ok(165));
     }
 
     #[test]
@@ -162,7 +275,7 @@ mem[26] = 1",
         )
         .expect("invalid input");
 
-        assert_eq!(solve_2(&input), 208);
+        assert_eq!(solve_2(&input), Ok(208));
     }
 
     #[test]
@@ -174,7 +287,7 @@ mem[42] = 1",
         )
         .expect("invalid input");
 
-        assert_eq!(solve_2(&input), 1);
+        assert_eq!(solve_2(&input), Ok(1));
     }
 
     #[test]
@@ -186,7 +299,7 @@ mem[42] = 1",
         )
         .expect("invalid input");
 
-        assert_eq!(solve_2(&input), 2);
+        assert_eq!(solve_2(&input), Ok(2));
     }
 
     #[test]
@@ -199,7 +312,7 @@ mem[42] = 1",
         )
         .expect("invalid input");
 
-        assert_eq!(solve_2(&input), 202);
+        assert_eq!(solve_2(&input), Ok(202));
     }
 
     #[test]
@@ -212,7 +325,7 @@ mem[42] = 1",
         )
         .expect("invalid input");
 
-        assert_eq!(solve_2(&input), 4);
+        assert_eq!(solve_2(&input), Ok(4));
     }
 
     #[test]
@@ -224,7 +337,7 @@ mem[2] = 1",
         )
         .expect("invalid input");
 
-        assert_eq!(solve_2(&input), 101);
+        assert_eq!(solve_2(&input), Ok(101));
     }
 
     #[test]
@@ -237,14 +350,79 @@ mem[2] = 1",
         )
         .expect("invalid input");
 
-        assert_eq!(solve_2(&input), 201);
+        assert_eq!(solve_2(&input), Ok(201));
+    }
+
+    #[test]
+    fn mem_before_mask() {
+        let input = parse("mem[8] = 11\nmask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X")
+            .expect("invalid input");
+
+        assert_eq!(solve_1(&input).map_err(|e| e.position), Err(Some((1, 1))));
+        assert_eq!(solve_2(&input).map_err(|e| e.position), Err(Some((1, 1))));
+    }
+
+    #[test]
+    fn sum_overflow() {
+        let input = parse(
+            r"mask = 000000000000000000000000000000000000
+mem[1] = 18446744073709551615
+mem[2] = 18446744073709551615",
+        )
+        .expect("invalid input");
+
+        assert!(solve_2(&input).is_err());
+    }
+
+    #[test]
+    fn too_many_floating_bits() {
+        let input = parse(
+            r"mask = 000000000000000000000000000000000000
+mem[8] = 11
+mask = 0000XXXXXXXXXXXXXXXXXXXXXXXXXXXXXXXX
+mem[8] = 0",
+        )
+        .expect("invalid input");
+
+        assert_eq!(solve_1(&input), Ok(0));
+        let error = solve_2(&input).expect_err("too many floating bits");
+        assert_eq!(error.position, Some((3, 1)));
+    }
+
+    #[test]
+    fn lint_mem_before_mask() {
+        let text =
+            "mem[8] = 11\nmem[7] = 101\nmask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X\nmem[8] = 0";
+        let input = parse(text).expect("invalid input");
+
+        assert_eq!(
+            lint(&input)
+                .iter()
+                .map(|lint| lint.line())
+                .collect::<Vec<_>>(),
+            vec![Some(1), Some(2)]
+        );
+        let text = include_str!("../input");
+        assert!(lint(&parse(text).expect("invalid input")).is_empty());
+    }
+
+    #[test]
+    fn invalid_value() {
+        let error =
+            parse("mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X\nmem[8] = 99999999999999999999")
+                .map(drop)
+                .expect_err("invalid value");
+
+        assert_eq!(error.position, Some((2, 10)));
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_1(b: &mut Bencher) {
         b.iter(part_1);
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_2(b: &mut Bencher) {
         b.iter(part_2);
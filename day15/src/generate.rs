@@ -0,0 +1,38 @@
+use common::Rng;
+
+/// `size` distinct starting numbers (at least 1), below `3 * size` and
+/// below 20 for the small sizes of the puzzle inputs.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+
+    let mut numbers = (0..(3 * size).max(20)).collect::<Vec<_>>();
+    rng.shuffle(&mut numbers);
+
+    let numbers = numbers[..size]
+        .iter()
+        .map(|number| number.to_string())
+        .collect::<Vec<_>>();
+
+    format!("{}\n", numbers.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Game;
+
+    #[test]
+    fn valid_input() {
+        for (seed, size) in (0..10).zip([1, 6, 3000].iter().cycle()) {
+            let game = generate(&mut Rng::new(seed), *size)
+                .parse::<Game>()
+                .unwrap();
+            let mut numbers = game.input.clone();
+            numbers.sort_unstable();
+            numbers.dedup();
+
+            assert_eq!(numbers.len(), *size);
+            assert!(crate::solve_1(&game) < 3 * *size as u32 + 2020);
+        }
+    }
+}
@@ -1,33 +1,42 @@
-#![feature(test)]
-extern crate test;
+#![cfg_attr(feature = "bench", feature(test))]
 
-#[macro_use]
-extern crate lazy_static;
+#[cfg(feature = "bench")]
+extern crate test;
 
+use common::{Answer, Error, Solution};
 use std::collections::HashMap;
 
-lazy_static! {
-    static ref INPUT: Game = "2,15,0,9,1,20".parse().expect("invalid input");
+mod generate;
+
+pub use generate::generate;
+
+/// The embedded puzzle input, parsed on first use.
+#[cfg(feature = "input")]
+fn input() -> &'static Game {
+    static INPUT: std::sync::OnceLock<Game> = std::sync::OnceLock::new();
+
+    INPUT.get_or_init(|| include_str!("../input").parse().expect("invalid input"))
 }
 
-struct Game {
+pub struct Game {
     input: Vec<u32>,
 }
 
 impl std::str::FromStr for Game {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let input = input
+        let numbers = input
+            .trim()
             .split(',')
             .map(|value| {
                 value
                     .parse()
-                    .map_err(|_| format!("invalid number: {}", value))
+                    .map_err(|_| Error::at(input, value, "invalid number"))
             })
             .collect::<Result<_, _>>()?;
 
-        Ok(Game { input })
+        Ok(Game { input: numbers })
     }
 }
 
@@ -88,17 +97,54 @@ fn solve_2(game: &Game) -> u32 {
     game.iter().nth(30_000_000 - 1).unwrap()
 }
 
+#[cfg(feature = "input")]
 pub fn part_1() -> u32 {
-    solve_1(&INPUT)
+    solve_1(input())
 }
 
+#[cfg(feature = "input")]
 pub fn part_2() -> u32 {
-    solve_2(&INPUT)
+    solve_2(input())
+}
+
+pub fn solve_part_1(input: &str) -> Result<u32, Error> {
+    input
+        .parse::<Game>()
+        .map(|input| solve_1(&input))
+        .map_err(|e| e.in_day(Day15::DAY))
+}
+
+pub fn solve_part_2(input: &str) -> Result<u32, Error> {
+    input
+        .parse::<Game>()
+        .map(|input| solve_2(&input))
+        .map_err(|e| e.in_day(Day15::DAY))
+}
+
+pub struct Day15;
+
+impl Solution for Day15 {
+    const DAY: u32 = 15;
+
+    type Input<'a> = Game;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+        input.parse::<Game>().map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+        Ok(solve_1(input).into())
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+        Ok(Some(solve_2(input).into()))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     #[test]
@@ -136,6 +182,11 @@ mod tests {
         assert_eq!(solve_1(&"3,1,2".parse().expect("invalid input")), 1836);
     }
 
+    #[test]
+    fn same_results_solve_part_1() {
+        assert_eq!(solve_part_1("0,3,6\n"), Ok(436));
+    }
+
     #[test]
     #[ignore]
     fn same_results_part_2() {
@@ -178,11 +229,23 @@ mod tests {
         assert_eq!(solve_2(&"3,1,2".parse().expect("invalid input")), 362);
     }
 
+    #[test]
+    fn invalid_number() {
+        let error = "0,3,-6"
+            .parse::<Game>()
+            .map(drop)
+            .expect_err("invalid number");
+
+        assert_eq!(error.position, Some((1, 5)));
+    }
+
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_1(b: &mut Bencher) {
         b.iter(part_1);
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     #[ignore]
     fn bench_part_2(b: &mut Bencher) {
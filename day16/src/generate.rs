@@ -0,0 +1,135 @@
+use common::Rng;
+
+const FIELDS: [&str; 20] = [
+    "departure location",
+    "departure station",
+    "departure platform",
+    "departure track",
+    "departure date",
+    "departure time",
+    "arrival location",
+    "arrival station",
+    "arrival platform",
+    "arrival track",
+    "class",
+    "duration",
+    "price",
+    "route",
+    "row",
+    "seat",
+    "train",
+    "type",
+    "wagon",
+    "zone",
+];
+
+/// Valid values are within `MIN..=MAX`; invalid ones are below or above,
+/// up to 999.
+const MIN: u64 = 25;
+const MAX: u64 = 975;
+
+/// A note with the 20 fields of the puzzle inputs and `size` nearby
+/// tickets, about one in four of them with an invalid value.
+///
+/// The valid values are split in 20 bands, and the fields ranked so that
+/// a value in band `s` is valid for the fields of rank `s` and above. The
+/// column of the field of rank `r` only has values in the bands up to `r`,
+/// and your ticket has one in band `r`, so that the fields can be told
+/// apart one at a time, from the last rank to the first.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    // the first band has at least two values, to split the first rule in two
+    let mut cuts = (MIN + 2..=MAX).collect::<Vec<_>>();
+    rng.shuffle(&mut cuts);
+    cuts.truncate(FIELDS.len() - 1);
+    cuts.push(MIN);
+    cuts.push(MAX + 1);
+    cuts.sort_unstable();
+    let band = |rng: &mut Rng, s: usize| rng.range(cuts[s], cuts[s + 1] - 1);
+
+    // the rank of the field of each column, and the field of each rank
+    let mut ranks = (0..FIELDS.len()).collect::<Vec<_>>();
+    rng.shuffle(&mut ranks);
+    let mut fields = FIELDS.to_vec();
+    rng.shuffle(&mut fields);
+
+    let mut rules = (0..FIELDS.len()).collect::<Vec<_>>();
+    rng.shuffle(&mut rules);
+    let rules = rules
+        .iter()
+        .map(|&r| {
+            let split = rng.range(MIN, cuts[r + 1] - 2);
+            format!(
+                "{}: {}-{} or {}-{}",
+                fields[r],
+                MIN,
+                split,
+                split + 1,
+                cuts[r + 1] - 1
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let your_ticket = ranks.iter().map(|&r| band(rng, r)).collect::<Vec<_>>();
+
+    let nearby_tickets = (0..size)
+        .map(|_| {
+            let mut ticket = ranks
+                .iter()
+                .map(|&r| {
+                    let s = rng.range(0, r as u64) as usize;
+                    band(rng, s)
+                })
+                .collect::<Vec<_>>();
+            if rng.chance(0.25) {
+                ticket[rng.below(FIELDS.len() as u64) as usize] = if rng.chance(0.5) {
+                    rng.below(MIN)
+                } else {
+                    rng.range(MAX + 1, 999)
+                };
+            }
+            ticket
+        })
+        .collect::<Vec<_>>();
+
+    let line = |ticket: &Vec<u64>| {
+        ticket
+            .iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+
+    format!(
+        "{}\n\nyour ticket:\n{}\n\nnearby tickets:\n{}",
+        rules.join("\n"),
+        line(&your_ticket),
+        nearby_tickets
+            .iter()
+            .map(|ticket| format!("{}\n", line(ticket)))
+            .collect::<String>()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Note;
+
+    #[test]
+    fn unique_fields() {
+        let mut names = FIELDS.to_vec();
+        names.sort_unstable();
+
+        for (seed, size) in (0..10).zip([0, 10, 300].iter().cycle()) {
+            let note = generate(&mut Rng::new(seed), *size)
+                .parse::<Note>()
+                .unwrap();
+            let mut fields = crate::solve_2(&note).unwrap();
+            fields.sort_unstable();
+
+            assert_eq!(note.nearby_tickets.len(), *size);
+            assert_eq!(fields, names);
+            assert!(crate::departure_product(&note).unwrap() > 0);
+        }
+    }
+}
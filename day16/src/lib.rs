@@ -1,21 +1,30 @@
-#![feature(test)]
+#![cfg_attr(feature = "bench", feature(test))]
+
+#[cfg(feature = "bench")]
 extern crate test;
 
+#[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
 
-#[cfg(not(feature = "optimized"))]
-use std::collections::HashSet;
+use common::{Answer, Error, Solution};
+
+use std::collections::{HashMap, HashSet};
+
+mod generate;
+
+pub use generate::generate;
 
-#[cfg(feature = "optimized")]
-use std::collections::HashMap;
+/// The embedded puzzle input, parsed on first use.
+#[cfg(feature = "input")]
+fn input() -> &'static Note {
+    static INPUT: std::sync::OnceLock<Note> = std::sync::OnceLock::new();
 
-lazy_static! {
-    static ref INPUT: Note = include_str!("../input").parse().expect("invalid input");
+    INPUT.get_or_init(|| include_str!("../input").parse().expect("invalid input"))
 }
 
 #[derive(Debug)]
-struct Rule {
+pub struct Rule {
     name: String,
     ranges: Vec<(u64, u64)>,
 }
@@ -27,15 +36,12 @@ impl Rule {
 }
 
 impl std::str::FromStr for Rule {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
         let mut parts = input.split(':');
 
-        let name = parts
-            .next()
-            .ok_or_else(|| "no name".to_string())?
-            .to_string();
+        let name = parts.next().unwrap_or_default().to_string();
 
         let ranges = parts
             .next()
@@ -45,73 +51,65 @@ impl std::str::FromStr for Rule {
                     .map(|range| {
                         range
                             .split('-')
-                            .map(|p| p.parse().map_err(|_| format!("invalid number: {}", p)))
+                            .map(|p| p.parse().map_err(|_| Error::at(input, p, "invalid number")))
                             .collect::<Result<Vec<u64>, _>>()
                             .and_then(|v| {
                                 if v.len() == 2 {
                                     Ok((v[0], v[1]))
                                 } else {
-                                    Err("invalid format".to_string())
+                                    Err(Error::at(input, range, "expecting `<min>-<max>`"))
                                 }
                             })
                     })
                     .collect::<Result<Vec<(u64, u64)>, _>>()
             })
-            .ok_or_else(|| "no ranges".to_string())??;
+            .ok_or_else(|| Error::at(input, input, "expecting `<name>: <ranges>`"))??;
 
         Ok(Rule { name, ranges })
     }
 }
 
 #[derive(Debug)]
-struct Note {
+pub struct Note {
     rules: Vec<Rule>,
     your_ticket: Vec<u64>,
     nearby_tickets: Vec<Vec<u64>>,
 }
 
 impl std::str::FromStr for Note {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
         let mut parts = input.split("\n\n");
+        let ticket = |line: &str| {
+            line.split(',')
+                .map(|v| v.parse().map_err(|_| Error::at(input, v, "invalid number")))
+                .collect::<Result<_, _>>()
+        };
 
         let rules = parts
             .next()
-            .ok_or_else(|| "invalid rules".to_string())
+            .ok_or_else(|| Error::new("missing rules"))
             .and_then(|part| {
                 part.split('\n')
-                    .map(|line| line.parse())
+                    .map(|line| line.parse().map_err(|e: Error| e.within(input, line)))
                     .collect::<Result<_, _>>()
             })?;
 
         let your_ticket = parts
             .next()
-            .ok_or_else(|| "invalid your ticket".to_string())
+            .ok_or_else(|| Error::new("missing your ticket"))
             .and_then(|part| {
                 part.lines()
                     .nth(1)
-                    .ok_or_else(|| "invalid your ticket".to_string())
-                    .and_then(|line| {
-                        line.split(',')
-                            .map(|v| v.parse().map_err(|_| format!("invalid number: {}", v)))
-                            .collect::<Result<_, _>>()
-                    })
+                    .ok_or_else(|| Error::at(input, part, "missing your ticket"))
+                    .and_then(ticket)
             })?;
 
         let nearby_tickets = parts
             .next()
-            .ok_or_else(|| "invalid nearby tickets".to_string())
-            .and_then(|part| {
-                part.lines()
-                    .skip(1)
-                    .map(|line| {
-                        line.split(',')
-                            .map(|v| v.parse().map_err(|_| format!("invalid number: {}", v)))
-                            .collect::<Result<_, _>>()
-                    })
-                    .collect::<Result<_, _>>()
-            })?;
+            .ok_or_else(|| Error::new("missing nearby tickets"))
+            .and_then(|part| part.lines().skip(1).map(ticket).collect::<Result<_, _>>())?;
 
         Ok(Note {
             rules,
@@ -139,9 +137,18 @@ fn solve_1(note: &Note) -> u64 {
         .sum()
 }
 
-#[cfg(not(feature = "optimized"))]
-fn solve_2(note: &Note) -> Vec<String> {
-    let sets: Vec<HashSet<String>> = std::iter::once(&note.your_ticket)
+/// The `optimized` feature picks the bit set version.
+fn solve_2(note: &Note) -> Result<Vec<String>, Error> {
+    if cfg!(feature = "optimized") {
+        solve_2_bit_set(note)
+    } else {
+        solve_2_hash_set(note)
+    }
+}
+
+/// For each position, the fields that all the valid tickets allow there.
+fn candidates(note: &Note) -> Result<Vec<HashSet<String>>, Error> {
+    std::iter::once(&note.your_ticket)
         .chain(note.nearby_tickets.iter())
         .filter_map(|ticket| {
             let ticket_set = ticket
@@ -174,7 +181,11 @@ fn solve_2(note: &Note) -> Vec<String> {
             })
             .or(Some(ticket_sets))
         })
-        .unwrap();
+        .ok_or_else(|| Error::new("no valid ticket"))
+}
+
+fn solve_2_hash_set(note: &Note) -> Result<Vec<String>, Error> {
+    let sets = candidates(note)?;
 
     let (mut reduced, mut current): (Vec<_>, Vec<_>) = (
         Vec::with_capacity(sets.len()),
@@ -184,7 +195,9 @@ fn solve_2(note: &Note) -> Vec<String> {
         let (mut singles, unknown): (Vec<_>, Vec<_>) =
             current.into_iter().partition(|(_, set)| set.len() == 1);
 
-        assert!(!singles.is_empty());
+        if singles.is_empty() {
+            return Err(Error::new("cannot tell the remaining fields apart"));
+        }
 
         let all_singles = singles.iter().fold(HashSet::new(), |set, (_, s)| {
             set.union(s).cloned().collect()
@@ -199,14 +212,17 @@ fn solve_2(note: &Note) -> Vec<String> {
 
     reduced.sort_by_key(|(i, _)| *i);
 
-    reduced
+    Ok(reduced
         .iter()
-        .map(|(_, set)| set.iter().cloned().next().unwrap())
-        .collect()
+        .flat_map(|(_, set)| set.iter().cloned())
+        .collect())
 }
 
-#[cfg(feature = "optimized")]
-fn solve_2(note: &Note) -> Vec<String> {
+fn solve_2_bit_set(note: &Note) -> Result<Vec<String>, Error> {
+    if note.rules.len() > 64 {
+        return Err(Error::new("more than 64 rules"));
+    }
+
     let mut current_id = 1u64;
     let mut name2id = HashMap::with_capacity(64);
     let mut id2name = HashMap::with_capacity(64);
@@ -251,7 +267,7 @@ fn solve_2(note: &Note) -> Vec<String> {
             })
             .or(Some(ticket_sets))
         })
-        .unwrap();
+        .ok_or_else(|| Error::new("no valid ticket"))?;
 
     let (mut reduced, mut current): (Vec<_>, Vec<_>) = (
         Vec::with_capacity(sets.len()),
@@ -262,7 +278,9 @@ fn solve_2(note: &Note) -> Vec<String> {
             .into_iter()
             .partition(|(_, set)| set.count_ones() == 1);
 
-        assert!(!singles.is_empty());
+        if singles.is_empty() {
+            return Err(Error::new("cannot tell the remaining fields apart"));
+        }
 
         let all_singles = singles.iter().fold(0u64, |set, (_, s)| set | s);
 
@@ -275,35 +293,122 @@ fn solve_2(note: &Note) -> Vec<String> {
 
     reduced.sort_by_key(|(i, _)| *i);
 
-    reduced
+    Ok(reduced
         .iter()
         .map(|(_, set)| id2name[set].clone())
-        .collect()
+        .collect())
 }
 
-pub fn part_1() -> u64 {
-    solve_1(&INPUT)
-}
+fn departure_product(note: &Note) -> Result<u64, Error> {
+    let fields = solve_2(note)?;
 
-pub fn part_2() -> u64 {
-    let fields = solve_2(&INPUT);
-
-    fields
+    Ok(fields
         .iter()
         .enumerate()
         .filter_map(|(i, name)| {
             if name.starts_with("departure") {
-                Some(INPUT.your_ticket[i])
+                Some(note.your_ticket[i])
             } else {
                 None
             }
         })
-        .product()
+        .product())
+}
+
+#[cfg(feature = "input")]
+pub fn part_1() -> u64 {
+    solve_1(input())
+}
+
+#[cfg(feature = "input")]
+pub fn part_2() -> u64 {
+    departure_product(input()).expect("invalid input")
+}
+
+pub fn solve_part_1(input: &str) -> Result<u64, Error> {
+    input
+        .parse::<Note>()
+        .map(|input| solve_1(&input))
+        .map_err(|e| e.in_day(Day16::DAY))
+}
+
+pub fn solve_part_2(input: &str) -> Result<u64, Error> {
+    input
+        .parse::<Note>()
+        .and_then(|input| departure_product(&input))
+        .map_err(|e| e.in_day(Day16::DAY))
+}
+
+pub struct Day16;
+
+impl Solution for Day16 {
+    const DAY: u32 = 16;
+
+    type Input<'a> = Note;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+        input.parse::<Note>().map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+        Ok(solve_1(input).into())
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+        departure_product(input)
+            .map(|answer| Some(answer.into()))
+            .map_err(|e| e.in_day(Self::DAY))
+    }
+
+    const COMMANDS: &'static [(&'static str, &'static str)] = &[(
+        "candidates [position]",
+        "the fields that the valid tickets allow at every position, or one",
+    )];
+
+    fn explore(
+        input: &mut Self::Input<'_>,
+        command: &str,
+        args: &[&str],
+    ) -> Option<Result<String, Error>> {
+        if command != "candidates" {
+            return None;
+        }
+
+        let position = match args {
+            [] => None,
+            [position] => match position.parse::<usize>() {
+                Ok(position) => Some(position),
+                Err(_) => return Some(Err(Error::new(format!("invalid position: {}", position)))),
+            },
+            _ => return Some(Err(Error::new("expecting `candidates [position]`"))),
+        };
+
+        Some(candidates(input).and_then(|sets| {
+            if position.is_some_and(|position| position >= sets.len()) {
+                return Err(Error::new(format!("tickets have {} fields", sets.len())));
+            }
+
+            Ok(sets
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| position.is_none_or(|position| position == *i))
+                .map(|(i, set)| {
+                    let mut names = set.iter().map(String::as_str).collect::<Vec<_>>();
+                    names.sort_unstable();
+                    format!("{:>3}: {}", i, names.join(", "))
+                })
+                .collect::<Vec<_>>()
+                .join("\n"))
+        }))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use common::Rng;
+    use proptest::prelude::*;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     lazy_static! {
@@ -332,15 +437,108 @@ nearby tickets:
     fn same_results_part_2() {
         assert_eq!(
             solve_2(&INPUT),
-            vec!["row".to_string(), "class".to_string(), "seat".to_string()]
+            Ok(vec![
+                "row".to_string(),
+                "class".to_string(),
+                "seat".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn invalid_note() {
+        let error =
+            "class: 1-3 or 5-7\nrow: 6-11 or 33\n\nyour ticket:\n7,1\n\nnearby tickets:\n7,3"
+                .parse::<Note>()
+                .unwrap_err();
+        assert_eq!(error.position, Some((2, 14)));
+        assert_eq!(error.snippet, "33");
+
+        let error = "class: 1-3 or 5-7\n\nyour ticket:\n7\n\nnearby tickets:\n7\n3,x"
+            .parse::<Note>()
+            .unwrap_err();
+        assert_eq!(error.position, Some((8, 3)));
+        assert_eq!(error.snippet, "x");
+    }
+
+    #[test]
+    fn ambiguous_fields() {
+        let note = "a: 1-3\nb: 1-3\n\nyour ticket:\n1,2\n\nnearby tickets:\n2,1"
+            .parse::<Note>()
+            .expect("invalid input");
+
+        assert!(solve_2(&note).is_err());
+    }
+
+    #[test]
+    fn candidate_fields() {
+        let mut note = "a: 1-3\nb: 1-3 or 5-5\n\nyour ticket:\n1,5\n\nnearby tickets:\n2,1\n9,9"
+            .parse::<Note>()
+            .expect("invalid input");
+
+        assert_eq!(
+            Day16::explore(&mut note, "candidates", &[]),
+            Some(Ok("  0: a, b\n  1: b".to_string()))
         );
+        assert_eq!(
+            Day16::explore(&mut note, "candidates", &["1"]),
+            Some(Ok("  1: b".to_string()))
+        );
+        assert!(matches!(
+            Day16::explore(&mut note, "candidates", &["2"]),
+            Some(Err(_))
+        ));
+    }
+
+    /// Small notes, most of them with no valid ticket or fields that cannot
+    /// be told apart, which the two versions must agree on as well.
+    fn note() -> impl Strategy<Value = Note> {
+        (1..=5usize)
+            .prop_flat_map(|fields| {
+                (
+                    prop::collection::vec(prop::array::uniform4(0..20u64), fields),
+                    prop::collection::vec(0..25u64, fields),
+                    prop::collection::vec(prop::collection::vec(0..25u64, fields), 0..6),
+                )
+            })
+            .prop_map(|(rules, your_ticket, nearby_tickets)| Note {
+                rules: rules
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, mut bounds)| {
+                        bounds.sort_unstable();
+                        Rule {
+                            name: format!("field {}", i),
+                            ranges: vec![(bounds[0], bounds[1]), (bounds[2], bounds[3])],
+                        }
+                    })
+                    .collect(),
+                your_ticket,
+                nearby_tickets,
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn same_fields_random_notes(note in note()) {
+            prop_assert_eq!(solve_2_hash_set(&note), solve_2_bit_set(&note));
+        }
+
+        #[test]
+        fn same_fields_generated_notes(seed in any::<u64>(), size in 0..50usize) {
+            let note = generate(&mut Rng::new(seed), size).parse::<Note>().unwrap();
+
+            prop_assert_eq!(solve_2_hash_set(&note), solve_2_bit_set(&note));
+        }
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_1(b: &mut Bencher) {
         b.iter(part_1);
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_2(b: &mut Bencher) {
         b.iter(part_2);
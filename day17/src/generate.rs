@@ -0,0 +1,35 @@
+use common::Rng;
+
+/// A `size`×`size` initial slice (at least 1×1), with about half of the
+/// cubes active, like the 8×8 puzzle inputs.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+
+    (0..size)
+        .map(|_| {
+            let mut row = (0..size)
+                .map(|_| if rng.chance(0.45) { '#' } else { '.' })
+                .collect::<String>();
+            row.push('\n');
+            row
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ConwayCubes;
+
+    #[test]
+    fn valid_input() {
+        for (seed, size) in (0..5).zip([1, 3, 8].iter().cycle()) {
+            let cubes = generate(&mut Rng::new(seed), *size)
+                .parse::<ConwayCubes>()
+                .unwrap();
+
+            assert!(cubes.len() <= size * size);
+            crate::solve_1(&cubes);
+        }
+    }
+}
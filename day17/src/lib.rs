@@ -1,59 +1,48 @@
-#![feature(test)]
+#![cfg_attr(feature = "bench", feature(test))]
+
+#[cfg(feature = "bench")]
 extern crate test;
 
+#[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
 
+use common::{Answer, Error, Solution};
+use grid::Grid;
 use std::collections::HashSet;
 
-lazy_static! {
-    static ref INPUT: ConwayCubes = include_str!("../input").parse().expect("invalid input");
-    static ref POSITIONS_3D: Vec<Coordinate> = {
-        (-1i128..=1i128)
-            .map(|x| {
-                (-1i128..=1i128)
-                    .map(move |y| {
-                        (-1i128..=1i128).filter_map(move |z| {
-                            if x == 0 && y == 0 && z == 0 {
-                                None
-                            } else {
-                                Some((x, y, z, 0))
-                            }
-                        })
-                    })
-                    .flatten()
-            })
-            .flatten()
-            .collect()
-    };
-    static ref POSITIONS_4D: Vec<Coordinate> = {
-        (-1i128..=1i128)
-            .map(|x| {
-                (-1i128..=1i128)
-                    .map(move |y| {
-                        (-1i128..=1i128)
-                            .map(move |z| {
-                                (-1i128..=1i128).filter_map(move |w| {
-                                    if x == 0 && y == 0 && z == 0 && w == 0 {
-                                        None
-                                    } else {
-                                        Some((x, y, z, w))
-                                    }
-                                })
-                            })
-                            .flatten()
-                    })
-                    .flatten()
-            })
-            .flatten()
-            .collect()
-    };
+mod generate;
+
+pub use generate::generate;
+
+/// The offsets of the neighbours of a cube in 3 dimensions.
+fn positions_3d() -> Vec<Coordinate> {
+    grid::neighbours_nd::<3>()
+        .into_iter()
+        .map(|[x, y, z]| (x as i128, y as i128, z as i128, 0))
+        .collect()
+}
+
+/// The offsets of the neighbours of a cube in 4 dimensions.
+fn positions_4d() -> Vec<Coordinate> {
+    grid::neighbours_nd::<4>()
+        .into_iter()
+        .map(|[x, y, z, w]| (x as i128, y as i128, z as i128, w as i128))
+        .collect()
+}
+
+/// The embedded puzzle input, parsed on first use.
+#[cfg(feature = "input")]
+fn input() -> &'static ConwayCubes {
+    static INPUT: std::sync::OnceLock<ConwayCubes> = std::sync::OnceLock::new();
+
+    INPUT.get_or_init(|| include_str!("../input").parse().expect("invalid input"))
 }
 
 type Coordinate = (i128, i128, i128, i128);
 
 #[derive(Debug, Clone)]
-struct ConwayCubes(HashSet<Coordinate>);
+pub struct ConwayCubes(HashSet<Coordinate>);
 
 impl ConwayCubes {
     fn evolve(&self, positions: &[Coordinate]) -> ConwayCubes {
@@ -98,35 +87,65 @@ impl ConwayCubes {
         ConwayCubes(set)
     }
 
+    /// Draws every z (and w) slice of the bounding box of the active cubes.
+    fn render(&self) -> String {
+        let bounds = |f: fn(&Coordinate) -> i128| {
+            let min = self.iter().map(f).min().unwrap_or_default();
+            let max = self.iter().map(f).max().unwrap_or_default();
+            min..=max
+        };
+        let (xs, ys) = (bounds(|c| c.0), bounds(|c| c.1));
+        let (zs, ws) = (bounds(|c| c.2), bounds(|c| c.3));
+
+        let mut slices = Vec::new();
+        for w in ws.clone() {
+            for z in zs.clone() {
+                let mut slice = if ws == (0..=0) {
+                    format!("z={}\n", z)
+                } else {
+                    format!("z={}, w={}\n", z, w)
+                };
+                for y in ys.clone() {
+                    slice.extend(xs.clone().map(|x| {
+                        if self.contains(&(x, y, z, w)) {
+                            '#'
+                        } else {
+                            '.'
+                        }
+                    }));
+                    slice.push('\n');
+                }
+                slices.push(slice);
+            }
+        }
+
+        slices.join("\n")
+    }
+
     fn evolve_3d(&self) -> ConwayCubes {
-        self.evolve(&POSITIONS_3D)
+        self.evolve(&positions_3d())
     }
 
     fn evolve_4d(&self) -> ConwayCubes {
-        self.evolve(&POSITIONS_4D)
+        self.evolve(&positions_4d())
     }
 }
 
 impl std::str::FromStr for ConwayCubes {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let grid = Grid::parse(input, "expecting '.' or '#'", |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })?;
+
         Ok(ConwayCubes(
-            input
-                .lines()
-                .enumerate()
-                .map(|(y, line)| {
-                    line.chars().enumerate().filter_map(move |(x, c)| match c {
-                        '#' => Some(Ok((x as i128, y as i128, 0, 0))),
-                        '.' => None,
-                        _ => Some(Err(format!(
-                            "invalid char at ({}, {}, {}, {}): {}",
-                            x, y, 0, 0, c
-                        ))),
-                    })
-                })
-                .flatten()
-                .collect::<Result<_, _>>()?,
+            grid.positions()
+                .filter(|(_, &active)| active)
+                .map(|((x, y), _)| (x as i128, y as i128, 0, 0))
+                .collect(),
         ))
     }
 }
@@ -151,17 +170,73 @@ fn solve_2(input: &ConwayCubes) -> usize {
         .len()
 }
 
+#[cfg(feature = "input")]
 pub fn part_1() -> usize {
-    solve_1(&INPUT)
+    solve_1(input())
 }
 
+#[cfg(feature = "input")]
 pub fn part_2() -> usize {
-    solve_2(&INPUT)
+    solve_2(input())
+}
+
+pub fn solve_part_1(input: &str) -> Result<usize, Error> {
+    input
+        .parse::<ConwayCubes>()
+        .map(|input| solve_1(&input))
+        .map_err(|e| e.in_day(Day17::DAY))
+}
+
+pub fn solve_part_2(input: &str) -> Result<usize, Error> {
+    input
+        .parse::<ConwayCubes>()
+        .map(|input| solve_2(&input))
+        .map_err(|e| e.in_day(Day17::DAY))
+}
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    const DAY: u32 = 17;
+
+    type Input<'a> = ConwayCubes;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+        input
+            .parse::<ConwayCubes>()
+            .map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+        Ok(solve_1(input).into())
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+        Ok(Some(solve_2(input).into()))
+    }
+
+    fn generations(
+        input: &Self::Input<'_>,
+        part: usize,
+    ) -> Option<Box<dyn Iterator<Item = String>>> {
+        let evolve = match part {
+            1 => ConwayCubes::evolve_3d,
+            2 => ConwayCubes::evolve_4d,
+            _ => return None,
+        };
+
+        Some(Box::new(
+            std::iter::successors(Some(input.clone()), move |state| Some(evolve(state)))
+                .take(7)
+                .map(|state| state.render()),
+        ))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     lazy_static! {
@@ -172,11 +247,38 @@ mod tests {
         .expect("invalid input");
     }
 
+    #[test]
+    fn generations() {
+        let frames = Day17::generations(&INPUT, 1)
+            .expect("no generations")
+            .collect::<Vec<_>>();
+
+        assert_eq!(frames.len(), 7);
+        assert_eq!(frames[0], "z=0\n.#.\n..#\n###\n");
+        assert_eq!(
+            frames[1],
+            "z=-1\n#..\n..#\n.#.\n\nz=0\n#.#\n.##\n.#.\n\nz=1\n#..\n..#\n.#.\n"
+        );
+        assert_eq!(frames[6].matches('#').count(), 112);
+        assert!(Day17::generations(&INPUT, 2)
+            .and_then(|mut frames| frames.nth(1))
+            .is_some_and(|frame| frame.starts_with("z=-1, w=-1\n")));
+    }
+
     #[test]
     fn same_results_part_1() {
         assert_eq!(solve_1(&INPUT), 112);
     }
 
+    #[test]
+    fn invalid_cube() {
+        let error = ".#.\n..#\n#?#".parse::<ConwayCubes>().unwrap_err();
+
+        assert_eq!(error.position, Some((3, 2)));
+        assert_eq!(error.snippet, "?");
+    }
+
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_1(b: &mut Bencher) {
         b.iter(part_1);
@@ -187,6 +289,7 @@ mod tests {
         assert_eq!(solve_2(&INPUT), 848);
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_2(b: &mut Bencher) {
         b.iter(part_2);
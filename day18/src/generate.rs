@@ -0,0 +1,52 @@
+use common::Rng;
+
+/// Expressions have at most this many numbers, so that, even with every
+/// number 9 and every operator `*`, they stay below 9^12.
+const MAX_NUMBERS: usize = 12;
+
+/// `size` expressions of single-digit numbers, `+`, `*` and parentheses
+/// nested up to two levels deep, like the puzzle inputs.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| loop {
+            let line = expression(rng, 0);
+            if line.bytes().filter(u8::is_ascii_digit).count() <= MAX_NUMBERS {
+                break format!("{}\n", line);
+            }
+        })
+        .collect()
+}
+
+fn expression(rng: &mut Rng, depth: usize) -> String {
+    let terms = (0..rng.range(2, 5))
+        .map(|_| {
+            if depth < 2 && rng.chance(0.25) {
+                format!("({})", expression(rng, depth + 1))
+            } else {
+                rng.range(1, 9).to_string()
+            }
+        })
+        .collect::<Vec<_>>();
+
+    terms[1..]
+        .iter()
+        .fold(terms[0].clone(), |expression, term| {
+            format!("{} {} {}", expression, rng.choose(&["+", "*"]), term)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_input() {
+        for seed in 0..10 {
+            let input = generate(&mut Rng::new(seed), 400);
+
+            assert_eq!(input.lines().count(), 400);
+            assert!(crate::solve_1(&input).is_ok());
+            assert!(crate::solve_2(&input).is_ok());
+        }
+    }
+}
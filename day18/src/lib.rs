@@ -1,231 +1,416 @@
-#![feature(test)]
+#![cfg_attr(feature = "bench", feature(test))]
+
+#[cfg(feature = "bench")]
 extern crate test;
 
-#[macro_use]
-extern crate lazy_static;
-
-lazy_static! {
-    static ref INPUT: &'static str = include_str!("../input");
-}
-
-fn parse_expr<I>(expr: &mut I) -> Result<u64, String>
-where
-    I: Iterator<Item = char>,
-{
-    let mut current = None;
-    let mut current_op = None;
-    while let Some(value) = expr.next() {
-        match (value, current, current_op) {
-            (' ', _, _) => {}
-            ('0'..='9', None, None) => current = Some(value as u64 - '0' as u64),
-            ('0'..='9', Some(c), Some('+')) => {
-                current = Some(c + value as u64 - '0' as u64);
-                current_op = None;
-            }
-            ('0'..='9', Some(c), Some('*')) => {
-                current = Some(c * (value as u64 - '0' as u64));
-                current_op = None;
-            }
-            ('(', None, None) => current = Some(parse_expr(expr)?),
-            ('(', Some(c), Some('+')) => {
-                current = Some(c + parse_expr(expr)?);
-                current_op = None;
-            }
-            ('(', Some(c), Some('*')) => {
-                current = Some(c * parse_expr(expr)?);
-                current_op = None;
-            }
-            (')', None, None) => return Err("empty sub expr".to_string()),
-            (')', Some(c), None) => return Ok(c),
-            (op, Some(_), None) if op == '+' || op == '*' => current_op = Some(value),
-            _ => {
-                return Err(format!(
-                    "invalid state: ({}, {:?}, {:?})",
-                    value, current, current_op
-                ))
-            }
-        }
-    }
+use common::{Answer, Error, Solution};
+
+mod generate;
+
+pub use generate::generate;
+
+#[cfg(feature = "input")]
+const INPUT: &str = include_str!("../input");
 
-    current.ok_or_else(|| "empty expression".to_string())
+/// The deepest nesting of parentheses accepted, so that parsing and
+/// evaluating an expression cannot overflow the stack.
+pub const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Operand<'a> {
+    Number(u64),
+    Group(Box<Expr<'a>>),
 }
 
-fn parse_expr_p<I>(expr: &mut I) -> Result<u64, String>
-where
-    I: Iterator<Item = char>,
-{
-    let mut current = Vec::new();
-    let mut current_op = Vec::new();
-    while let Some(value) = expr.next() {
-        match (value, current.last().copied(), current_op.last().copied()) {
-            (' ', _, _) => {}
-            ('0'..='9', None, None) => {
-                current.push(value as u64 - '0' as u64);
-            }
-            ('0'..='9', Some(c), Some('+')) => {
-                current.pop();
-                current_op.pop();
+/// An expression as written: the precedence of its operators is up to the
+/// part that evaluates it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expr<'a> {
+    first: Operand<'a>,
+    /// Each operator, as a slice of the input to point at if it overflows,
+    /// with its right operand.
+    rest: Vec<(&'a str, Operand<'a>)>,
+}
 
-                current.push(c + value as u64 - '0' as u64);
-            }
-            ('0'..='9', Some(_), Some('*')) => {
-                current.push(value as u64 - '0' as u64);
-            }
-            ('(', None, None) => {
-                current.push(parse_expr_p(expr)?);
-            }
-            ('(', Some(c), Some('+')) => {
-                current.pop();
-                current_op.pop();
+/// The homework: the input, which the errors of the evaluation point into,
+/// and an expression for each of its lines.
+#[derive(Debug, Clone)]
+pub struct Homework<'a> {
+    input: &'a str,
+    expressions: Vec<Expr<'a>>,
+}
+
+/// Parses the expression of `line`, a slice of `input`, char by char.
+struct Parser<'a> {
+    input: &'a str,
+    line: &'a str,
+    chars: std::str::CharIndices<'a>,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, i: usize, message: &str) -> Error {
+        let end = self.line[i..]
+            .chars()
+            .next()
+            .map_or(i, |c| i + c.len_utf8());
+        Error::at(self.input, &self.line[i..end], message)
+    }
 
-                current.push(c + parse_expr_p(expr)?);
+    /// The next char that is not a space, with its index.
+    fn next(&mut self) -> (usize, Option<char>) {
+        loop {
+            match self.chars.next() {
+                Some((_, ' ')) => {}
+                Some((i, c)) => return (i, Some(c)),
+                None => return (self.line.len(), None),
             }
-            ('(', Some(_), Some('*')) => {
-                current.push(parse_expr_p(expr)?);
+        }
+    }
+
+    /// `depth` is the number of parentheses still open, so a `)` closes the
+    /// expression only if it is not 0.
+    fn expr(&mut self, depth: usize) -> Result<Expr<'a>, Error> {
+        let first = self.operand(depth, true)?;
+
+        let mut rest = Vec::new();
+        loop {
+            match self.next() {
+                (i, Some('+' | '*')) => {
+                    let op = &self.line[i..=i];
+                    rest.push((op, self.operand(depth, false)?));
+                }
+                (i, Some(')')) if depth == 0 => return Err(self.error(i, "unmatched ')'")),
+                (_, Some(')')) => break,
+                (i, None) if depth > 0 => return Err(self.error(i, "missing ')'")),
+                (_, None) => break,
+                (i, Some(_)) => return Err(self.error(i, "unexpected character")),
             }
-            (')', None, None) => return Err("empty sub expr".to_string()),
-            (')', Some(_), None) => break,
-            (op, Some(_), _) if op == '+' || op == '*' => {
-                current_op.push(op);
+        }
+
+        Ok(Expr { first, rest })
+    }
+
+    fn operand(&mut self, depth: usize, first: bool) -> Result<Operand<'a>, Error> {
+        match self.next() {
+            (_, Some(c @ '0'..='9')) => Ok(Operand::Number(c as u64 - '0' as u64)),
+            (i, Some('(')) if depth == MAX_DEPTH => {
+                Err(self.error(i, "expression nested too deeply"))
             }
-            _ => break,
+            (_, Some('(')) => Ok(Operand::Group(Box::new(self.expr(depth + 1)?))),
+            (i, Some(')')) if first && depth == 0 => Err(self.error(i, "unmatched ')'")),
+            (i, Some(')')) if first => Err(self.error(i, "empty sub expression")),
+            (i, None) if depth > 0 => Err(self.error(i, "missing ')'")),
+            (i, None) if first => Err(self.error(i, "empty expression")),
+            (i, None) => Err(self.error(i, "missing operand")),
+            (i, Some(_)) => Err(self.error(i, "unexpected character")),
         }
     }
+}
 
-    Ok(current.iter().product())
+impl<'a> Operand<'a> {
+    fn evaluate<F>(&self, evaluate: F) -> Result<u64, Error>
+    where
+        F: Fn(&Expr<'a>) -> Result<u64, Error>,
+    {
+        match self {
+            Operand::Number(value) => Ok(*value),
+            Operand::Group(expr) => evaluate(expr),
+        }
+    }
 }
 
-fn parse(expr: &str) -> Result<u64, String> {
-    parse_expr(&mut expr.chars())
+impl<'a> Expr<'a> {
+    /// The value of the expression evaluated left to right.
+    fn evaluate(&self, input: &str) -> Result<u64, Error> {
+        let evaluate = |expr: &Expr<'a>| expr.evaluate(input);
+
+        self.rest
+            .iter()
+            .try_fold(self.first.evaluate(evaluate)?, |current, (op, operand)| {
+                let value = operand.evaluate(evaluate)?;
+                let result = if *op == "+" {
+                    current.checked_add(value)
+                } else {
+                    current.checked_mul(value)
+                };
+                result.ok_or_else(|| Error::at(input, op, "overflow"))
+            })
+    }
+
+    /// The value of the expression with `+` before `*`: `product` holds the
+    /// value of the terms already multiplied, `sum` the one of the term
+    /// being added up.
+    fn evaluate_p(&self, input: &str) -> Result<u64, Error> {
+        let evaluate = |expr: &Expr<'a>| expr.evaluate_p(input);
+        let overflow = |op| Error::at(input, op, "overflow");
+
+        let mut product = 1u64;
+        let mut sum = self.first.evaluate(evaluate)?;
+        for (op, operand) in &self.rest {
+            let value = operand.evaluate(evaluate)?;
+            if *op == "+" {
+                sum = sum.checked_add(value).ok_or_else(|| overflow(op))?;
+            } else {
+                product = product.checked_mul(sum).ok_or_else(|| overflow(op))?;
+                sum = value;
+            }
+        }
+
+        product
+            .checked_mul(sum)
+            .ok_or_else(|| match self.rest.last() {
+                Some((op, _)) => overflow(op),
+                None => Error::new("overflow"),
+            })
+    }
 }
 
-fn parse_p(expr: &str) -> Result<u64, String> {
-    parse_expr_p(&mut expr.chars())
+impl<'a> Homework<'a> {
+    fn sum<F>(&self, evaluate: F) -> Result<u64, Error>
+    where
+        F: Fn(&Expr<'a>, &str) -> Result<u64, Error>,
+    {
+        self.expressions.iter().try_fold(0u64, |sum, expr| {
+            sum.checked_add(evaluate(expr, self.input)?)
+                .ok_or_else(|| Error::new("the sum of the expressions overflows"))
+        })
+    }
 }
 
-fn solve_1(input: &str) -> u64 {
-    input
+fn parse(input: &str) -> Result<Homework<'_>, Error> {
+    let expressions = input
         .lines()
-        .map(|exp| parse(exp).expect("invalid expr"))
-        .sum()
+        .map(|line| {
+            Parser {
+                input,
+                line,
+                chars: line.char_indices(),
+            }
+            .expr(0)
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok(Homework { input, expressions })
 }
 
-fn solve_2(input: &str) -> u64 {
-    input
-        .lines()
-        .map(|exp| parse_p(exp).expect("invalid expr"))
-        .sum()
+fn solve_1(input: &str) -> Result<u64, Error> {
+    parse(input)?.sum(Expr::evaluate)
 }
 
+fn solve_2(input: &str) -> Result<u64, Error> {
+    parse(input)?.sum(Expr::evaluate_p)
+}
+
+#[cfg(feature = "input")]
 pub fn part_1() -> u64 {
-    solve_1(&INPUT)
+    solve_1(INPUT).expect("invalid input")
 }
 
+#[cfg(feature = "input")]
 pub fn part_2() -> u64 {
-    solve_2(&INPUT)
+    solve_2(INPUT).expect("invalid input")
+}
+
+pub fn solve_part_1(input: &str) -> Result<u64, Error> {
+    solve_1(input).map_err(|e| e.in_day(Day18::DAY))
+}
+
+pub fn solve_part_2(input: &str) -> Result<u64, Error> {
+    solve_2(input).map_err(|e| e.in_day(Day18::DAY))
+}
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    const DAY: u32 = 18;
+
+    type Input<'a> = Homework<'a>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+        parse(input).map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+        input
+            .sum(Expr::evaluate)
+            .map(Answer::from)
+            .map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+        input
+            .sum(Expr::evaluate_p)
+            .map(|answer| Some(answer.into()))
+            .map_err(|e| e.in_day(Self::DAY))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     #[test]
     fn same_results_part_1_1() {
-        assert_eq!(solve_1("1 + 2 * 3 + 4 * 5 + 6"), 71);
+        assert_eq!(solve_1("1 + 2 * 3 + 4 * 5 + 6"), Ok(71));
     }
 
     #[test]
     fn same_results_part_1_2() {
-        assert_eq!(solve_1("1 + (2 * 3) + (4 * (5 + 6))"), 51);
+        assert_eq!(solve_1("1 + (2 * 3) + (4 * (5 + 6))"), Ok(51));
     }
 
     #[test]
     fn same_results_part_1_3() {
-        assert_eq!(solve_1("2 * 3 + (4 * 5)"), 26);
+        assert_eq!(solve_1("2 * 3 + (4 * 5)"), Ok(26));
     }
 
     #[test]
     fn same_results_part_1_4() {
-        assert_eq!(solve_1("5 + (8 * 3 + 9 + 3 * 4 * 3)"), 437);
+        assert_eq!(solve_1("5 + (8 * 3 + 9 + 3 * 4 * 3)"), Ok(437));
     }
 
     #[test]
     fn same_results_part_1_5() {
-        assert_eq!(solve_1("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))"), 12240);
+        assert_eq!(
+            solve_1("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))"),
+            Ok(12240)
+        );
     }
 
     #[test]
     fn same_results_part_1_6() {
         assert_eq!(
             solve_1("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2"),
-            13632
+            Ok(13632)
         );
     }
 
     #[test]
     fn same_results_part_2_1() {
-        assert_eq!(solve_2("1 + 2 * 3 + 4 * 5 + 6"), 231);
+        assert_eq!(solve_2("1 + 2 * 3 + 4 * 5 + 6"), Ok(231));
     }
 
     #[test]
     fn same_results_part_2_2() {
-        assert_eq!(solve_2("1 + (2 * 3) + (4 * (5 + 6))"), 51);
+        assert_eq!(solve_2("1 + (2 * 3) + (4 * (5 + 6))"), Ok(51));
     }
 
     #[test]
     fn same_results_part_2_3() {
-        assert_eq!(solve_2("2 * 3 + (4 * 5)"), 46);
+        assert_eq!(solve_2("2 * 3 + (4 * 5)"), Ok(46));
     }
 
     #[test]
     fn same_results_part_2_4() {
-        assert_eq!(solve_2("5 + (8 * 3 + 9 + 3 * 4 * 3)"), 1445);
+        assert_eq!(solve_2("5 + (8 * 3 + 9 + 3 * 4 * 3)"), Ok(1445));
     }
 
     #[test]
     fn same_results_part_2_5() {
-        assert_eq!(solve_2("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))"), 669060);
+        assert_eq!(
+            solve_2("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))"),
+            Ok(669060)
+        );
     }
 
     #[test]
     fn same_results_part_2_6() {
         assert_eq!(
             solve_2("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2"),
-            23340
+            Ok(23340)
         );
     }
 
     #[test]
     fn same_results_part_2_sum() {
-        assert_eq!(solve_2("1 + 2"), 3);
+        assert_eq!(solve_2("1 + 2"), Ok(3));
     }
 
     #[test]
     fn same_results_part_2_mul() {
-        assert_eq!(solve_2("1 * 2"), 2);
+        assert_eq!(solve_2("1 * 2"), Ok(2));
     }
 
     #[test]
     fn same_results_part_2_sum_1() {
-        assert_eq!(solve_2("1 + (2 * 3)"), 7);
+        assert_eq!(solve_2("1 + (2 * 3)"), Ok(7));
     }
 
     #[test]
     fn same_results_part_2_mul_1() {
-        assert_eq!(solve_2("1 * (2 + 3)"), 5);
+        assert_eq!(solve_2("1 * (2 + 3)"), Ok(5));
     }
 
     #[test]
     fn same_results_part_2_sum_2() {
-        assert_eq!(solve_2("1 + 2 * 3 + 4"), 21);
+        assert_eq!(solve_2("1 + 2 * 3 + 4"), Ok(21));
+    }
+
+    #[test]
+    fn invalid_expression() {
+        let error = solve_1("1 + 2\n2 * (3 + x)").unwrap_err();
+        assert_eq!(error.position, Some((2, 10)));
+        assert_eq!(error.snippet, "x");
+
+        let error = solve_2("1 + 2\n2 * ()").unwrap_err();
+        assert_eq!(error.position, Some((2, 6)));
+
+        let error = solve_2("1 + 2\n2 * (3 + x)").unwrap_err();
+        assert_eq!(error.position, Some((2, 10)));
+        assert_eq!(error.message, "unexpected character");
+
+        assert_eq!(
+            solve_2("1 + 2\n\n3").unwrap_err().message,
+            "empty expression"
+        );
+        assert_eq!(solve_1("1 +").unwrap_err().message, "missing operand");
+        assert_eq!(solve_2("1 *").unwrap_err().message, "missing operand");
+    }
+
+    #[test]
+    fn unbalanced_parentheses() {
+        for solve in [solve_1, solve_2] {
+            let error = solve("1 + 2) * 5").unwrap_err();
+            assert_eq!(error.position, Some((1, 6)));
+            assert_eq!(error.message, "unmatched ')'");
+
+            let error = solve("(1 + 2").unwrap_err();
+            assert_eq!(error.message, "missing ')'");
+        }
+    }
+
+    #[test]
+    fn overflow() {
+        let line = vec!["9"; 40].join(" * ");
+        for solve in [solve_1, solve_2] {
+            assert_eq!(solve(&line).unwrap_err().message, "overflow");
+        }
+        assert_eq!(
+            solve_2(&format!("({})", line)).unwrap_err().message,
+            "overflow"
+        );
+    }
+
+    #[test]
+    fn too_deep() {
+        let line = format!("{}1{}", "(".repeat(MAX_DEPTH), ")".repeat(MAX_DEPTH));
+        assert_eq!(solve_1(&line), Ok(1));
+
+        let line = format!("{}1{}", "(".repeat(200_000), ")".repeat(200_000));
+        let error = Day18::parse(&line).unwrap_err();
+        assert_eq!(error.position, Some((1, MAX_DEPTH + 1)));
+        assert_eq!(error.message, "expression nested too deeply");
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_1(b: &mut Bencher) {
         b.iter(part_1);
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_2(b: &mut Bencher) {
         b.iter(part_2);
@@ -0,0 +1,131 @@
+use common::Rng;
+
+/// The length of the messages matched by rules 42 and 31.
+const CHUNK: usize = 4;
+
+/// Rules in the shape of the puzzle inputs, and `size` messages.
+///
+/// Rule 0 is `8 11`, with 8 being `42` and 11 being `42 31`, where 42 and
+/// 31 match the chunks of four chars with an even and an odd number of
+/// `b`, one way or the other, through rules that match the shorter chunks
+/// of either parity. A third of the messages match 0 before the rules are
+/// changed, a third only after, and the rest never do.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let mut numbers = (1..CHUNK * 10)
+        .filter(|n| ![8, 11, 31, 42].contains(n))
+        .collect::<Vec<_>>();
+    rng.shuffle(&mut numbers);
+
+    // the rules matching the chunks of each length with an even and an odd
+    // number of `b`
+    let mut parity = vec![(numbers[0], numbers[1])];
+    let mut rules = vec![
+        format!("{}: \"a\"", numbers[0]),
+        format!("{}: \"b\"", numbers[1]),
+        "0: 8 11".to_string(),
+        "8: 42".to_string(),
+        "11: 42 31".to_string(),
+    ];
+    let even_is_42 = rng.chance(0.5);
+
+    for length in 2..=CHUNK {
+        let (even, odd) = if length < CHUNK {
+            (numbers[2 * length - 2], numbers[2 * length - 1])
+        } else if even_is_42 {
+            (42, 31)
+        } else {
+            (31, 42)
+        };
+        let ((e, o), (a, b)) = (parity[length - 2], parity[0]);
+
+        rules.push(format!("{}: {} {} | {} {}", even, e, a, o, b));
+        rules.push(format!("{}: {} {} | {} {}", odd, e, b, o, a));
+        parity.push((even, odd));
+    }
+    rng.shuffle(&mut rules);
+
+    let messages = (0..size)
+        .map(|_| {
+            let (m, n) = match rng.below(3) {
+                0 => (2, 1),
+                1 => loop {
+                    let m = rng.range(2, 5);
+                    let n = rng.range(1, m - 1);
+                    if (m, n) != (2, 1) {
+                        break (m, n);
+                    }
+                },
+                _ => {
+                    let m = rng.range(1, 4);
+                    (m, rng.range(m, 4))
+                }
+            };
+
+            (0..m + n)
+                .map(|i| chunk(rng, (i < m) != even_is_42))
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>();
+
+    format!("{}\n\n{}\n", rules.join("\n"), messages.join("\n"))
+}
+
+/// A random chunk with an odd or an even number of `b`.
+fn chunk(rng: &mut Rng, odd: bool) -> String {
+    let mut chars = (0..CHUNK)
+        .map(|_| if rng.chance(0.5) { 'a' } else { 'b' })
+        .collect::<Vec<_>>();
+    if (chars.iter().filter(|&&c| c == 'b').count() % 2 == 1) != odd {
+        chars[0] = if chars[0] == 'a' { 'b' } else { 'a' };
+    }
+
+    chars.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The number of chunks matching 42 followed by the number matching 31,
+    /// if the message has that shape.
+    fn shape(message: &str, even_is_42: bool) -> Option<(usize, usize)> {
+        let chunks = message
+            .as_bytes()
+            .chunks(CHUNK)
+            .map(|chunk| (chunk.iter().filter(|&&c| c == b'b').count() % 2 == 0) == even_is_42)
+            .collect::<Vec<_>>();
+        let m = chunks.iter().take_while(|&&is_42| is_42).count();
+
+        if chunks[m..].iter().all(|&is_42| !is_42) {
+            Some((m, chunks.len() - m))
+        } else {
+            None
+        }
+    }
+
+    #[test]
+    fn valid_input() {
+        for seed in 0..10 {
+            let input = generate(&mut Rng::new(seed), 50);
+            let (rules, messages) = crate::parse(&input).unwrap();
+            let answers = (
+                crate::solve_1(&rules, &messages),
+                crate::solve_2(&rules, &messages).unwrap(),
+            );
+
+            assert_eq!(messages.len(), 50);
+            assert!([true, false].iter().any(|&even_is_42| {
+                let shapes = messages
+                    .iter()
+                    .filter_map(|message| shape(message, even_is_42))
+                    .collect::<Vec<_>>();
+
+                answers
+                    == (
+                        shapes.iter().filter(|&&shape| shape == (2, 1)).count(),
+                        shapes.iter().filter(|(m, n)| m > n && *n > 0).count(),
+                    )
+            }));
+        }
+    }
+}
@@ -1,24 +1,36 @@
-#![feature(test)]
+#![cfg_attr(feature = "bench", feature(test))]
+
+#[cfg(feature = "bench")]
 extern crate test;
 
+#[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
 
-use std::collections::HashMap;
+use common::{Answer, Error, Solution};
+use std::collections::{HashMap, HashSet};
+
+mod generate;
+
+pub use generate::generate;
 
-lazy_static! {
-    static ref INPUT: (Rules, Vec<&'static str>) = parse(include_str!("../input"));
+/// The embedded puzzle input, parsed on first use.
+#[cfg(feature = "input")]
+fn input() -> &'static (Rules, Vec<&'static str>) {
+    static INPUT: std::sync::OnceLock<(Rules, Vec<&'static str>)> = std::sync::OnceLock::new();
+
+    INPUT.get_or_init(|| parse(include_str!("../input")).expect("invalid input"))
 }
 
 #[derive(Clone)]
-enum Rule {
+pub enum Rule {
     Simple(char),
     Seq(Vec<usize>),
     Or(Vec<usize>, Vec<usize>),
 }
 
 impl std::str::FromStr for Rule {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
         use Rule::*;
@@ -27,36 +39,39 @@ impl std::str::FromStr for Rule {
             part.split(' ')
                 .map(|v| {
                     v.parse()
-                        .map_err(|_| format!("invalid number: {} input: {}", v, input))
+                        .map_err(|_| Error::at(input, v, "invalid rule number"))
                 })
                 .collect::<Result<_, _>>()
         };
 
         let mut parts = input.split(" | ");
 
-        match (parts.next(), parts.next()) {
-            (Some(part), None) => {
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(part), None, None) => {
                 if part.starts_with('"') {
-                    Ok(Simple(
-                        part[1..2]
-                            .chars()
-                            .next()
-                            .ok_or_else(|| format!("expecting char: {}", part))?,
-                    ))
+                    let mut chars = part.chars();
+                    match (chars.next(), chars.next(), chars.next(), chars.next()) {
+                        (Some('"'), Some(c), Some('"'), None) => Ok(Simple(c)),
+                        _ => Err(Error::at(input, part, "expecting a quoted char")),
+                    }
                 } else {
                     Ok(Seq(parse_seq(part)?))
                 }
             }
-            (Some(part_left), Some(part_right)) => {
+            (Some(part_left), Some(part_right), None) => {
                 Ok(Or(parse_seq(part_left)?, parse_seq(part_right)?))
             }
-            _ => Err("invalid rule format".to_string()),
+            _ => Err(Error::at(
+                input,
+                input,
+                "expecting at most two alternatives",
+            )),
         }
     }
 }
 
 #[derive(Clone)]
-struct Rules(HashMap<usize, Rule>);
+pub struct Rules(HashMap<usize, Rule>);
 
 impl Rules {
     fn is_match<'a>(&self, input: &'a str, rule_index: usize) -> Vec<(&'a str, &'a str)> {
@@ -67,13 +82,12 @@ impl Rules {
                 .try_fold(vec![(0, input)], |v, rule_index| {
                     let v = v
                         .iter()
-                        .map(|(i, input)| {
+                        .flat_map(|(i, input)| {
                             self.is_match(input, *rule_index)
                                 .iter()
                                 .map(|(p, r)| (i + p.len(), *r))
                                 .collect::<Vec<_>>()
                         })
-                        .flatten()
                         .collect::<Vec<_>>();
                     if v.is_empty() {
                         Err(())
@@ -88,8 +102,8 @@ impl Rules {
         };
 
         match self.0.get(&rule_index) {
-            Some(Simple(c)) if !input.is_empty() => match input[0..1].chars().next() {
-                Some(t) if t == *c => vec![(&input[0..1], &input[1..])],
+            Some(Simple(c)) => match input.chars().next() {
+                Some(t) if t == *c => vec![input.split_at(t.len_utf8())],
                 _ => Vec::new(),
             },
             Some(Seq(seq)) => match_seq(seq),
@@ -105,10 +119,68 @@ impl Rules {
     fn replace(&mut self, rule_index: usize, rule: Rule) {
         self.0.insert(rule_index, rule);
     }
+
+    fn check_defined(&self, rule_index: usize) -> Result<(), Error> {
+        if self.0.contains_key(&rule_index) {
+            Ok(())
+        } else {
+            Err(Error::new(format!("rule {} is not defined", rule_index)))
+        }
+    }
+
+    /// Every rule number in `input`, the text the rules are parsed from,
+    /// must be of a defined rule.
+    fn check_references(&self, input: &str) -> Result<(), Error> {
+        input
+            .lines()
+            .filter_map(|line| line.split(": ").nth(1))
+            .flat_map(|rule| rule.split(' '))
+            .filter(|reference| *reference != "|" && !reference.starts_with('"'))
+            .try_for_each(|reference| match reference.parse() {
+                Ok(rule_index) if self.0.contains_key(&rule_index) => Ok(()),
+                _ => Err(Error::at(input, reference, "undefined rule")),
+            })
+    }
+
+    /// Every rule consumes at least one char, so matching only loops forever
+    /// if a rule can reach itself through the first rule of its alternatives.
+    fn check_left_recursion(&self) -> Result<(), Error> {
+        fn visit(
+            rules: &Rules,
+            rule_index: usize,
+            visiting: &mut HashSet<usize>,
+            done: &mut HashSet<usize>,
+        ) -> Result<(), Error> {
+            if done.contains(&rule_index) {
+                return Ok(());
+            }
+            if !visiting.insert(rule_index) {
+                return Err(Error::new(format!("rule {} is left recursive", rule_index)));
+            }
+
+            let first = match rules.0.get(&rule_index) {
+                Some(Rule::Seq(seq)) => vec![seq.first()],
+                Some(Rule::Or(left, right)) => vec![left.first(), right.first()],
+                _ => Vec::new(),
+            };
+            for &next in first.into_iter().flatten() {
+                visit(rules, next, visiting, done)?;
+            }
+
+            visiting.remove(&rule_index);
+            done.insert(rule_index);
+            Ok(())
+        }
+
+        let mut done = HashSet::new();
+        self.0
+            .keys()
+            .try_for_each(|&rule_index| visit(self, rule_index, &mut HashSet::new(), &mut done))
+    }
 }
 
 impl std::str::FromStr for Rules {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
         let rules = input
@@ -119,27 +191,37 @@ impl std::str::FromStr for Rules {
                     (Some(index), Some(rule)) => Ok((
                         index
                             .parse()
-                            .map_err(|_| format!("invalid number: {} line: {}", index, line))?,
-                        rule.parse()?,
+                            .map_err(|_| Error::at(input, index, "invalid rule number"))?,
+                        rule.parse().map_err(|e: Error| e.within(input, rule))?,
                     )),
-                    _ => Err(format!("invalid rule entry: {}", line)),
+                    _ => Err(Error::at(input, line, "expecting `<number>: <rule>`")),
                 }
             })
             .collect::<Result<_, _>>()?;
 
-        Ok(Self(rules))
+        let rules = Self(rules);
+        rules.check_references(input)?;
+        rules.check_left_recursion()?;
+
+        Ok(rules)
     }
 }
 
-fn parse(input: &str) -> (Rules, Vec<&str>) {
+fn parse(input: &str) -> Result<(Rules, Vec<&str>), Error> {
     let mut parts = input.split("\n\n");
 
     match (parts.next(), parts.next()) {
-        (Some(rules), Some(messages)) => (
-            rules.parse().expect("invalid rules"),
-            messages.lines().collect(),
-        ),
-        _ => panic!("invalid format"),
+        (Some(rules), Some(messages)) => {
+            let rules = rules
+                .parse::<Rules>()
+                .map_err(|e: Error| e.within(input, rules))?;
+            rules.check_defined(0)?;
+
+            Ok((rules, messages.lines().collect()))
+        }
+        _ => Err(Error::new(
+            "expecting rules and messages separated by a blank line",
+        )),
     }
 }
 
@@ -156,13 +238,17 @@ fn solve_1(rules: &Rules, messages: &[&str]) -> usize {
         .count()
 }
 
-fn solve_2(rules: &Rules, messages: &[&str]) -> usize {
+fn solve_2(rules: &Rules, messages: &[&str]) -> Result<usize, Error> {
+    rules.check_defined(42)?;
+    rules.check_defined(31)?;
+
     let mut rules = rules.clone();
 
     rules.replace(8, Rule::Or(vec![42], vec![42, 8]));
     rules.replace(11, Rule::Or(vec![42, 31], vec![42, 11, 31]));
+    rules.check_left_recursion()?;
 
-    messages
+    Ok(messages
         .iter()
         .filter_map(|line| {
             if rules.is_match(line, 0).iter().any(|(_, r)| r.is_empty()) {
@@ -171,20 +257,57 @@ fn solve_2(rules: &Rules, messages: &[&str]) -> usize {
                 None
             }
         })
-        .count()
+        .count())
 }
 
+#[cfg(feature = "input")]
 pub fn part_1() -> usize {
-    solve_1(&INPUT.0, &INPUT.1)
+    solve_1(&input().0, &input().1)
 }
 
+#[cfg(feature = "input")]
 pub fn part_2() -> usize {
-    solve_2(&INPUT.0, &INPUT.1)
+    solve_2(&input().0, &input().1).expect("invalid input")
+}
+
+pub fn solve_part_1(input: &str) -> Result<usize, Error> {
+    parse(input)
+        .map(|(rules, messages)| solve_1(&rules, &messages))
+        .map_err(|e| e.in_day(Day19::DAY))
+}
+
+pub fn solve_part_2(input: &str) -> Result<usize, Error> {
+    parse(input)
+        .and_then(|(rules, messages)| solve_2(&rules, &messages))
+        .map_err(|e| e.in_day(Day19::DAY))
+}
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    const DAY: u32 = 19;
+
+    type Input<'a> = (Rules, Vec<&'a str>);
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+        parse(input).map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+        Ok(solve_1(&input.0, &input.1).into())
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+        solve_2(&input.0, &input.1)
+            .map(|answer| Some(answer.into()))
+            .map_err(|e| e.in_day(Self::DAY))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     lazy_static! {
@@ -201,7 +324,8 @@ bababa
 abbbab
 aaabbb
 aaaabbb"#
-        );
+        )
+        .expect("invalid input");
         static ref EXAMPLE_2: (Rules, Vec<&'static str>) = parse(
             r#"42: 9 14 | 10 1
 9: 14 27 | 1 26
@@ -250,7 +374,8 @@ aaaabbaaaabbaaa
 aaaabbaabbaaaaaaabbbabbbaaabbaabaaa
 babaaabbbaaabaababbaabababaaab
 aabbbbbaabbbaaaaaabbbbbababaaaaabbaaabba"#
-        );
+        )
+        .expect("invalid input");
     }
 
     #[test]
@@ -265,7 +390,7 @@ aabbbbbaabbbaaaaaabbbbbababaaaaabbaaabba"#
 
     #[test]
     fn same_results_part_2_2() {
-        assert_eq!(solve_2(&EXAMPLE_2.0, &EXAMPLE_2.1), 12);
+        assert_eq!(solve_2(&EXAMPLE_2.0, &EXAMPLE_2.1), Ok(12));
     }
 
     #[test]
@@ -310,11 +435,48 @@ aabbbbbaabbbaaaaaabbbbbababaaaaabbaaabba"#
         assert_eq!(rules.is_match("abab", 0), vec![("ab", "ab"), ("abab", "")]);
     }
 
+    #[test]
+    fn invalid_rule() {
+        let error = parse("0: 1 2\n1: \"a\n2: \"b\"\n\nab")
+            .map(drop)
+            .expect_err("invalid rule");
+        assert_eq!(error.position, Some((2, 4)));
+        assert_eq!(error.snippet, "\"a");
+
+        let error = parse("0: 1 x\n1: \"a\"\n\nab")
+            .map(drop)
+            .expect_err("invalid rule");
+        assert_eq!(error.position, Some((1, 6)));
+    }
+
+    #[test]
+    fn undefined_rule() {
+        let error = parse("0: 1 2\n1: \"a\"\n2: 1 | 3\n\nab")
+            .map(drop)
+            .expect_err("undefined rule");
+        assert_eq!(error.position, Some((3, 8)));
+        assert_eq!(error.message, "undefined rule");
+
+        let error = parse("1: \"a\"\n\nab").map(drop).expect_err("no rule 0");
+        assert_eq!(error.message, "rule 0 is not defined");
+
+        let (rules, messages) = parse("0: 1\n1: \"a\"\n\na").expect("invalid input");
+        assert_eq!(solve_1(&rules, &messages), 1);
+        assert!(solve_2(&rules, &messages).is_err());
+    }
+
+    #[test]
+    fn left_recursive_rule() {
+        assert!(parse("0: 0 1 | 1\n1: \"a\"\n\naa").is_err());
+    }
+
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_1(b: &mut Bencher) {
         b.iter(part_1);
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     #[ignore]
     fn bench_part_2(b: &mut Bencher) {
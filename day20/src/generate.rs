@@ -0,0 +1,192 @@
+use common::Rng;
+use std::ops::RangeInclusive;
+
+const MONSTER: [&str; 3] = [
+    "                  # ",
+    "#    ##    ##    ###",
+    " #  #  #  #  #  #   ",
+];
+
+/// The tiles are 10×10, 8×8 once their borders are removed.
+const TILE: usize = 10;
+
+/// The sizes `generate` supports: 12 is that of the puzzle inputs, and 15
+/// the most for which 10-cell borders can all be told apart (16 would also
+/// be the widest image that fits the 128-bit rows of the solver).
+pub const SIZES: RangeInclusive<usize> = 3..=15;
+
+/// `size`×`size` tiles (clamped to `SIZES`), each one rotated and flipped at
+/// random, of an image with some sea monsters.
+///
+/// The borders shared by two tiles are drawn first, each one different
+/// from all the others and from their reverse, and not a palindrome, so
+/// that the tiles fit together in a single way.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let n = size.clamp(*SIZES.start(), *SIZES.end());
+    let width = n * (TILE - 2);
+
+    // the image without borders, with a sea monster every 400 cells
+    let mut image = (0..width)
+        .map(|_| (0..width).map(|_| rng.chance(0.3)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let (height, length) = (MONSTER.len(), MONSTER[0].len());
+    let mut monsters: Vec<(usize, usize)> = Vec::new();
+    for _ in 0..1000 {
+        if monsters.len() >= (width * width / 400).max(1) {
+            break;
+        }
+        let (x, y) = (
+            rng.below((width - length + 1) as u64) as usize,
+            rng.below((width - height + 1) as u64) as usize,
+        );
+        if monsters
+            .iter()
+            .all(|&(mx, my)| mx.abs_diff(x) >= length || my.abs_diff(y) >= height)
+        {
+            monsters.push((x, y));
+        }
+    }
+    for &(x, y) in &monsters {
+        for (dy, line) in MONSTER.iter().enumerate() {
+            for (dx, c) in line.chars().enumerate() {
+                image[y + dy][x + dx] |= c == '#';
+            }
+        }
+    }
+
+    // the corners of the tiles, and the borders between them: `rows[y][x]`
+    // is the top border of the tile at (x, y), `columns[y][x]` its left one
+    let corners = loop {
+        let corners = (0..=n)
+            .map(|_| (0..=n).map(|_| rng.chance(0.5)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        if fits(&corners) {
+            break corners;
+        }
+    };
+    let mut used = std::collections::HashSet::new();
+    let mut border = |rng: &mut Rng, first: bool, last: bool| loop {
+        let bits =
+            (rng.below(1 << (TILE - 2)) << 1) as u16 | first as u16 | ((last as u16) << (TILE - 1));
+        let reversed = bits.reverse_bits() >> (16 - TILE);
+
+        if bits != reversed && used.insert(bits.min(reversed)) {
+            break (0..TILE).map(|i| bits & (1 << i) != 0).collect::<Vec<_>>();
+        }
+    };
+    let rows = (0..=n)
+        .map(|y| {
+            (0..n)
+                .map(|x| border(rng, corners[y][x], corners[y][x + 1]))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let columns = (0..n)
+        .map(|y| {
+            (0..=n)
+                .map(|x| border(rng, corners[y][x], corners[y + 1][x]))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut ids = (1000..10_000).collect::<Vec<_>>();
+    rng.shuffle(&mut ids);
+
+    let mut tiles = (0..n * n)
+        .map(|i| {
+            let (x, y) = (i % n, i / n);
+            let mut tile = (0..TILE)
+                .map(|r| {
+                    (0..TILE)
+                        .map(|c| match (r, c) {
+                            (0, _) => rows[y][x][c],
+                            (r, _) if r == TILE - 1 => rows[y + 1][x][c],
+                            (_, 0) => columns[y][x][r],
+                            (_, c) if c == TILE - 1 => columns[y][x + 1][r],
+                            _ => image[y * (TILE - 2) + r - 1][x * (TILE - 2) + c - 1],
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+
+            for _ in 0..rng.below(4) {
+                tile = rotate(&tile);
+            }
+            if rng.chance(0.5) {
+                tile.reverse();
+            }
+
+            (ids[i], tile)
+        })
+        .collect::<Vec<_>>();
+    rng.shuffle(&mut tiles);
+
+    tiles
+        .iter()
+        .map(|(id, tile)| {
+            let lines = tile
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|&on| if on { '#' } else { '.' })
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>();
+
+            format!("Tile {}:\n{}\n", id, lines.join("\n"))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Whether there are enough distinct borders for the tiles with `corners`:
+/// 120 from an unset corner to an unset one (256 for the 8 cells between
+/// them, but for the 16 palindromes, up to reversal), as many between set
+/// corners, and 256 between an unset and a set one.
+fn fits(corners: &[Vec<bool>]) -> bool {
+    let n = corners.len() - 1;
+    let mut counts = [0; 3];
+    for y in 0..=n {
+        for x in 0..=n {
+            let ends = (x < n)
+                .then(|| (corners[y][x], corners[y][x + 1]))
+                .into_iter()
+                .chain((y < n).then(|| (corners[y][x], corners[y + 1][x])));
+            for (first, last) in ends {
+                counts[if first != last { 2 } else { first as usize }] += 1;
+            }
+        }
+    }
+
+    counts[0] <= 120 && counts[1] <= 120 && counts[2] <= 256
+}
+
+/// `tile` rotated a quarter turn clockwise.
+fn rotate(tile: &[Vec<bool>]) -> Vec<Vec<bool>> {
+    (0..tile.len())
+        .map(|r| {
+            (0..tile.len())
+                .map(|c| tile[tile.len() - 1 - c][r])
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Tiles;
+
+    #[test]
+    fn valid_input() {
+        for (seed, size) in (0..6).zip([3, 12, 15].iter().cycle()) {
+            let input = generate(&mut Rng::new(seed), *size);
+            let tiles = input.trim().parse::<Tiles<u32>>().unwrap();
+            let cells = input.matches('#').count() as u32;
+
+            assert_eq!(tiles.len(), size * size);
+            assert!(crate::solve_1(&tiles) >= 1000u128.pow(4));
+            assert!(crate::solve_2(&tiles).unwrap() < cells);
+        }
+    }
+}
@@ -1,9 +1,14 @@
-#![feature(test)]
+#![cfg_attr(feature = "bench", feature(test))]
+
+#[cfg(feature = "bench")]
 extern crate test;
 
+#[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
 
+use common::{Answer, Error, Picture, Raster, Solution};
+use grid::Grid;
 use std::collections::{HashMap, HashSet, VecDeque};
 
 mod tiles;
@@ -12,46 +17,39 @@ use tiles::*;
 mod combination2b;
 use combination2b::*;
 
-#[cfg(debug_assertions)]
-macro_rules! debug {
-    ($($e:expr),*) => { println!($($e),*); }
-}
+mod generate;
 
-#[cfg(debug_assertions)]
-macro_rules! print_mask {
-    ($e:expr) => {
-        println!(stringify!($e));
-        for i in $e {
-            println!("{:0128b}", i);
-        }
-    };
-}
+pub use generate::{generate, SIZES};
 
-#[cfg(not(debug_assertions))]
-macro_rules! debug {
-    ($($e:expr),*) => {};
-}
+const MONSTER: [&str; 3] = [
+    "                  # ",
+    "#    ##    ##    ###",
+    " #  #  #  #  #  #   ",
+];
+const MONSTER_WIDTH: usize = MONSTER[0].len();
 
-#[cfg(not(debug_assertions))]
-macro_rules! print_mask {
-    ($e:expr) => {};
+/// The sea monster as one bit mask per row, right aligned.
+fn monster_pattern() -> Vec<u128> {
+    MONSTER
+        .iter()
+        .map(|line| {
+            line.chars()
+                .fold(0, |v, c| if c == '#' { (v << 1) | 1 } else { v << 1 })
+        })
+        .collect()
 }
 
-lazy_static! {
-    static ref INPUT: Tiles<u32> = include_str!("../input")
-        .trim()
-        .parse()
-        .expect("invalid input");
-    static ref MONSTER: &'static str = r"                  # 
-#    ##    ##    ###
- #  #  #  #  #  #   ";
-    static ref MONSTER_PATTERN: Vec<u128> = MONSTER
-        .lines()
-        .map(|line| line
-            .chars()
-            .fold(0, |v, c| if c == '#' { (v << 1) | 1 } else { v << 1 }))
-        .collect();
-    static ref MONSTER_WIDTH: usize = MONSTER.lines().next().unwrap().len();
+/// The embedded puzzle input, parsed on first use.
+#[cfg(feature = "input")]
+fn input() -> &'static Tiles<u32> {
+    static INPUT: std::sync::OnceLock<Tiles<u32>> = std::sync::OnceLock::new();
+
+    INPUT.get_or_init(|| {
+        include_str!("../input")
+            .trim()
+            .parse()
+            .expect("invalid input")
+    })
 }
 
 fn solve_1(input: &Tiles<u32>) -> u128 {
@@ -79,9 +77,20 @@ fn solve_1(input: &Tiles<u32>) -> u128 {
         .product()
 }
 
-fn assemble_tile(input: &Tiles<u32>) -> (Tile<(), TileOptionalNop>, usize, usize, usize) {
+#[allow(clippy::type_complexity)]
+fn assemble_tile(
+    input: &Tiles<u32>,
+) -> Result<(Tile<(), TileOptionalNop>, usize, usize, usize), Error> {
     let size = input.len();
     let edge = (size as f32).sqrt() as i128;
+    if edge < 2 || (edge * edge) as usize != size {
+        return Err(Error::new(format!(
+            "{} tiles do not form a square image",
+            size
+        )));
+    }
+
+    let mismatch = |(x, y): (i128, i128)| Error::new(format!("no tile fits at ({}, {})", x, y));
 
     let mut map: Vec<Option<u128>> = vec![None; size];
     let mut tiles: HashMap<u128, Tile<u32, TileOptionalU32>> = HashMap::new();
@@ -140,24 +149,20 @@ fn assemble_tile(input: &Tiles<u32>) -> (Tile<(), TileOptionalNop>, usize, usize
                 .iter()
                 .filter_map(|(id, v)| if v.len() == 2 { Some(*id) } else { None })
                 .next()
-                .unwrap();
+                .ok_or_else(|| Error::new("no corner tile"))?;
 
             let mut seed_tile = input[&seed].to_owned();
 
             let mut n = neighbors[&seed].iter();
 
-            let right = n.next().unwrap();
-            let bottom = n.next().unwrap();
-
-            assert!(n.next().is_none());
+            let (right, bottom) = match (n.next(), n.next()) {
+                (Some(right), Some(bottom)) => (right, bottom),
+                _ => return Err(mismatch((0, 0))),
+            };
 
             let mut right_tile = input[right].to_owned();
             let mut bottom_tile = input[bottom].to_owned();
 
-            debug!("seed tile: {}\n{:?}", seed, seed_tile);
-            debug!("right tile: {}\n{:?}", right, right_tile);
-            debug!("bottom tile: {}\n{:?}", bottom, bottom_tile);
-
             // fix sides
             {
                 let right_edge = seed_tile
@@ -171,68 +176,54 @@ fn assemble_tile(input: &Tiles<u32>) -> (Tile<(), TileOptionalNop>, usize, usize
                     .copied()
                     .collect();
 
-                debug!("right edge: {:?}", right_edge);
-                debug!("bottom edge: {:?}", bottom_edge);
-                debug!();
-
-                let right_rotation = right_tile.find(&right_edge).unwrap();
+                let right_rotation = right_tile
+                    .find(&right_edge)
+                    .ok_or_else(|| mismatch((1, 0)))?;
                 if right_rotation != LEFT_INDEX {
                     let angle = right_rotation as isize - LEFT_INDEX as isize;
                     right_tile.rotate(angle);
-                    debug!("right tile rotate {}", angle);
-                    debug!("{:?}", right_tile);
                 }
 
-                let bottom_rotation = bottom_tile.find(&bottom_edge).unwrap();
+                let bottom_rotation = bottom_tile
+                    .find(&bottom_edge)
+                    .ok_or_else(|| mismatch((0, 1)))?;
                 if bottom_rotation != TOP_INDEX {
                     let angle = bottom_rotation as isize - TOP_INDEX as isize;
                     bottom_tile.rotate(angle);
-                    debug!("bottom tile rotate {}", angle);
-                    debug!("{:?}", bottom_tile);
                 }
 
-                let seed_rotation = seed_tile.find(&right_edge).unwrap();
+                let seed_rotation = seed_tile
+                    .find(&right_edge)
+                    .ok_or_else(|| mismatch((0, 0)))?;
                 if seed_rotation != RIGHT_INDEX {
                     let angle = seed_rotation as isize - RIGHT_INDEX as isize;
                     seed_tile.rotate(angle);
-                    debug!("seed tile rotate {}", angle);
-                    debug!("{:?}", seed_tile);
                 }
 
-                let seed_flip = seed_tile.find(&bottom_edge).unwrap();
+                let seed_flip = seed_tile
+                    .find(&bottom_edge)
+                    .ok_or_else(|| mismatch((0, 0)))?;
                 if seed_flip != BOTTOM_INDEX {
-                    assert_eq!(seed_flip, TOP_INDEX);
+                    if seed_flip != TOP_INDEX {
+                        return Err(mismatch((0, 0)));
+                    }
                     seed_tile.flip_v();
-                    debug!("seed tile flip v");
-                    debug!("{:?}", seed_tile);
                 }
 
                 if seed_tile.edge_vec[RIGHT_INDEX].0 != right_tile.edge_vec[LEFT_INDEX].1 {
                     right_tile.flip_v();
-                    debug!("right tile flip v");
-                    debug!("{:?}", right_tile);
                 }
 
                 if seed_tile.edge_vec[BOTTOM_INDEX].0 != bottom_tile.edge_vec[TOP_INDEX].1 {
                     bottom_tile.flip_h();
-                    debug!("bottom tile flip h");
-                    debug!("{:?}", bottom_tile);
                 }
 
-                debug!();
-
-                debug!("post seed tile\n{:?}", seed_tile);
-                debug!("post right tile\n{:?}", right_tile);
-                debug!("post bottom tile\n{:?}", bottom_tile);
-
-                assert_eq!(
-                    seed_tile.edge_vec[RIGHT_INDEX].0, right_tile.edge_vec[LEFT_INDEX].1,
-                    "seed vs right"
-                );
-                assert_eq!(
-                    seed_tile.edge_vec[BOTTOM_INDEX].0, bottom_tile.edge_vec[TOP_INDEX].1,
-                    "seed vs bottom"
-                );
+                if seed_tile.edge_vec[RIGHT_INDEX].0 != right_tile.edge_vec[LEFT_INDEX].1 {
+                    return Err(mismatch((1, 0)));
+                }
+                if seed_tile.edge_vec[BOTTOM_INDEX].0 != bottom_tile.edge_vec[TOP_INDEX].1 {
+                    return Err(mismatch((0, 1)));
+                }
             }
 
             set(&mut map, &mut tiles, &mut found, (0, 0), seed, seed_tile);
@@ -273,14 +264,14 @@ fn assemble_tile(input: &Tiles<u32>) -> (Tile<(), TileOptionalNop>, usize, usize
                     .difference(&found)
                     .copied()
                     .collect::<HashSet<_>>(),
-                (None, None) => unreachable!(),
+                (None, None) => return Err(mismatch((x, y))),
             };
 
-            assert_eq!(n.len(), 1, "({}, {}): {:?}", x, y, n);
-
-            let id = n.iter().next().unwrap();
+            let id = match (n.len(), n.iter().next()) {
+                (1, Some(id)) => id,
+                _ => return Err(mismatch((x, y))),
+            };
             let mut tile = input[id].to_owned();
-            debug!("\nworking on {}\n{:?}", id, tile);
 
             // TODO: I think this algo is bad
             // I must rotate & flip tile on one shot if left and top edge are both of them defined
@@ -292,22 +283,18 @@ fn assemble_tile(input: &Tiles<u32>) -> (Tile<(), TileOptionalNop>, usize, usize
                     .intersection(&tile.edge_set)
                     .copied()
                     .collect::<HashSet<_>>();
-                assert_eq!(top_edge.len(), 2, "top_edge invalid");
-
-                debug!("top tile: {}\nedge: {:?}\n{:?}", top_id, top_edge, top_tile);
-                assert_eq!(
-                    top_tile.find(&top_edge).unwrap(),
-                    BOTTOM_INDEX,
-                    "({}, {}) top_tile invalid position",
-                    x,
-                    y
-                );
-
-                let top_rotation = tile.find(&top_edge).unwrap();
+                if top_edge.len() != 2 {
+                    return Err(mismatch((x, y)));
+                }
+
+                if top_tile.find(&top_edge) != Some(BOTTOM_INDEX) {
+                    return Err(mismatch((x, y)));
+                }
+
+                let top_rotation = tile.find(&top_edge).ok_or_else(|| mismatch((x, y)))?;
                 if top_rotation != TOP_INDEX {
                     let angle = top_rotation as isize - TOP_INDEX as isize;
                     tile.rotate(angle);
-                    debug!("tile rotate {}", angle);
                 }
 
                 Some(top_tile.edge_vec[BOTTOM_INDEX].to_owned())
@@ -322,31 +309,24 @@ fn assemble_tile(input: &Tiles<u32>) -> (Tile<(), TileOptionalNop>, usize, usize
                     .intersection(&tile.edge_set)
                     .copied()
                     .collect::<HashSet<_>>();
-                assert_eq!(left_edge.len(), 2, "left_edge invalid");
-
-                debug!("left tile: {}\nedge: {:?}\n{:?}", left_id, edge, left_tile);
-                assert_eq!(
-                    left_tile.find(&left_edge).unwrap(),
-                    RIGHT_INDEX,
-                    "({}, {}) left_tile invalid position",
-                    x,
-                    y
-                );
-
-                let left_rotation = tile.find(&left_edge).unwrap();
+                if left_edge.len() != 2 {
+                    return Err(mismatch((x, y)));
+                }
+
+                if left_tile.find(&left_edge) != Some(RIGHT_INDEX) {
+                    return Err(mismatch((x, y)));
+                }
+
+                let left_rotation = tile.find(&left_edge).ok_or_else(|| mismatch((x, y)))?;
                 if left_rotation != LEFT_INDEX {
                     if top_edge.is_some() {
-                        assert_eq!(
-                            left_rotation, RIGHT_INDEX,
-                            "({}, {}), cannot rotate {}",
-                            x, y, left_rotation
-                        );
+                        if left_rotation != RIGHT_INDEX {
+                            return Err(mismatch((x, y)));
+                        }
                         tile.flip_h();
-                        debug!("tile flip h");
                     } else {
                         let angle = left_rotation as isize - LEFT_INDEX as isize;
                         tile.rotate(angle);
-                        debug!("tile rotate {}", angle);
                     }
                 }
 
@@ -358,29 +338,18 @@ fn assemble_tile(input: &Tiles<u32>) -> (Tile<(), TileOptionalNop>, usize, usize
             if let Some((a, _)) = left_edge {
                 if tile.edge_vec[LEFT_INDEX].1 != a {
                     tile.flip_v();
-                    debug!("tile flip v");
                 }
             }
             if let Some((a, _)) = top_edge {
                 if tile.edge_vec[TOP_INDEX].1 != a {
                     tile.flip_h();
-                    debug!("tile flip h");
                 }
             }
 
-            if let Some((a, _)) = left_edge {
-                assert_eq!(
-                    a, tile.edge_vec[LEFT_INDEX].1,
-                    "({}, {}) invalid left position",
-                    x, y
-                );
-            }
-            if let Some((a, _)) = top_edge {
-                assert_eq!(
-                    a, tile.edge_vec[TOP_INDEX].1,
-                    "({}, {}) invalid top position",
-                    x, y
-                );
+            if matches!(left_edge, Some((a, _)) if a != tile.edge_vec[LEFT_INDEX].1)
+                || matches!(top_edge, Some((a, _)) if a != tile.edge_vec[TOP_INDEX].1)
+            {
+                return Err(mismatch((x, y)));
             }
 
             set(&mut map, &mut tiles, &mut found, (x, y), *id, tile);
@@ -398,41 +367,60 @@ fn assemble_tile(input: &Tiles<u32>) -> (Tile<(), TileOptionalNop>, usize, usize
     // removing borders...
 
     let edge = edge as usize;
-    let h = input.values().next().unwrap().image.len() - 2;
-    let w = input.values().next().unwrap().image[0].len() - 2;
-    debug!("making mega tile: ({}, {})", edge * w, edge * h);
-
-    let mut image = Vec::new();
-    image.resize_with(edge * h, Vec::new);
+    let size = input.values().next().map_or(0, |tile| tile.image.width());
+    let (h, w) = (size.saturating_sub(2), size.saturating_sub(2));
+    if w == 0 {
+        return Err(Error::new("tiles narrower than 3 cells have no image"));
+    }
+    if edge * w > 128 {
+        return Err(Error::new("images wider than 128 cells are not supported"));
+    }
 
-    let mut row = 0;
+    let mut image = Vec::with_capacity(edge * w * edge * h);
     for y in 0..edge {
-        for x in 0..edge {
-            let tile = &tiles[&map[x + y * edge].unwrap()];
-            for (i, v) in tile.image[1..tile.image.len() - 1].iter().enumerate() {
-                image[row + i].append(&mut v[1..v.len() - 1].to_owned());
+        let row = (0..edge)
+            .map(|x| {
+                map[x + y * edge]
+                    .and_then(|id| tiles.get(&id))
+                    .ok_or_else(|| mismatch((x as i128, y as i128)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for i in 1..=h {
+            for tile in &row {
+                image.extend_from_slice(&tile.image.row(i)[1..=w]);
             }
         }
-        row += h;
     }
 
-    assert_eq!(image.len(), edge * h, "invalid rows");
-    assert_eq!(image[0].len(), edge * w, "invalid cols");
-
-    let tile: Tile<(), TileOptionalNop> = Tile::new_from_image(image);
-    debug!("mega tile\n{:?}", tile);
+    let tile: Tile<(), TileOptionalNop> = Tile::new_from_image(Grid::new(edge * w, image));
 
-    (tile, edge, h, w)
+    Ok((tile, edge, h, w))
 }
 
-fn solve_2(input: &Tiles<u32>) -> u32 {
-    let (tile, edge, h, _w) = assemble_tile(input);
+fn solve_2(input: &Tiles<u32>) -> Result<u32, Error> {
+    let (tile, edge, h, _w) = assemble_tile(input)?;
 
     let total_water_roughness = tile.get_mask().iter().map(|v| v.count_ones()).sum::<u32>();
 
-    print_mask!(tile.get_mask());
-    print_mask!(MONSTER_PATTERN.iter());
+    let (_, monsters) =
+        find_monsters(&tile, edge * h).ok_or_else(|| Error::new("no sea monster found"))?;
+    let monster_roughness = monster_pattern()
+        .iter()
+        .map(|v| v.count_ones())
+        .sum::<u32>();
+
+    Ok(total_water_roughness - monsters.len() as u32 * monster_roughness)
+}
 
+/// The image turned so that the sea monsters show, with the top left
+/// corner of each.
+#[allow(clippy::type_complexity)]
+fn find_monsters(
+    tile: &Tile<(), TileOptionalNop>,
+    width: usize,
+) -> Option<(Tile<(), TileOptionalNop>, Vec<(usize, usize)>)> {
+    let pattern = monster_pattern();
     let flips: Vec<fn(&mut Tile<_, _>)> = vec![flip_none, flip_h, flip_v];
     for r in 0..4 {
         let mut tile = tile.to_owned();
@@ -442,15 +430,97 @@ fn solve_2(input: &Tiles<u32>) -> u32 {
             let mut tile = tile.to_owned();
             f(&mut tile);
 
-            let monster_roughness =
-                check_pattern(&tile.get_mask(), edge * h, &MONSTER_PATTERN, *MONSTER_WIDTH);
-            if monster_roughness != 0 {
-                return total_water_roughness - monster_roughness;
+            let monsters = find_pattern(&tile.get_mask(), width, &pattern, MONSTER_WIDTH);
+            if !monsters.is_empty() {
+                return Some((tile, monsters));
+            }
+        }
+    }
+
+    None
+}
+
+/// The assembled image, with the sea monsters in red for part 2.
+fn picture(input: &Tiles<u32>, part: usize) -> Result<Raster, Error> {
+    let (mut tile, edge, h, _w) = assemble_tile(input)?;
+    let width = edge * h;
+
+    let mut monsters = Vec::new();
+    if part == 2 {
+        let (turned, found) =
+            find_monsters(&tile, width).ok_or_else(|| Error::new("no sea monster found"))?;
+        tile = turned;
+        monsters = found;
+    }
+
+    let mut raster = Raster::new(tile.image.width(), tile.image.height());
+    for (position, cell) in tile.image.positions() {
+        if *cell == TileCell::On {
+            raster.set(position, (13, 71, 161));
+        }
+    }
+    for (x, y) in monsters {
+        for (i, row) in MONSTER.iter().enumerate() {
+            for (j, c) in row.chars().enumerate() {
+                if c == '#' {
+                    raster.set((x + j, y + i), (229, 57, 53));
+                }
+            }
+        }
+    }
+
+    Ok(raster)
+}
+
+/// What `assemble_tile` takes for granted: a square number of tiles, and
+/// edges that each match at most one edge of another tile.
+fn lint(text: &str, input: &Tiles<u32>) -> Vec<Error> {
+    const SIDES: [&str; 4] = ["top", "right", "bottom", "left"];
+
+    let mut lints = Vec::new();
+    let size = input.len();
+    let edge = (size as f32).sqrt() as usize;
+    if edge * edge != size {
+        lints.push(Error::new(format!(
+            "{} tiles do not form a square image",
+            size
+        )));
+    }
+
+    let mut ids = input.keys().collect::<Vec<_>>();
+    ids.sort_unstable();
+    for id in ids {
+        let header = format!("Tile {}:", id);
+        let header = text
+            .find(&header)
+            .map_or("", |start| &text[start..start + header.len()]);
+
+        for (side, (sign, _)) in SIDES.iter().zip(&input[id].edge_vec) {
+            let mut matches = input
+                .iter()
+                .filter(|(other, tile)| *other != id && tile.edge_set.contains(sign))
+                .map(|(other, _)| *other)
+                .collect::<Vec<_>>();
+            if matches.len() > 1 {
+                matches.sort_unstable();
+                lints.push(Error::at(
+                    text,
+                    header,
+                    format!(
+                        "{} edge matches tiles {}",
+                        side,
+                        matches
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                ));
             }
         }
     }
 
-    unreachable!()
+    lints
 }
 
 fn flip_none<T, O: TileOptional<T>>(_tile: &mut Tile<T, O>) {
@@ -465,56 +535,130 @@ fn flip_v<T, O: TileOptional<T>>(tile: &mut Tile<T, O>) {
     tile.flip_v();
 }
 
-fn check_pattern(
+/// The top left corners of the places where `pattern` shows in `image`,
+/// both as one bit mask per row, right aligned.
+fn find_pattern(
     image: &[u128],
     image_width: usize,
     pattern: &[u128],
     pattern_width: usize,
-) -> u32 {
+) -> Vec<(usize, usize)> {
     let pattern_ones = pattern.iter().map(|v| v.count_ones()).collect::<Vec<_>>();
     let image_height = image.len();
     let pattern_height = pattern.len();
+    if image_height < pattern_height || image_width < pattern_width {
+        return Vec::new();
+    }
 
-    let mut count = 0;
-    let mut row = 0;
-    while row < image_height - pattern_height + 1 {
-        let mut partial_count = 0;
-        let mut s = 0;
-        while s < image_width - pattern_width + 1 {
+    let mut found = Vec::new();
+    for row in 0..image_height - pattern_height + 1 {
+        for s in 0..image_width - pattern_width + 1 {
             if image[row..row + pattern_height]
                 .iter()
                 .enumerate()
                 .all(|(i, row)| (row & (pattern[i] << s)).count_ones() == pattern_ones[i])
             {
-                debug!("hit at ({}, {})", s, row);
-                partial_count += 1;
+                found.push((image_width - pattern_width - s, row));
             }
-            s += 1;
-        }
-        if partial_count > 0 {
-            count += partial_count;
         }
-        row += 1;
     }
 
-    if count != 0 {
-        count * pattern_ones.into_iter().sum::<u32>()
-    } else {
-        0
-    }
+    found
 }
 
+#[cfg(feature = "input")]
 pub fn part_1() -> u128 {
-    solve_1(&INPUT)
+    solve_1(input())
 }
 
+#[cfg(feature = "input")]
 pub fn part_2() -> u32 {
-    solve_2(&INPUT)
+    solve_2(input()).expect("invalid input")
+}
+
+pub fn solve_part_1(input: &str) -> Result<u128, Error> {
+    input
+        .trim()
+        .parse::<Tiles<u32>>()
+        .map(|input| solve_1(&input))
+        .map_err(|e| e.in_day(Day20::DAY))
+}
+
+pub fn solve_part_2(input: &str) -> Result<u32, Error> {
+    input
+        .trim()
+        .parse::<Tiles<u32>>()
+        .and_then(|input| solve_2(&input))
+        .map_err(|e| e.in_day(Day20::DAY))
+}
+
+pub struct Day20;
+
+impl Solution for Day20 {
+    const DAY: u32 = 20;
+
+    type Input<'a> = Tiles<u32>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+        input
+            .trim()
+            .parse::<Tiles<u32>>()
+            .map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+        Ok(solve_1(input).into())
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+        solve_2(input)
+            .map(|answer| Some(answer.into()))
+            .map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn lint(text: &str, input: &Self::Input<'_>) -> Vec<Error> {
+        lint(text, input)
+    }
+
+    fn picture(input: &Self::Input<'_>, part: usize) -> Option<Result<Picture, Error>> {
+        Some(picture(input, part).map(Picture::Raster))
+    }
+
+    const COMMANDS: &'static [(&'static str, &'static str)] = &[
+        ("tile <id>", "print a tile with the signatures of its edges"),
+        (
+            "render",
+            "print the assembled image, borders removed, before looking for monsters",
+        ),
+    ];
+
+    fn explore(
+        input: &mut Self::Input<'_>,
+        command: &str,
+        args: &[&str],
+    ) -> Option<Result<String, Error>> {
+        match (command, args) {
+            ("render", []) => Some(assemble_tile(input).map(|(tile, ..)| tile.get_image())),
+            ("tile", [id]) => Some(
+                id.parse()
+                    .ok()
+                    .and_then(|id| input.get(&id))
+                    .map(|tile| format!("{:?}", tile))
+                    .ok_or_else(|| Error::new(format!("no tile {}", id))),
+            ),
+            ("render", _) | ("tile", _) => Some(Err(Error::new(format!(
+                "wrong arguments for {}, type help for the commands",
+                command
+            )))),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     lazy_static! {
@@ -523,8 +667,7 @@ mod tests {
             .parse()
             .expect("invalid input");
         static ref INPUT_RESULT: Tile<(), TileOptionalNop> =
-            Tile::new_from_lines(include_str!("../input-example-result").trim().lines())
-                .expect("invalid input");
+            Tile::new(include_str!("../input-example-result").trim()).expect("invalid input");
     }
 
     #[test]
@@ -534,22 +677,23 @@ mod tests {
 
     #[test]
     fn same_results_part_2() {
-        assert_eq!(solve_2(&INPUT), 273);
+        assert_eq!(solve_2(&INPUT), Ok(273));
     }
 
     #[test]
     fn same_results_part_2_partial() {
-        let tile = assemble_tile(&INPUT).0;
+        let tile = assemble_tile(&INPUT).expect("invalid input").0;
 
-        debug!("assembled tile\n{:?}", tile);
-        debug!("target tile\n{:?}", *INPUT_RESULT);
-
-        assert_eq!(tile.image.len(), INPUT_RESULT.image.len(), "invalid height");
         assert_eq!(
-            tile.image[0].len(),
-            INPUT_RESULT.image[0].len(),
+            tile.image.height(),
+            INPUT_RESULT.image.height(),
             "invalid height"
         );
+        assert_eq!(
+            tile.image.width(),
+            INPUT_RESULT.image.width(),
+            "invalid width"
+        );
 
         let flips: Vec<fn(&mut Tile<_, _>)> = vec![flip_none, flip_h, flip_v];
         for f in flips {
@@ -569,23 +713,100 @@ mod tests {
     }
 
     #[test]
-    fn test_check_pattern() {
+    fn test_find_pattern() {
         assert_eq!(
-            check_pattern(
-                &MONSTER_PATTERN,
-                *MONSTER_WIDTH,
-                &MONSTER_PATTERN,
-                *MONSTER_WIDTH
+            find_pattern(
+                &monster_pattern(),
+                MONSTER_WIDTH,
+                &monster_pattern(),
+                MONSTER_WIDTH
             ),
-            15
+            vec![(0, 0)]
+        );
+    }
+
+    #[test]
+    fn sea_monsters_picture() {
+        let input = Day20::parse(include_str!("../input-example")).expect("invalid input");
+        let raster = picture(&input, 2).expect("invalid input");
+
+        let red = (0..raster.height())
+            .flat_map(|y| (0..raster.width()).map(move |x| (x, y)))
+            .filter(|&position| raster.get(position) == Some((229, 57, 53)))
+            .count();
+        assert_eq!((raster.width(), raster.height()), (24, 24));
+        assert_eq!(red, 2 * 15);
+    }
+
+    #[test]
+    fn render() {
+        let mut input = Day20::parse(include_str!("../input-example")).expect("invalid input");
+
+        let image = Day20::explore(&mut input, "render", &[])
+            .expect("unknown command")
+            .expect("invalid input");
+
+        assert_eq!(image.lines().count(), INPUT_RESULT.image.height());
+        assert!(Day20::explore(&mut input, "tile", &["2311"]).is_some_and(|tile| tile.is_ok()));
+        assert!(Day20::explore(&mut input, "tile", &["1"]).is_some_and(|tile| tile.is_err()));
+    }
+
+    #[test]
+    fn invalid_tiles() {
+        let error = "Tile 2311:\n#.\n.#\n\nTile 1951\n#.\n.#"
+            .parse::<Tiles<u32>>()
+            .map(drop)
+            .expect_err("invalid header");
+        assert_eq!(error.position, Some((5, 1)));
+
+        let error = "Tile 2311:\n#.\n.#\n\nTile 1951:\n#.\n.x"
+            .parse::<Tiles<u32>>()
+            .map(drop)
+            .expect_err("invalid cell");
+        assert_eq!(error.position, Some((7, 2)));
+        assert_eq!(error.snippet, "x");
+
+        let error = "Tile 2311:\n#.\n.#\n\nTile 1951:\n#.\n.##"
+            .parse::<Tiles<u32>>()
+            .map(drop)
+            .expect_err("ragged tile");
+        assert_eq!(error.position, Some((7, 1)));
+    }
+
+    #[test]
+    fn not_a_square() {
+        let tiles = "Tile 1:\n#.\n.#\n\nTile 2:\n#.\n.#\n\nTile 3:\n#.\n.#"
+            .parse::<Tiles<u32>>()
+            .expect("invalid input");
+
+        assert!(solve_2(&tiles).is_err());
+    }
+
+    #[test]
+    fn lint_tiles() {
+        let text = "Tile 1:\n#.\n..\n\nTile 2:\n#.\n..\n\nTile 3:\n.#\n..";
+        let tiles = Day20::parse(text).expect("invalid input");
+
+        // every edge of a tile matches an edge of both other tiles
+        let lints = lint(text, &tiles);
+        assert_eq!(lints.len(), 1 + 3 * 4);
+        assert_eq!(lints[0].to_string(), "3 tiles do not form a square image");
+        assert_eq!(
+            lints[5].to_string(),
+            "line 5, column 1: top edge matches tiles 1, 3: \"Tile 2:\""
         );
+
+        let text = include_str!("../input");
+        assert!(lint(text, &Day20::parse(text).expect("invalid input")).is_empty());
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_1(b: &mut Bencher) {
         b.iter(part_1);
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_2(b: &mut Bencher) {
         b.iter(part_2);
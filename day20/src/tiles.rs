@@ -1,20 +1,12 @@
+use common::Error;
+use grid::Grid;
 use std::collections::{HashMap, HashSet};
 
-const SINS: [isize; 4] = [0, 1, 0, -1];
-const CONS: [isize; 4] = [1, 0, -1, 0];
-const DELTA: [[isize; 2]; 4] = [[0, 0], [-1, 0], [-1, -1], [0, -1]];
-
 pub const TOP_INDEX: usize = 0;
 pub const RIGHT_INDEX: usize = 1;
 pub const BOTTOM_INDEX: usize = 2;
 pub const LEFT_INDEX: usize = 3;
 
-lazy_static! {
-    static ref ROTATIONS: Vec<[[isize; 2]; 2]> = (0..4)
-        .map(|i| [[CONS[i], -SINS[i]], [SINS[i], CONS[i]]])
-        .collect();
-}
-
 #[derive(Clone)]
 pub struct Tiles<T, O: TileOptional<T> = TileOptionalU32>(HashMap<u128, Tile<T, O>>);
 
@@ -27,25 +19,47 @@ impl<T, O: TileOptional<T>> std::ops::Deref for Tiles<T, O> {
 }
 
 impl<T, O: TileOptional<T>> std::str::FromStr for Tiles<T, O> {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        Ok(Self(
-            input
-                .split("\n\n")
-                .map(|tile| {
-                    let mut lines = tile.split('\n');
-
-                    lines
-                        .next()
-                        .ok_or_else(|| "id not found".to_string())
-                        .and_then(|line| {
-                            line[5..5 + 4].parse().map_err(|_| "invalid id".to_string())
-                        })
-                        .and_then(|id| Tile::new_from_lines(lines).map(|tile| (id, tile)))
-                })
-                .collect::<Result<HashMap<_, _>, _>>()?,
-        ))
+        let mut tiles = HashMap::new();
+        let mut size = None;
+
+        for tile in input.split("\n\n") {
+            let header = tile.split('\n').next().unwrap_or_default();
+            let id = header
+                .strip_prefix("Tile ")
+                .and_then(|id| id.strip_suffix(':'))
+                .ok_or_else(|| Error::at(input, header, "expecting `Tile <id>:`"))?;
+            let id = id
+                .parse()
+                .map_err(|_| Error::at(input, id, "invalid tile id"))?;
+
+            let body = &tile[header.len()..];
+            let body = body.strip_prefix('\n').unwrap_or(body);
+            let image = Tile::new(body).map_err(|e| e.within(input, body))?;
+
+            let edge = image.image.width();
+            if edge > 32 {
+                return Err(Error::at(
+                    input,
+                    header,
+                    "tiles wider than 32 cells are not supported",
+                ));
+            }
+            if *size.get_or_insert(edge) != edge {
+                return Err(Error::at(
+                    input,
+                    header,
+                    "tile size differs from the previous tiles",
+                ));
+            }
+            if tiles.insert(id, image).is_some() {
+                return Err(Error::at(input, header, "duplicate tile id"));
+            }
+        }
+
+        Ok(Self(tiles))
     }
 }
 
@@ -61,7 +75,7 @@ impl<T: std::fmt::Debug, O: TileOptional<T>> std::fmt::Debug for Tiles<T, O> {
 }
 
 pub trait TileOptional<T> {
-    fn calc_signs(_image: &[Vec<TileCell>]) -> (HashSet<T>, Vec<(T, T)>);
+    fn calc_signs(_image: &Grid<TileCell>) -> (HashSet<T>, Vec<(T, T)>);
 }
 
 pub struct TileOptionalU32;
@@ -86,17 +100,19 @@ impl TileOptionalU32 {
 pub struct TileOptionalNop;
 
 impl TileOptional<()> for TileOptionalNop {
-    fn calc_signs(_image: &[Vec<TileCell>]) -> (HashSet<()>, Vec<((), ())>) {
+    fn calc_signs(_image: &Grid<TileCell>) -> (HashSet<()>, Vec<((), ())>) {
         (HashSet::new(), Vec::new())
     }
 }
 
 impl TileOptional<u32> for TileOptionalU32 {
-    fn calc_signs(image: &[Vec<TileCell>]) -> (HashSet<u32>, Vec<(u32, u32)>) {
-        let top = Self::calc_sign(&image[0]);
-        let right = Self::calc_sign(image.iter().map(|row| row.last().unwrap()));
-        let bottom = Self::calc_sign(image.last().unwrap().iter().rev());
-        let left = Self::calc_sign(image.iter().rev().map(|row| &row[0]));
+    fn calc_signs(image: &Grid<TileCell>) -> (HashSet<u32>, Vec<(u32, u32)>) {
+        let (width, height) = (image.width(), image.height());
+
+        let top = Self::calc_sign(image.row(0));
+        let right = Self::calc_sign((0..height).map(|y| &image[(width - 1, y)]));
+        let bottom = Self::calc_sign(image.row(height - 1).iter().rev());
+        let left = Self::calc_sign((0..height).rev().map(|y| &image[(0, y)]));
 
         let mut set = HashSet::new();
         let mut vec = Vec::new();
@@ -116,7 +132,7 @@ impl TileOptional<u32> for TileOptionalU32 {
 }
 
 pub struct Tile<T, O: TileOptional<T> = TileOptionalU32> {
-    pub image: Vec<Vec<TileCell>>,
+    pub image: Grid<TileCell>,
     pub edge_set: HashSet<T>,
     pub edge_vec: Vec<(T, T)>,
     _t: std::marker::PhantomData<O>,
@@ -134,43 +150,22 @@ impl<T: Clone, O: TileOptional<T>> Clone for Tile<T, O> {
 }
 
 impl<T, O: TileOptional<T>> Tile<T, O> {
-    pub fn new_from_lines<'a, I: Iterator<Item = &'a str>>(lines: I) -> Result<Self, String> {
+    pub fn new(input: &str) -> Result<Self, Error> {
         use TileCell::*;
 
-        let image = lines
-            .map(|line| {
-                line.chars()
-                    .map(|c| match c {
-                        '.' => Ok(Empty),
-                        '#' => Ok(On),
-                        _ => Err(format!("invalid cell tile: {}", c)),
-                    })
-                    .collect::<Result<Vec<_>, _>>()
-            })
-            .collect::<Result<Vec<_>, _>>()?;
-
-        if image.is_empty() {
-            return Err("invalid tile: width zero".to_string());
+        let image = Grid::parse(input, "expecting '.' or '#'", |c| match c {
+            '.' => Some(Empty),
+            '#' => Some(On),
+            _ => None,
+        })?;
+        if image.width() != image.height() {
+            return Err(Error::at(input, input, "invalid tile: not square"));
         }
-        if image.len() != image[0].len() {
-            return Err(format!(
-                "invalid tile: width {} != height {}",
-                image.len(),
-                image[0].len()
-            ));
-        }
-
-        let (edge_set, edge_vec) = O::calc_signs(&image);
 
-        Ok(Self {
-            image,
-            edge_set,
-            edge_vec,
-            _t: std::marker::PhantomData,
-        })
+        Ok(Self::new_from_image(image))
     }
 
-    pub fn new_from_image(image: Vec<Vec<TileCell>>) -> Self {
+    pub fn new_from_image(image: Grid<TileCell>) -> Self {
         let (edge_set, edge_vec) = O::calc_signs(&image);
 
         Self {
@@ -181,96 +176,42 @@ impl<T, O: TileOptional<T>> Tile<T, O> {
         }
     }
 
-    pub fn rotate(&mut self, angle: isize) -> &mut Self {
-        let angle = ((angle + 4) % 4) as usize;
-        if angle == 0 {
-            return self;
-        }
-
-        let edge = self.image.len();
-        let middle = edge as isize / 2;
-        let (dx, dy) = if edge % 2 == 0 {
-            (DELTA[angle][0], DELTA[angle][1])
-        } else {
-            (0, 0)
-        };
-
-        let matrix = ROTATIONS[angle];
-        let rot = |(x, y)| {
-            let (x, y) = (x as isize - middle, y as isize - middle);
-            let (x, y) = (
-                x * matrix[0][0] + y * matrix[0][1],
-                x * matrix[1][0] + y * matrix[1][1],
-            );
-            ((x + middle + dx) as usize, (y + middle + dy) as usize)
-        };
-
-        let image = (0..edge)
-            .map(|y| {
-                (0..edge)
-                    .map(|x| {
-                        let (tx, ty) = rot((x, y));
-                        self.image[ty][tx]
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>();
-
-        let (edge_set, edge_vec) = O::calc_signs(&image);
+    fn update_signs(&mut self) -> &mut Self {
+        let (edge_set, edge_vec) = O::calc_signs(&self.image);
 
-        self.image = image;
         self.edge_set = edge_set;
         self.edge_vec = edge_vec;
 
         self
     }
 
-    pub fn flip_h(&mut self) -> &mut Self {
-        for row in self.image.iter_mut() {
-            row.reverse();
+    /// Rotates the tile `angle` quarter turns counterclockwise.
+    pub fn rotate(&mut self, angle: isize) -> &mut Self {
+        if angle % 4 == 0 {
+            return self;
         }
 
-        let (edge_set, edge_vec) = O::calc_signs(&self.image);
-
-        self.edge_set = edge_set;
-        self.edge_vec = edge_vec;
+        self.image.rotate(angle);
+        self.update_signs()
+    }
 
-        self
+    pub fn flip_h(&mut self) -> &mut Self {
+        self.image.flip_h();
+        self.update_signs()
     }
 
     pub fn flip_v(&mut self) -> &mut Self {
-        self.image.reverse();
-
-        let (edge_set, edge_vec) = O::calc_signs(&self.image);
-
-        self.edge_set = edge_set;
-        self.edge_vec = edge_vec;
-
-        self
+        self.image.flip_v();
+        self.update_signs()
     }
 
-    #[allow(dead_code)]
-    fn get_image(&self) -> String {
-        use TileCell::*;
-
-        let r = self
-            .image
-            .iter()
-            .map(|row| {
-                row.iter()
-                    .map(|c| match c {
-                        On => '#',
-                        Empty => '.',
-                    })
-                    .collect::<String>()
-            })
-            .collect::<Vec<_>>();
-        r.join("\n")
+    pub fn get_image(&self) -> String {
+        self.image.render(|&c| c.to_char())
     }
 
     pub fn get_mask(&self) -> Vec<u128> {
         self.image
-            .iter()
+            .rows()
             .map(|row| {
                 row.iter().fold(0, |v, c| match c {
                     TileCell::On => (v << 1) | 1,
@@ -295,51 +236,50 @@ impl<T: Eq + std::hash::Hash, O: TileOptional<T>> Tile<T, O> {
 
 impl<T: std::fmt::Debug, O: TileOptional<T>> std::fmt::Debug for Tile<T, O> {
     fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
-        use TileCell::*;
-
-        for (s, v) in vec!["top", "right", "bottom", "left"]
+        for (s, v) in ["top", "right", "bottom", "left"]
             .iter()
             .zip(self.edge_vec.iter())
         {
             fmt.write_fmt(format_args!("{}: {:?}\n", s, v))?;
         }
 
-        for row in &self.image {
-            for t in row {
-                fmt.write_str(match t {
-                    On => "#",
-                    Empty => ".",
-                })?;
-            }
-            fmt.write_str("\n")?;
-        }
-
-        Ok(())
+        writeln!(fmt, "{}", self.image.render(|&c| c.to_char()))
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TileCell {
     Empty,
     On,
 }
 
+impl TileCell {
+    fn to_char(self) -> char {
+        match self {
+            TileCell::Empty => '.',
+            TileCell::On => '#',
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     lazy_static! {
-        static ref INPUT: &'static Tile<u32, TileOptionalU32> =
-            crate::INPUT.values().next().unwrap();
+        static ref TILES: Tiles<u32> = include_str!("../input")
+            .trim()
+            .parse()
+            .expect("invalid input");
+        static ref INPUT: &'static Tile<u32, TileOptionalU32> = TILES.values().next().unwrap();
     }
 
     #[test]
     fn test_rotate_3_90() {
-        let mut tile: Tile<u32> = Tile::new_from_lines(
+        let mut tile: Tile<u32> = Tile::new(
             r"#.#
 .#.
-..#"
-            .lines(),
+..#",
         )
         .expect("invalid input");
 
@@ -359,7 +299,7 @@ mod tests {
 .#.
 ..#";
 
-        let mut tile: Tile<u32> = Tile::new_from_lines(image.lines()).expect("invalid input");
+        let mut tile: Tile<u32> = Tile::new(image).expect("invalid input");
 
         tile.rotate(0);
 
@@ -372,7 +312,7 @@ mod tests {
 .#.
 ..#";
 
-        let mut tile: Tile<u32> = Tile::new_from_lines(image.lines()).expect("invalid input");
+        let mut tile: Tile<u32> = Tile::new(image).expect("invalid input");
         tile.rotate(2);
 
         assert_eq!(tile.get_image(), image);
@@ -380,10 +320,9 @@ mod tests {
 
     #[test]
     fn test_rotate_2_90() {
-        let mut tile: Tile<u32> = Tile::new_from_lines(
+        let mut tile: Tile<u32> = Tile::new(
             r"#.
-.#"
-            .lines(),
+.#",
         )
         .expect("invalid input");
 
@@ -401,7 +340,7 @@ mod tests {
         let image = r"#.
 .#";
 
-        let mut tile: Tile<u32> = Tile::new_from_lines(image.lines()).expect("invalid input");
+        let mut tile: Tile<u32> = Tile::new(image).expect("invalid input");
 
         tile.rotate(0);
 
@@ -413,7 +352,7 @@ mod tests {
         let image = r"#.
 .#";
 
-        let mut tile: Tile<u32> = Tile::new_from_lines(image.lines()).expect("invalid input");
+        let mut tile: Tile<u32> = Tile::new(image).expect("invalid input");
 
         tile.rotate(2);
 
@@ -425,7 +364,7 @@ mod tests {
         let image = r"##
 .#";
 
-        let mut tile: Tile<u32> = Tile::new_from_lines(image.lines()).expect("invalid input");
+        let mut tile: Tile<u32> = Tile::new(image).expect("invalid input");
 
         tile.flip_h();
 
@@ -441,7 +380,7 @@ mod tests {
         let image = r"##
 .#";
 
-        let mut tile: Tile<u32> = Tile::new_from_lines(image.lines()).expect("invalid input");
+        let mut tile: Tile<u32> = Tile::new(image).expect("invalid input");
 
         tile.flip_v();
 
@@ -0,0 +1,151 @@
+use common::Rng;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::RangeInclusive;
+
+const ALLERGENS: [&str; 8] = [
+    "dairy", "eggs", "fish", "nuts", "peanuts", "sesame", "soy", "wheat",
+];
+
+/// The sizes `generate` supports: five foods for each allergen.
+pub const SIZES: RangeInclusive<usize> = 5..=usize::MAX;
+
+/// `size` foods (at least 5), with an allergen for every five of them, up
+/// to the eight of the puzzle inputs, and 25 ingredients per allergen.
+///
+/// Each food lists one to three allergens, and contains their ingredients,
+/// half of the other ingredients with an allergen and a third of the safe
+/// ones. The foods are drawn again until the allergens can be told apart
+/// one at a time, each time by an ingredient that is the only candidate
+/// for an allergen.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(*SIZES.start());
+    let allergens = (size / 5).clamp(1, ALLERGENS.len());
+
+    let mut ingredients = BTreeSet::new();
+    while ingredients.len() < 25 * allergens {
+        let length = rng.range(4, 8);
+        ingredients.insert(
+            (0..length)
+                .map(|_| (b'a' + rng.below(26) as u8) as char)
+                .collect::<String>(),
+        );
+    }
+    let mut ingredients = ingredients.into_iter().collect::<Vec<_>>();
+    rng.shuffle(&mut ingredients);
+
+    // the ingredient of each allergen is the one at the same index
+    let mut names = ALLERGENS.to_vec();
+    rng.shuffle(&mut names);
+    let names = &names[..allergens];
+
+    loop {
+        let foods = (0..size)
+            .map(|_| {
+                let mut listed = (0..allergens).collect::<Vec<_>>();
+                rng.shuffle(&mut listed);
+                listed.truncate(rng.range(1, 3.min(allergens as u64)) as usize);
+                listed.sort_unstable();
+
+                let mut contained = (0..ingredients.len())
+                    .filter(|&i| {
+                        listed.contains(&i)
+                            || rng.chance(if i < allergens { 0.5 } else { 1.0 / 3.0 })
+                    })
+                    .collect::<Vec<_>>();
+                rng.shuffle(&mut contained);
+
+                (contained, listed)
+            })
+            .collect::<Vec<_>>();
+
+        if resolve(&foods, allergens).is_some() {
+            return foods
+                .iter()
+                .map(|(contained, listed)| {
+                    format!(
+                        "{} (contains {})\n",
+                        contained
+                            .iter()
+                            .map(|&i| ingredients[i].as_str())
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                        listed
+                            .iter()
+                            .map(|&a| names[a])
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                })
+                .collect();
+        }
+    }
+}
+
+/// The ingredient of each allergen, if they can be told apart by the
+/// elimination described above.
+pub(crate) fn resolve<T: Ord + Copy>(
+    foods: &[(Vec<T>, Vec<T>)],
+    allergens: usize,
+) -> Option<BTreeMap<T, T>> {
+    let mut candidates = BTreeMap::<T, BTreeSet<T>>::new();
+    for (contained, listed) in foods {
+        let contained = contained.iter().copied().collect::<BTreeSet<_>>();
+        for allergen in listed {
+            let set = candidates
+                .entry(*allergen)
+                .or_insert_with(|| contained.clone());
+            *set = set.intersection(&contained).copied().collect();
+        }
+    }
+    if candidates.len() != allergens {
+        return None;
+    }
+
+    let mut resolved = BTreeMap::new();
+    while let Some((&allergen, set)) = candidates.iter().find(|(_, set)| set.len() == 1) {
+        let ingredient = *set.iter().next().unwrap();
+        resolved.insert(allergen, ingredient);
+        candidates.remove(&allergen);
+        candidates.values_mut().for_each(|set| {
+            set.remove(&ingredient);
+        });
+    }
+
+    if candidates.is_empty() {
+        Some(resolved)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_results() {
+        for (seed, size) in (0..6).zip([5, 10, 20].iter().cycle()) {
+            let input = generate(&mut Rng::new(seed), *size);
+            let foods = input
+                .lines()
+                .map(|line| {
+                    let (ingredients, allergens) = line.split_once(" (contains ").unwrap();
+                    (
+                        ingredients.split(' ').collect::<Vec<_>>(),
+                        allergens.trim_end_matches(')').split(", ").collect(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            let resolved = resolve(&foods, (size / 5).min(ALLERGENS.len())).unwrap();
+            let safe = foods
+                .iter()
+                .flat_map(|(ingredients, _)| ingredients)
+                .filter(|ingredient| !resolved.values().any(|i| i == *ingredient))
+                .count();
+            let dangerous = resolved.values().copied().collect::<Vec<_>>().join(",");
+
+            assert_eq!(foods.len(), *size);
+            assert_eq!(crate::solve(&input.parse().unwrap()), Ok((safe, dangerous)));
+        }
+    }
+}
@@ -1,93 +1,91 @@
-#![feature(test)]
+#![cfg_attr(feature = "bench", feature(test))]
+
+#[cfg(feature = "bench")]
 extern crate test;
 
+#[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
 
-use std::collections::{HashMap, HashSet};
+use common::{Answer, Error, Solution};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
-lazy_static! {
-    static ref INPUT: Data<String> = include_str!("../input").parse().expect("invalid input");
-}
+mod generate;
 
-#[derive(Clone)]
-struct Data<T>(Vec<(HashSet<T>, HashSet<T>)>);
+pub use generate::{generate, SIZES};
 
-impl<T: Eq + std::hash::Hash + Clone> Data<T> {
-    fn reduce(&mut self) -> Option<HashSet<(T, T)>> {
-        let (mut r_ingredients, mut r_allergens, mut res) =
-            (HashSet::new(), HashSet::new(), HashSet::new());
+/// The embedded puzzle input, parsed on first use.
+#[cfg(feature = "input")]
+fn input() -> &'static Data<String> {
+    static INPUT: std::sync::OnceLock<Data<String>> = std::sync::OnceLock::new();
+
+    INPUT.get_or_init(|| include_str!("../input").parse().expect("invalid input"))
+}
 
+#[derive(Clone, Debug)]
+pub struct Data<T>(Vec<(HashSet<T>, HashSet<T>)>);
+
+impl<T: Ord + std::hash::Hash + Clone> Data<T> {
+    /// The ingredient of each allergen, as (ingredient, allergen) pairs: an
+    /// allergen can only be in the ingredients of every food listing it, and
+    /// once one of them is down to a single candidate, that ingredient is no
+    /// candidate for the others. The error is an allergen left ambiguous.
+    fn resolve(&self) -> Result<Vec<(T, T)>, T> {
+        let mut candidates = BTreeMap::<T, HashSet<T>>::new();
         for (ingredients, allergens) in &self.0 {
-            if ingredients.len() == allergens.len() {
-                r_ingredients = r_ingredients.union(&ingredients).cloned().collect();
-                r_allergens = r_allergens.union(&allergens).cloned().collect();
-
-                if ingredients.len() == 1 {
-                    res.insert((
-                        ingredients.iter().next().unwrap().to_owned(),
-                        allergens.iter().next().unwrap().to_owned(),
-                    ));
-                }
+            for allergen in allergens {
+                let set = candidates
+                    .entry(allergen.clone())
+                    .or_insert_with(|| ingredients.clone());
+                set.retain(|ingredient| ingredients.contains(ingredient));
             }
         }
 
-        if r_ingredients.is_empty() && self.0.len() > 2 {
-            let mut data = Vec::new();
-            for (i, (a_ingredients, a_allergens)) in self.0[0..self.0.len() - 2]
-                .iter()
-                .enumerate()
-                .filter(|(_, (_, a))| !a.is_empty())
-            {
-                for (b_ingredients, b_allergens) in
-                    self.0[i..].iter().filter(|(_, a)| !a.is_empty())
-                {
-                    let allergens = a_allergens
-                        .intersection(b_allergens)
-                        .cloned()
-                        .collect::<HashSet<_>>();
-                    if !allergens.is_empty() {
-                        let ingredients = a_ingredients
-                            .intersection(b_ingredients)
-                            .cloned()
-                            .collect::<HashSet<_>>();
-                        let k = (ingredients, allergens);
-                        if !data.contains(&k) {
-                            data.push(k);
-                        }
-                    }
-                }
-            }
-
-            if let Some(r) = Data(data).reduce() {
-                r.iter().for_each(|(r_i, r_a)| {
-                    r_ingredients.insert(r_i.to_owned());
-                    r_allergens.insert(r_a.to_owned());
-                });
-                res = r;
-            }
+        let mut resolved = Vec::new();
+        while let Some((allergen, set)) = candidates.iter().find(|(_, set)| set.len() == 1) {
+            let (allergen, ingredient) = (allergen.clone(), set.iter().next().unwrap().clone());
+            candidates.remove(&allergen);
+            candidates.values_mut().for_each(|set| {
+                set.remove(&ingredient);
+            });
+            resolved.push((ingredient, allergen));
         }
 
-        if !r_ingredients.is_empty() {
-            self.0 = self
-                .0
-                .iter()
-                .map(|(ingredients, allergens)| {
-                    (
-                        ingredients.difference(&r_ingredients).cloned().collect(),
-                        allergens.difference(&r_allergens).cloned().collect(),
-                    )
-                })
-                .collect();
-            Some(res)
-        } else {
-            None
+        match candidates.into_iter().next() {
+            Some((allergen, _)) => Err(allergen),
+            None => Ok(resolved),
         }
     }
+
+    /// The number of times the ingredients without an allergen appear, and
+    /// the ingredients with one, by allergen.
+    fn solution(&self) -> Result<(usize, Vec<(T, T)>), T> {
+        let resolved = self.resolve()?;
+        let unsafe_ingredients = resolved
+            .iter()
+            .map(|(ingredient, _)| ingredient)
+            .collect::<HashSet<_>>();
+
+        let count = self
+            .0
+            .iter()
+            .flat_map(|(ingredients, _)| ingredients)
+            .filter(|ingredient| !unsafe_ingredients.contains(ingredient))
+            .count();
+
+        Ok((count, resolved))
+    }
+}
+
+fn ambiguous(allergen: &str) -> Error {
+    Error::new(format!(
+        "the ingredient containing {} cannot be told apart",
+        allergen
+    ))
 }
 
 impl std::str::FromStr for Data<String> {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
         Ok(Self(
@@ -95,18 +93,37 @@ impl std::str::FromStr for Data<String> {
                 .lines()
                 .map(|line| {
                     let mut parts = line.split(" (contains ");
-                    match (parts.next(), parts.next()) {
-                        (Some(ingredients), Some(allergens)) => Ok((
-                            ingredients
-                                .split_ascii_whitespace()
-                                .map(str::to_string)
-                                .collect(),
-                            allergens[0..allergens.len() - 1]
+                    match (parts.next(), parts.next().map(|a| a.strip_suffix(')'))) {
+                        (Some(ingredients), Some(Some(allergens))) => {
+                            if ingredients.trim().is_empty() {
+                                return Err(Error::at(input, line, "expecting ingredients"));
+                            }
+                            let allergens = allergens
                                 .split(", ")
-                                .map(str::to_string)
-                                .collect(),
+                                .map(|allergen| {
+                                    if allergen.is_empty()
+                                        || allergen.contains(|c: char| c.is_whitespace())
+                                    {
+                                        Err(Error::at(input, allergen, "invalid allergen"))
+                                    } else {
+                                        Ok(allergen.to_string())
+                                    }
+                                })
+                                .collect::<Result<_, _>>()?;
+
+                            Ok((
+                                ingredients
+                                    .split_ascii_whitespace()
+                                    .map(str::to_string)
+                                    .collect(),
+                                allergens,
+                            ))
+                        }
+                        _ => Err(Error::at(
+                            input,
+                            line,
+                            "expecting `<ingredients> (contains <allergens>)`",
                         )),
-                        _ => Err(format!("invalid line: {}", line)),
                     }
                 })
                 .collect::<Result<_, _>>()?,
@@ -123,28 +140,23 @@ impl<T> std::ops::Deref for Data<T> {
 }
 
 #[allow(dead_code)]
-fn solve_string(input: &Data<String>) -> (usize, String) {
-    let mut input = (*input).clone();
-
-    let mut v = Vec::new();
-    while let Some(set) = input.reduce() {
-        set.iter().cloned().for_each(|s| v.push(s));
-    }
+fn solve_string(input: &Data<String>) -> Result<(usize, String), Error> {
+    let (count, mut v) = input.solution().map_err(|allergen| ambiguous(&allergen))?;
 
     v.sort_by_key(|(_, a)| a.to_owned());
 
-    (
-        input.iter().map(|(i, _)| i).flatten().count(),
+    Ok((
+        count,
         v.iter()
             .map(|(i, _)| i)
             .cloned()
             .collect::<Vec<_>>()
             .join(","),
-    )
+    ))
 }
 
-fn solve(input: &Data<String>) -> (usize, String) {
-    let (mut input, id2string) = {
+fn solve(input: &Data<String>) -> Result<(usize, String), Error> {
+    let (input, id2string) = {
         let mut string2id = HashMap::new();
         let mut id = 0;
         (
@@ -185,34 +197,75 @@ fn solve(input: &Data<String>) -> (usize, String) {
         )
     };
 
-    let mut v = Vec::new();
-    while let Some(set) = input.reduce() {
-        set.iter().cloned().for_each(|s| v.push(s));
-    }
+    let (count, v) = input
+        .solution()
+        .map_err(|allergen| ambiguous(&id2string[&allergen]))?;
 
     let mut v = v
         .iter()
-        .map(|(i, a)| (id2string[&i].to_owned(), id2string[&a].to_owned()))
+        .map(|(i, a)| (id2string[i].to_owned(), id2string[a].to_owned()))
         .collect::<Vec<_>>();
     v.sort_by_key(|(_, a)| a.to_owned());
 
-    (
-        input.iter().map(|(i, _)| i).flatten().count(),
+    Ok((
+        count,
         v.iter()
             .map(|(i, _)| i)
             .cloned()
             .collect::<Vec<_>>()
             .join(","),
-    )
+    ))
 }
 
+#[cfg(feature = "input")]
 pub fn part() -> (usize, String) {
-    solve(&INPUT)
+    solve(input()).expect("invalid input")
+}
+
+pub fn solve_part(input: &str) -> Result<(usize, String), Error> {
+    input
+        .parse()
+        .and_then(|input| solve(&input))
+        .map_err(|e: Error| e.in_day(Day21::DAY))
+}
+
+pub fn solve_part_1(input: &str) -> Result<usize, Error> {
+    solve_part(input).map(|(count, _)| count)
+}
+
+pub fn solve_part_2(input: &str) -> Result<String, Error> {
+    solve_part(input).map(|(_, list)| list)
+}
+
+pub struct Day21;
+
+impl Solution for Day21 {
+    const DAY: u32 = 21;
+
+    type Input<'a> = Data<String>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+        input.parse().map_err(|e: Error| e.in_day(Self::DAY))
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+        solve(input)
+            .map(|(count, _)| count.into())
+            .map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+        solve(input)
+            .map(|(_, list)| Some(list.into()))
+            .map_err(|e| e.in_day(Self::DAY))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     lazy_static! {
@@ -226,24 +279,124 @@ sqjhc mxmxvkd sbzzf (contains fish)"
 
     #[test]
     fn same_results_part_1() {
-        assert_eq!(solve(&INPUT).0, 5);
+        assert_eq!(solve(&INPUT).map(|(count, _)| count), Ok(5));
     }
 
     #[test]
     fn same_results_part_2() {
-        assert_eq!(solve(&INPUT).1, "mxmxvkd,sqjhc,fvjkl");
+        assert_eq!(
+            solve(&INPUT).map(|(_, list)| list),
+            Ok("mxmxvkd,sqjhc,fvjkl".to_string())
+        );
+    }
+
+    #[test]
+    fn invalid_food() {
+        let error = "mxmxvkd kfcds (contains dairy, fish)\nsqjhc fvjkl (contains soy"
+            .parse::<Data<String>>()
+            .map(drop)
+            .expect_err("invalid food");
+
+        assert_eq!(error.position, Some((2, 1)));
+
+        let error = "a b (contains x, )"
+            .parse::<Data<String>>()
+            .map(drop)
+            .expect_err("empty allergen");
+        assert_eq!(error.position, Some((1, 18)));
+        assert!("a (contains )".parse::<Data<String>>().is_err());
+        assert!(" (contains x)".parse::<Data<String>>().is_err());
+    }
+
+    #[test]
+    fn ambiguous_allergen() {
+        let data = "a (contains x)\nb (contains x)"
+            .parse::<Data<String>>()
+            .expect("invalid input");
+        assert!(solve(&data).is_err());
+        assert!(solve_string(&data).is_err());
+
+        // an edited puzzle input, which used to take exponential time
+        let input = include_str!("../input").replacen(" (contains", " extra (contains", 1);
+        let data = input.parse::<Data<String>>().expect("invalid input");
+        assert_eq!(solve(&data).is_ok(), solve_string(&data).is_ok());
+    }
+
+    /// Foods with up to three allergens, each one in its own ingredient,
+    /// listed or not, and up to six safe ingredients. Like the puzzle, they
+    /// tell apart the allergens: both solvers fail otherwise.
+    fn data() -> impl Strategy<Value = Data<String>> {
+        (1..=3usize)
+            .prop_flat_map(|allergens| {
+                prop::collection::vec(
+                    (
+                        prop::collection::vec(any::<bool>(), allergens),
+                        prop::collection::vec(any::<bool>(), allergens + 6),
+                    ),
+                    1..=6,
+                )
+            })
+            .prop_map(|foods| {
+                Data(
+                    foods
+                        .into_iter()
+                        .filter(|(listed, _)| listed.contains(&true))
+                        .map(|(listed, contained)| {
+                            (
+                                (0..contained.len())
+                                    .filter(|&i| {
+                                        contained[i] || listed.get(i).copied().unwrap_or(false)
+                                    })
+                                    .map(|i| format!("i{}", i))
+                                    .collect(),
+                                (0..listed.len())
+                                    .filter(|&a| listed[a])
+                                    .map(|a| format!("a{}", a))
+                                    .collect(),
+                            )
+                        })
+                        .collect(),
+                )
+            })
+            .prop_filter("allergens cannot be told apart", |data| {
+                let foods = data
+                    .iter()
+                    .map(|(ingredients, allergens)| {
+                        (
+                            ingredients.iter().collect::<Vec<_>>(),
+                            allergens.iter().collect::<Vec<_>>(),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                let allergens = data
+                    .iter()
+                    .flat_map(|(_, allergens)| allergens)
+                    .collect::<HashSet<_>>();
+
+                generate::resolve(&foods, allergens.len()).is_some()
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn same_results_random_data(data in data()) {
+            prop_assert_eq!(solve(&data), solve_string(&data));
+        }
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_test_solve(b: &mut Bencher) {
         b.iter(|| solve(&INPUT));
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_test_solve_string(b: &mut Bencher) {
         b.iter(|| solve_string(&INPUT));
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     #[ignore]
     fn bench_solve(b: &mut Bencher) {
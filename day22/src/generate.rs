@@ -0,0 +1,72 @@
+use common::Rng;
+use std::collections::{HashSet, VecDeque};
+
+/// The cards from 1 to `2 * size` (at least 2), shuffled and dealt to
+/// the two players.
+///
+/// A regular game of combat can go on forever, and its solver would too:
+/// the decks are shuffled again until it ends.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(1);
+    let mut cards = (1..=2 * size as u32).collect::<Vec<_>>();
+
+    loop {
+        rng.shuffle(&mut cards);
+        let (player_1, player_2) = cards.split_at(size);
+
+        if ends(player_1, player_2) {
+            let deck = |cards: &[u32]| {
+                cards
+                    .iter()
+                    .map(|card| format!("{}\n", card))
+                    .collect::<String>()
+            };
+
+            return format!(
+                "Player 1:\n{}\nPlayer 2:\n{}",
+                deck(player_1),
+                deck(player_2)
+            );
+        }
+    }
+}
+
+fn ends(player_1: &[u32], player_2: &[u32]) -> bool {
+    let mut player_1 = player_1.iter().copied().collect::<VecDeque<_>>();
+    let mut player_2 = player_2.iter().copied().collect::<VecDeque<_>>();
+    let mut history = HashSet::new();
+
+    while !player_1.is_empty() && !player_2.is_empty() {
+        if !history.insert((player_1.clone(), player_2.clone())) {
+            return false;
+        }
+
+        let (card_1, card_2) = (player_1.pop_front().unwrap(), player_2.pop_front().unwrap());
+        if card_1 > card_2 {
+            player_1.extend([card_1, card_2]);
+        } else {
+            player_2.extend([card_2, card_1]);
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+
+    #[test]
+    fn valid_input() {
+        for (seed, size) in (0..10).zip([1, 5, 10].iter().cycle()) {
+            let data = generate(&mut Rng::new(seed), *size)
+                .parse::<Data>()
+                .unwrap();
+
+            assert_eq!((data.0.len(), data.1.len()), (*size, *size));
+            assert!(crate::solve_1(&data).is_ok());
+            assert!(crate::solve_2(&data).is_ok());
+        }
+    }
+}
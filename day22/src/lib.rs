@@ -1,38 +1,66 @@
-#![feature(test)]
+#![cfg_attr(feature = "bench", feature(test))]
+
+#[cfg(feature = "bench")]
 extern crate test;
 
+#[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
 
+use common::{Answer, Error, Solution};
 use std::collections::{HashSet, VecDeque};
 
-lazy_static! {
-    static ref INPUT: Data = include_str!("../input").parse().expect("invalid input");
+mod generate;
+
+pub use generate::generate;
+
+/// The embedded puzzle input, parsed on first use.
+#[cfg(feature = "input")]
+fn input() -> &'static Data {
+    static INPUT: std::sync::OnceLock<Data> = std::sync::OnceLock::new();
+
+    INPUT.get_or_init(|| include_str!("../input").parse().expect("invalid input"))
 }
 
-struct Data(Vec<u32>, Vec<u32>);
+pub struct Data(Vec<u32>, Vec<u32>);
 
 impl std::str::FromStr for Data {
-    type Err = String;
+    type Err = Error;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let mut parts = input.split("\n\n");
-        let mut parse = || {
-            parts
+        let mut parts = input.trim_end().split("\n\n");
+        let mut cards = HashSet::new();
+        let mut parse = |header| {
+            let part = parts
                 .next()
-                .ok_or_else(|| "expecting player".to_string())
-                .and_then(|part| {
-                    part.lines()
-                        .skip(1)
-                        .map(|line| {
-                            line.parse()
-                                .map_err(|_| format!("invalid number: {}", line))
-                        })
-                        .collect()
+                .ok_or_else(|| Error::new("expecting two players"))?;
+            let mut lines = part.lines();
+            match lines.next() {
+                Some(line) if line == header => {}
+                line => {
+                    let line = line.unwrap_or(part);
+                    return Err(Error::at(input, line, format!("expecting `{}`", header)));
+                }
+            }
+
+            lines
+                .map(|line| match line.parse() {
+                    Ok(card) if cards.insert(card) => Ok(card),
+                    Ok(_) => Err(Error::at(input, line, "duplicate card")),
+                    Err(_) => Err(Error::at(input, line, "invalid number")),
                 })
+                .collect::<Result<Vec<_>, _>>()
         };
 
-        Ok(Data(parse()?, parse()?))
+        let data = Data(parse("Player 1:")?, parse("Player 2:")?);
+        if let Some(part) = parts.next() {
+            return Err(Error::at(input, part, "expecting only two players"));
+        }
+        if data.0.is_empty() && data.1.is_empty() {
+            return Err(Error::new("both decks are empty"));
+        }
+
+        Ok(data)
     }
 }
 
@@ -56,14 +84,14 @@ impl Game {
         }
     }
 
-    fn play(&mut self) -> (Winner, &mut [u32]) {
+    fn play(&mut self) -> Result<(Winner, &mut [u32]), Error> {
         use Winner::*;
 
         loop {
             {
                 let k = (self.player_1.to_owned(), self.player_2.to_owned());
                 if self.history.contains(&k) {
-                    break (Player1, self.player_1.make_contiguous());
+                    break Ok((Player1, self.player_1.make_contiguous()));
                 } else {
                     self.history.insert(k);
                 }
@@ -78,7 +106,7 @@ impl Game {
                         &self.player_1.make_contiguous()[0..card_1 as usize],
                         &self.player_2.make_contiguous()[0..card_2 as usize],
                     )
-                    .play()
+                    .play()?
                     {
                         (Player1, _) => {
                             self.player_1.push_back(card_1);
@@ -100,25 +128,27 @@ impl Game {
                 }
                 (Some(card), None) => {
                     self.player_1.push_front(card);
-                    break (Player1, self.player_1.make_contiguous());
+                    break Ok((Player1, self.player_1.make_contiguous()));
                 }
                 (None, Some(card)) => {
                     self.player_2.push_front(card);
-                    break (Player2, self.player_2.make_contiguous());
+                    break Ok((Player2, self.player_2.make_contiguous()));
                 }
-                u => unreachable!(format!(
-                    "c: {:?} p1: {:?} p2: {:?}",
-                    u, self.player_1, self.player_2
-                )),
+                _ => break Err(Error::new("both players drew the same card")),
             }
         }
     }
 }
 
-fn solve_1(input: &Data) -> usize {
+fn solve_1(input: &Data) -> Result<usize, Error> {
     let mut player_1 = input.0.iter().copied().collect::<VecDeque<_>>();
     let mut player_2 = input.1.iter().copied().collect::<VecDeque<_>>();
+    let mut history = HashSet::new();
     loop {
+        if !history.insert((player_1.clone(), player_2.clone())) {
+            return Err(Error::new("the decks repeat, the game never ends"));
+        }
+
         match (player_1.pop_front(), player_2.pop_front()) {
             (Some(card_1), Some(card_2)) if card_1 > card_2 => {
                 player_1.push_back(card_1);
@@ -136,7 +166,7 @@ fn solve_1(input: &Data) -> usize {
                 player_2.push_front(card);
                 break;
             }
-            _ => unreachable!(),
+            _ => return Err(Error::new("both players drew the same card")),
         }
     }
 
@@ -148,38 +178,79 @@ fn solve_1(input: &Data) -> usize {
 
     winner.make_contiguous().reverse();
 
-    winner
+    Ok(winner
         .iter()
         .enumerate()
         .map(|(i, v)| *v as usize * (i + 1))
-        .sum()
+        .sum())
 }
 
-fn solve_2(input: &Data) -> usize {
+fn solve_2(input: &Data) -> Result<usize, Error> {
     let mut game = Game::new(&input.0, &input.1);
 
-    let (_, cards) = game.play();
+    let (_, cards) = game.play()?;
 
     cards.reverse();
 
-    cards
+    Ok(cards
         .iter()
         .enumerate()
         .map(|(i, v)| *v as usize * (i + 1))
-        .sum()
+        .sum())
 }
 
+#[cfg(feature = "input")]
 pub fn part_1() -> usize {
-    solve_1(&INPUT)
+    solve_1(input()).expect("invalid input")
 }
 
+#[cfg(feature = "input")]
 pub fn part_2() -> usize {
-    solve_2(&INPUT)
+    solve_2(input()).expect("invalid input")
+}
+
+pub fn solve_part_1(input: &str) -> Result<usize, Error> {
+    input
+        .parse::<Data>()
+        .and_then(|input| solve_1(&input))
+        .map_err(|e| e.in_day(Day22::DAY))
+}
+
+pub fn solve_part_2(input: &str) -> Result<usize, Error> {
+    input
+        .parse::<Data>()
+        .and_then(|input| solve_2(&input))
+        .map_err(|e| e.in_day(Day22::DAY))
+}
+
+pub struct Day22;
+
+impl Solution for Day22 {
+    const DAY: u32 = 22;
+
+    type Input<'a> = Data;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+        input.parse::<Data>().map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+        solve_1(input)
+            .map(Answer::from)
+            .map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+        solve_2(input)
+            .map(|answer| Some(answer.into()))
+            .map_err(|e| e.in_day(Self::DAY))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     lazy_static! {
@@ -202,19 +273,57 @@ Player 2:
 
     #[test]
     fn same_results_part_1() {
-        assert_eq!(solve_1(&INPUT), 306);
+        assert_eq!(solve_1(&INPUT), Ok(306));
     }
 
     #[test]
     fn same_results_part_2() {
-        assert_eq!(solve_2(&INPUT), 291);
+        assert_eq!(solve_2(&INPUT), Ok(291));
+    }
+
+    #[test]
+    fn endless_game() {
+        let data = "Player 1:\n43\n19\n\nPlayer 2:\n2\n29\n14"
+            .parse::<Data>()
+            .expect("invalid input");
+
+        assert!(solve_1(&data).is_err());
+        assert!(solve_2(&data).is_ok());
+    }
+
+    #[test]
+    fn invalid_sections() {
+        let error = |input: &str| input.parse::<Data>().map(drop).unwrap_err();
+
+        let e = error("Player 1:\n9\n\n2\n\nPlayer 2:\n5\n");
+        assert_eq!((e.position, e.snippet.as_str()), (Some((4, 1)), "2"));
+        let e = error("Player 1:\n9\n\nPlayer 2:\n5\n\nPlayer 3:\n7\n");
+        assert_eq!(
+            (e.position, e.message.as_str()),
+            (Some((7, 1)), "expecting only two players")
+        );
+        let e = error("Player 2:\n9\n\nPlayer 1:\n5");
+        assert_eq!(e.message, "expecting `Player 1:`");
+        assert!("Player 1:\n9\n\nPlayer 2:\n5\n\n".parse::<Data>().is_ok());
+    }
+
+    #[test]
+    fn duplicate_card() {
+        let error = "Player 1:\n9\n2\n\nPlayer 2:\n5\n2"
+            .parse::<Data>()
+            .map(drop)
+            .expect_err("duplicate card");
+
+        assert_eq!(error.position, Some((7, 1)));
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_1(b: &mut Bencher) {
         b.iter(part_1);
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_2(b: &mut Bencher) {
         b.iter(part_2);
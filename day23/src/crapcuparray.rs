@@ -1,5 +1,11 @@
 use super::*;
 
+pub trait CrapCups {
+    type I: Iterator;
+
+    fn crap_cups(&self) -> Self::I;
+}
+
 macro_rules! crapcupsarray {
     [$i:tt] => {
 pub struct CrapCupsIter {
@@ -10,7 +16,7 @@ pub struct CrapCupsIter {
 
 impl CrapCups for [u32; $i] {
     type I = CrapCupsIter;
-    
+
     fn crap_cups(&self) -> Self::I {
         CrapCupsIter {
             labels: self.to_owned(),
@@ -96,14 +102,22 @@ impl Iterator for CrapCupsIter {
 
 pub struct CrapCupsValue([u32; $i]);
 
+impl CrapCupsValue {
+    pub fn labels(&self) -> &[u32] {
+        &self.0
+    }
+}
+
 impl std::fmt::Debug for CrapCupsValue {
     fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
         fmt.write_fmt(format_args!("{:?}: {}", self.0, &self.to_string()))
     }
 }
 
-impl ToString for CrapCupsValue {
-    fn to_string(&self) -> String {
+impl std::fmt::Display for CrapCupsValue {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use std::fmt::Write;
+
         let index = self
             .0
             .iter()
@@ -112,7 +126,7 @@ impl ToString for CrapCupsValue {
             .unwrap();
         (1..$i)
             .map(|i| (self.0[(i + index) % self.0.len()] + '0' as u32) as u8 as char)
-            .collect()
+            .try_for_each(|c| fmt.write_char(c))
     }
 }
     }
@@ -120,14 +134,14 @@ impl ToString for CrapCupsValue {
 
 mod crapcupsarray9 {
     use super::*;
-    
+
     crapcupsarray![9];
 }
 
 #[allow(non_snake_case)]
 mod crapcupsarray1M {
     use super::*;
-    
+
     crapcupsarray![10_000];
 
     impl CrapCups for Vec<u32> {
@@ -136,8 +150,10 @@ mod crapcupsarray1M {
         fn crap_cups(&self) -> Self::I {
             let mut v = [0; 10_000];
 
-            self.iter().enumerate().for_each(|(i, e)| { v[i] = *e; });
-            
+            self.iter().enumerate().for_each(|(i, e)| {
+                v[i] = *e;
+            });
+
             CrapCupsIter {
                 labels: v,
                 current_cup_index: 0,
@@ -150,9 +166,60 @@ mod crapcupsarray1M {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+
+    /// The labels after cup 1, as linked by `solve`.
+    fn after_1(mem: &[usize]) -> Vec<u32> {
+        std::iter::successors(Some(mem[1]), |&cup| Some(mem[cup]))
+            .take_while(|&cup| cup != 1)
+            .map(|cup| cup as u32)
+            .collect()
+    }
+
+    /// The labels after cup 1, from the array of a state.
+    fn after_1_array(labels: &[u32]) -> Vec<u32> {
+        let index = labels.iter().position(|&label| label == 1).unwrap();
+
+        (1..labels.len())
+            .map(|i| labels[(index + i) % labels.len()])
+            .collect()
+    }
+
+    fn labels() -> impl Strategy<Value = Vec<u32>> {
+        Just((1..=9).collect::<Vec<u32>>()).prop_shuffle()
+    }
+
+    proptest! {
+        #[test]
+        fn same_as_solve_9(labels in labels(), moves in 0..200usize) {
+            let mut array = [0; 9];
+            array.copy_from_slice(&labels);
+            let (_, mem) = solve(&labels, 9, moves);
+
+            prop_assert_eq!(
+                after_1_array(array.crap_cups().nth(moves).unwrap().labels()),
+                after_1(&mem)
+            );
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn same_as_solve_10_000(labels in labels(), moves in 0..20usize) {
+            let cups = labels.iter().copied().chain(10..=10_000).collect::<Vec<_>>();
+            let (_, mem) = solve(&labels, 10_000, moves);
+
+            prop_assert_eq!(
+                after_1_array(cups.crap_cups().nth(moves).unwrap().labels()),
+                after_1(&mem)
+            );
+        }
+    }
 
     lazy_static! {
-        static ref EXAMPLE_1: [u32; 9] = parse("389125467");
+        static ref EXAMPLE_1: [u32; 9] = parse("389125467").expect("invalid input");
     }
 
     #[test]
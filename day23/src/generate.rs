@@ -0,0 +1,33 @@
+use common::Rng;
+use std::ops::RangeInclusive;
+
+/// The sizes `generate` supports: the puzzle always has nine cups.
+pub const SIZES: RangeInclusive<usize> = 9..=9;
+
+/// The nine cup labels in random order; there is nothing else to size.
+pub fn generate(rng: &mut Rng, _size: usize) -> String {
+    let mut labels = (1..=9).collect::<Vec<u32>>();
+    rng.shuffle(&mut labels);
+
+    labels
+        .iter()
+        .map(|label| label.to_string())
+        .chain(std::iter::once("\n".to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_input() {
+        for seed in 0..10 {
+            let labels = crate::parse(&generate(&mut Rng::new(seed), 0)).unwrap();
+            let mut after_1 = crate::solve_1(&labels).into_bytes();
+            after_1.sort_unstable();
+
+            assert_eq!(after_1, b"23456789");
+        }
+    }
+}
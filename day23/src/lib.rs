@@ -1,21 +1,51 @@
-#![feature(test)]
+#![cfg_attr(feature = "bench", feature(test))]
+
+#[cfg(feature = "bench")]
 extern crate test;
 
+#[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
 
-lazy_static! {
-    static ref INPUT: [u32; 9] = parse("952438716");
+use common::{Answer, Error, Solution};
+
+/// The game on a fixed-size array, moving the cups around at each move: far
+/// slower than `solve`, kept to check it against.
+#[cfg(test)]
+mod crapcuparray;
+mod generate;
+
+pub use generate::{generate, SIZES};
+
+/// The embedded puzzle input, parsed on first use.
+#[cfg(feature = "input")]
+fn input() -> &'static [u32; 9] {
+    static INPUT: std::sync::OnceLock<[u32; 9]> = std::sync::OnceLock::new();
+
+    INPUT.get_or_init(|| parse(include_str!("../input")).expect("invalid input"))
 }
 
-fn parse(input: &str) -> [u32; 9] {
+fn parse(input: &str) -> Result<[u32; 9], Error> {
     let mut array = [0; 9];
 
-    input.chars().enumerate().for_each(|(i, c)| {
-        array[i] = c as u32 - '0' as u32;
-    });
+    let labels = input.trim();
+    for (i, (j, c)) in labels.char_indices().enumerate() {
+        match c.to_digit(10) {
+            Some(label) if i < 9 && label != 0 && !array.contains(&label) => array[i] = label,
+            _ => {
+                return Err(Error::at(
+                    input,
+                    &labels[j..j + c.len_utf8()],
+                    "expecting each cup label from 1 to 9 once",
+                ))
+            }
+        }
+    }
+    if array.contains(&0) {
+        return Err(Error::at(input, labels, "expecting nine cup labels"));
+    }
 
-    array
+    Ok(array)
 }
 
 fn solve(labels: &[u32], len: usize, i: usize) -> (usize, Vec<usize>) {
@@ -102,21 +132,56 @@ fn solve_2(labels: &[u32; 9]) -> u64 {
     mem[1] as u64 * mem[mem[1]] as u64
 }
 
+#[cfg(feature = "input")]
 pub fn part_1() -> String {
-    solve_1(&INPUT)
+    solve_1(input())
 }
 
+#[cfg(feature = "input")]
 pub fn part_2() -> u64 {
-    solve_2(&INPUT)
+    solve_2(input())
+}
+
+pub fn solve_part_1(input: &str) -> Result<String, Error> {
+    parse(input)
+        .map(|input| solve_1(&input))
+        .map_err(|e| e.in_day(Day23::DAY))
+}
+
+pub fn solve_part_2(input: &str) -> Result<u64, Error> {
+    parse(input)
+        .map(|input| solve_2(&input))
+        .map_err(|e| e.in_day(Day23::DAY))
+}
+
+pub struct Day23;
+
+impl Solution for Day23 {
+    const DAY: u32 = 23;
+
+    type Input<'a> = [u32; 9];
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+        parse(input).map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+        Ok(solve_1(input).into())
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+        Ok(Some(solve_2(input).into()))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     lazy_static! {
-        static ref EXAMPLE_1: [u32; 9] = parse("389125467");
+        static ref EXAMPLE_1: [u32; 9] = parse("389125467").expect("invalid input");
     }
 
     #[test]
@@ -124,6 +189,11 @@ mod tests {
         assert_eq!(solve_1(&EXAMPLE_1), "67384529");
     }
 
+    #[test]
+    fn same_results_solve_part_1() {
+        assert_eq!(solve_part_1("389125467\n"), Ok("67384529".to_string()));
+    }
+
     #[test]
     fn same_results_example_2_1() {
         let (_, mem) = solve(&EXAMPLE_1[..], 1_000_000, 10_000_000);
@@ -137,11 +207,20 @@ mod tests {
         assert_eq!(solve_2(&EXAMPLE_1), 149245887792);
     }
 
+    #[test]
+    fn invalid_labels() {
+        assert_eq!(parse("389125462").unwrap_err().position, Some((1, 9)));
+        assert_eq!(parse("38912546").unwrap_err().position, Some((1, 1)));
+        assert_eq!(parse("3891254670").unwrap_err().position, Some((1, 10)));
+    }
+
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_1(b: &mut Bencher) {
         b.iter(part_1);
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_2(b: &mut Bencher) {
         b.iter(part_2);
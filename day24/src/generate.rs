@@ -0,0 +1,34 @@
+use common::Rng;
+
+const DIRECTIONS: [&str; 6] = ["e", "se", "sw", "w", "nw", "ne"];
+
+/// `size` tiles to flip, each one 15 to 25 steps away from the reference
+/// tile like in the puzzle inputs, so that some of them are flipped more
+/// than once.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let mut line = (0..rng.range(15, 25))
+                .map(|_| *rng.choose(&DIRECTIONS))
+                .collect::<String>();
+            line.push('\n');
+            line
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_input() {
+        for seed in 0..5 {
+            let input = generate(&mut Rng::new(seed), 400);
+            let lines = crate::parse(&input).unwrap();
+
+            assert_eq!(lines.len(), 400);
+            assert!((1..=400).contains(&crate::solve_1(&lines)));
+        }
+    }
+}
@@ -1,21 +1,34 @@
-#![feature(test)]
+#![cfg_attr(feature = "bench", feature(test))]
+
+#[cfg(feature = "bench")]
 extern crate test;
 
+#[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
 
+use common::{Answer, Error, Picture, Solution, Svg, BLACK};
 use std::collections::HashSet;
 
-lazy_static! {
-    static ref INPUT: Vec<&'static str> = include_str!("../input").lines().collect();
-    static ref POSITIONS: Vec<Cube> = vec![
-        Cube(1, 0, -1),
-        Cube(1, -1, 0),
-        Cube(0, -1, 1),
-        Cube(-1, 0, 1),
-        Cube(-1, 1, 0),
-        Cube(0, 1, -1)
-    ];
+mod generate;
+
+pub use generate::generate;
+
+const POSITIONS: [Cube; 6] = [
+    Cube(1, 0, -1),
+    Cube(1, -1, 0),
+    Cube(0, -1, 1),
+    Cube(-1, 0, 1),
+    Cube(-1, 1, 0),
+    Cube(0, 1, -1),
+];
+
+/// The embedded puzzle input, parsed on first use.
+#[cfg(feature = "input")]
+fn input() -> &'static Vec<&'static str> {
+    static INPUT: std::sync::OnceLock<Vec<&'static str>> = std::sync::OnceLock::new();
+
+    INPUT.get_or_init(|| parse(include_str!("../input")).expect("invalid input"))
 }
 
 type Coordinate = i32;
@@ -44,11 +57,11 @@ impl std::iter::Sum for Cube {
 }
 
 trait Walk {
-    fn walk(&self) -> WalkIterator;
+    fn walk(&self) -> WalkIterator<'_>;
 }
 
 impl Walk for &str {
-    fn walk(&self) -> WalkIterator {
+    fn walk(&self) -> WalkIterator<'_> {
         WalkIterator { input: self }
     }
 }
@@ -91,7 +104,27 @@ impl<'a> Iterator for WalkIterator<'a> {
     }
 }
 
-fn solve_1(input: &[&str]) -> usize {
+fn parse(input: &str) -> Result<Vec<&str>, Error> {
+    input
+        .lines()
+        .map(|line| {
+            let mut walk = line.walk();
+            walk.by_ref().for_each(drop);
+
+            match walk.input.chars().next() {
+                None => Ok(line),
+                Some(c) => Err(Error::at(
+                    input,
+                    &walk.input[..c.len_utf8()],
+                    "expecting e, se, sw, w, nw or ne",
+                )),
+            }
+        })
+        .collect()
+}
+
+/// The tiles that end up black after flipping those the lines lead to.
+fn black_tiles(input: &[&str]) -> HashSet<Cube> {
     input
         .iter()
         .map(|line| line.walk().sum::<Cube>())
@@ -103,72 +136,187 @@ fn solve_1(input: &[&str]) -> usize {
             }
             set
         })
-        .len()
 }
 
-fn solve_2(input: &[&str]) -> usize {
-    let mut tiles =
-        input
-            .iter()
-            .map(|line| line.walk().sum::<Cube>())
-            .fold(HashSet::new(), |mut set, c| {
-                if set.contains(&c) {
-                    set.remove(&c);
-                } else {
-                    set.insert(c);
-                }
-                set
-            });
-
-    for _i in 0..100 {
-        let (mut new_tiles, mut tiles_to_check) = (HashSet::new(), HashSet::new());
-        for &c in tiles.iter() {
-            let mut count = 0;
-            for dc in POSITIONS.iter() {
-                let t = c + dc;
-                if tiles.contains(&t) {
-                    count += 1;
-                } else {
-                    tiles_to_check.insert(t.to_owned());
-                }
+/// The black tiles the day after.
+fn flip(tiles: &HashSet<Cube>) -> HashSet<Cube> {
+    let (mut new_tiles, mut tiles_to_check) = (HashSet::new(), HashSet::new());
+    for &c in tiles.iter() {
+        let mut count = 0;
+        for dc in POSITIONS.iter() {
+            let t = c + dc;
+            if tiles.contains(&t) {
+                count += 1;
+            } else {
+                tiles_to_check.insert(t.to_owned());
             }
+        }
+
+        if (1..=2).contains(&count) {
+            new_tiles.insert(c);
+        }
+    }
 
-            if (1..=2).contains(&count) {
-                new_tiles.insert(c);
+    for c in tiles_to_check {
+        let mut count = 0;
+        for dc in POSITIONS.iter() {
+            let t = c + dc;
+            if tiles.contains(&t) {
+                count += 1;
             }
         }
 
-        for c in tiles_to_check {
-            let mut count = 0;
-            for dc in POSITIONS.iter() {
-                let t = c + dc;
-                if tiles.contains(&t) {
-                    count += 1;
+        if count == 2 {
+            new_tiles.insert(c);
+        }
+    }
+
+    new_tiles
+}
+
+/// Draws the floor in rows of offset hexagons, `#` for the black tiles.
+fn render(tiles: &HashSet<Cube>) -> String {
+    // a step east is two columns, one north-east is one column and a row up
+    let position = |&Cube(x, y, z): &Cube| (x - y, z);
+
+    let positions = tiles.iter().map(position).collect::<HashSet<_>>();
+    let bounds = |f: fn(&(Coordinate, Coordinate)) -> Coordinate| {
+        let min = positions.iter().map(f).min().unwrap_or_default();
+        let max = positions.iter().map(f).max().unwrap_or_default();
+        min..=max
+    };
+    let (columns, rows) = (bounds(|p| p.0), bounds(|p| p.1));
+
+    rows.map(|row| {
+        let line = columns
+            .clone()
+            .map(|column| {
+                if (column - row) % 2 != 0 {
+                    ' '
+                } else if positions.contains(&(column, row)) {
+                    '#'
+                } else {
+                    '.'
                 }
-            }
+            })
+            .collect::<String>();
+        format!("{}\n", line.trim_end())
+    })
+    .collect()
+}
 
-            if count == 2 {
-                new_tiles.insert(c);
-            }
+/// Draws the black tiles as hexagons, laid out as `render` does.
+fn picture(tiles: &HashSet<Cube>) -> Svg {
+    let (dx, dy) = (3f64.sqrt() / 2.0, 1.5);
+    let centre = |&Cube(x, y, z): &Cube| ((x - y) as f64 * dx, z as f64 * dy);
+
+    let centres = tiles.iter().map(centre).collect::<Vec<_>>();
+    let bounds = |f: fn(&(f64, f64)) -> f64| {
+        let min = centres.iter().map(f).fold(f64::INFINITY, f64::min);
+        let max = centres.iter().map(f).fold(f64::NEG_INFINITY, f64::max);
+        if min <= max {
+            (min - 1.0, max - min + 2.0)
+        } else {
+            (-1.0, 2.0)
         }
+    };
+    let ((x, width), (y, height)) = (bounds(|c| c.0), bounds(|c| c.1));
 
-        tiles = new_tiles;
+    let mut svg = Svg::new(x, y, width, height);
+    for (cx, cy) in centres {
+        // a little smaller than the tile, so that neighbours stand apart
+        let corners = (0..6)
+            .map(|i| {
+                let angle = std::f64::consts::PI / 3.0 * i as f64 + std::f64::consts::PI / 6.0;
+                (cx + 0.95 * angle.cos(), cy + 0.95 * angle.sin())
+            })
+            .collect::<Vec<_>>();
+        svg.polygon(&corners, BLACK);
     }
 
-    tiles.len()
+    svg
 }
 
+fn solve_1(input: &[&str]) -> usize {
+    black_tiles(input).len()
+}
+
+fn solve_2(input: &[&str]) -> usize {
+    (0..100)
+        .fold(black_tiles(input), |tiles, _| flip(&tiles))
+        .len()
+}
+
+#[cfg(feature = "input")]
 pub fn part_1() -> usize {
-    solve_1(&INPUT)
+    solve_1(input())
 }
 
+#[cfg(feature = "input")]
 pub fn part_2() -> usize {
-    solve_2(&INPUT)
+    solve_2(input())
+}
+
+pub fn solve_part_1(input: &str) -> Result<usize, Error> {
+    parse(input)
+        .map(|input| solve_1(&input))
+        .map_err(|e| e.in_day(Day24::DAY))
+}
+
+pub fn solve_part_2(input: &str) -> Result<usize, Error> {
+    parse(input)
+        .map(|input| solve_2(&input))
+        .map_err(|e| e.in_day(Day24::DAY))
+}
+
+pub struct Day24;
+
+impl Solution for Day24 {
+    const DAY: u32 = 24;
+
+    type Input<'a> = Vec<&'a str>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+        parse(input).map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+        Ok(solve_1(input).into())
+    }
+
+    fn part_2(input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+        Ok(Some(solve_2(input).into()))
+    }
+
+    fn generations(
+        input: &Self::Input<'_>,
+        part: usize,
+    ) -> Option<Box<dyn Iterator<Item = String>>> {
+        if part != 2 {
+            return None;
+        }
+
+        Some(Box::new(
+            std::iter::successors(Some(black_tiles(input)), |tiles| Some(flip(tiles)))
+                .take(101)
+                .map(|tiles| render(&tiles)),
+        ))
+    }
+
+    fn picture(input: &Self::Input<'_>, part: usize) -> Option<Result<Picture, Error>> {
+        let tiles = match part {
+            1 => black_tiles(input),
+            _ => (0..100).fold(black_tiles(input), |tiles, _| flip(&tiles)),
+        };
+
+        Some(Ok(Picture::Vector(picture(&tiles))))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     lazy_static! {
@@ -201,6 +349,34 @@ wseweeenwnesenwwwswnew"
         assert_eq!("nwwswee".walk().sum::<Cube>(), Cube(0, 0, 0));
     }
 
+    #[test]
+    fn render_hex() {
+        let tiles = ["e", "nee", "se"]
+            .iter()
+            .map(|line| line.walk().sum::<Cube>())
+            .collect();
+
+        assert_eq!(render(&tiles), ". #\n #\n# .\n");
+    }
+
+    #[test]
+    fn hexagons() {
+        let svg = picture(&black_tiles(&INPUT)).to_string();
+
+        assert_eq!(svg.matches("<polygon").count(), 10);
+    }
+
+    #[test]
+    fn generations() {
+        let frames = Day24::generations(&INPUT, 2)
+            .expect("no generations")
+            .collect::<Vec<_>>();
+
+        assert_eq!(frames.len(), 101);
+        assert_eq!(frames[0].matches('#').count(), 10);
+        assert_eq!(frames[100].matches('#').count(), 2208);
+    }
+
     #[test]
     fn same_results_example_1() {
         assert_eq!(solve_1(&INPUT), 10);
@@ -211,11 +387,21 @@ wseweeenwnesenwwwswnew"
         assert_eq!(solve_2(&INPUT), 2208);
     }
 
+    #[test]
+    fn invalid_direction() {
+        let error = parse("esenee\nesew\nnwwxnee").unwrap_err();
+
+        assert_eq!(error.position, Some((3, 4)));
+        assert_eq!(error.snippet, "x");
+    }
+
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_1(b: &mut Bencher) {
         b.iter(part_1);
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_2(b: &mut Bencher) {
         b.iter(part_2);
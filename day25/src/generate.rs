@@ -0,0 +1,45 @@
+use crate::{transform, MODULUS, SUBJECT_NUMBER};
+use common::Rng;
+use std::ops::RangeInclusive;
+
+/// The sizes `generate` supports: two distinct loop sizes, below the
+/// modulus.
+pub const SIZES: RangeInclusive<usize> = 2..=MODULUS as usize - 1;
+
+/// The public keys of a card and a door with distinct loop sizes up to
+/// `size` (at least 2); those of the puzzle inputs are in the millions.
+pub fn generate(rng: &mut Rng, size: usize) -> String {
+    let size = size.clamp(*SIZES.start(), *SIZES.end()) as u64;
+
+    let card = rng.range(1, size);
+    let door = loop {
+        let door = rng.range(1, size);
+        if door != card {
+            break door;
+        }
+    };
+
+    format!(
+        "{}\n{}\n",
+        transform(SUBJECT_NUMBER, card as usize),
+        transform(SUBJECT_NUMBER, door as usize)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_encryption_key() {
+        for seed in 0..10 {
+            let keys = crate::parse(&generate(&mut Rng::new(seed), 1000)).unwrap();
+
+            assert!(keys[0] != keys[1]);
+            assert_eq!(
+                crate::solve_1(keys[0], keys[1]),
+                crate::solve_1(keys[1], keys[0])
+            );
+        }
+    }
+}
@@ -1,55 +1,108 @@
-#![feature(test)]
+#![cfg_attr(feature = "bench", feature(test))]
+
+#[cfg(feature = "bench")]
 extern crate test;
 
-lazy_static::lazy_static! {
-    static ref INPUT: Vec<u64> = include_str!("../input")
-        .lines()
-        .map(|line| line
-             .parse()
-             .expect("invalid number"))
-        .collect();
+use common::{num, Answer, Error, Solution};
+
+mod generate;
+
+pub use generate::{generate, SIZES};
+
+/// The embedded puzzle input, parsed on first use.
+#[cfg(feature = "input")]
+fn input() -> &'static Vec<u64> {
+    static INPUT: std::sync::OnceLock<Vec<u64>> = std::sync::OnceLock::new();
+
+    INPUT.get_or_init(|| parse(include_str!("../input")).expect("invalid input"))
 }
 
 const SUBJECT_NUMBER: u64 = 7;
 const MODULUS: u64 = 20201227;
 
-fn loop_size(pbk: u64) -> usize {
-    std::iter::successors(Some(1), |v| Some((v * SUBJECT_NUMBER) % MODULUS))
-        .enumerate()
-        .take_while(|(_, v)| *v != pbk)
-        .last()
-        .map(|(i, _)| i)
-        .unwrap()
-        + 1
+/// The card and door public keys, in this order.
+fn parse(input: &str) -> Result<Vec<u64>, Error> {
+    let keys = input
+        .lines()
+        .map(|line| match line.parse() {
+            Ok(key) if key < MODULUS => Ok(key),
+            Ok(_) => Err(Error::at(
+                input,
+                line,
+                format!("public keys are below {}", MODULUS),
+            )),
+            Err(_) => Err(Error::at(input, line, "invalid number")),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match input.lines().nth(2) {
+        _ if keys.len() < 2 => Err(Error::new("expecting two public keys")),
+        Some(line) => Err(Error::at(input, line, "expecting two public keys")),
+        None => Ok(keys),
+    }
+}
+
+fn loop_size(pbk: u64) -> Result<usize, Error> {
+    num::discrete_log(SUBJECT_NUMBER as i128, pbk as i128, MODULUS as i128)
+        .map(|loop_size| loop_size as usize)
+        .ok_or_else(|| Error::new(format!("{} is not a valid public key", pbk)))
 }
 
 fn transform(subject_number: u64, loop_size: usize) -> u64 {
-    std::iter::successors(Some(1), |v| Some((v * subject_number) % MODULUS))
-        .nth(loop_size)
-        .unwrap()
+    num::mod_pow(subject_number as i128, loop_size as u128, MODULUS as i128) as u64
 }
 
-fn solve_1(pbk1: u64, pbk2: u64) -> u64 {
-    transform(pbk1, loop_size(pbk2))
+fn solve_1(pbk1: u64, pbk2: u64) -> Result<u64, Error> {
+    Ok(transform(pbk1, loop_size(pbk2)?))
 }
 
+#[cfg(feature = "input")]
 pub fn part_1() -> u64 {
-    solve_1(INPUT[0], INPUT[1])
+    solve_1(input()[0], input()[1]).expect("invalid input")
+}
+
+pub fn solve_part_1(input: &str) -> Result<u64, Error> {
+    parse(input)
+        .and_then(|input| solve_1(input[0], input[1]))
+        .map_err(|e| e.in_day(Day25::DAY))
+}
+
+pub struct Day25;
+
+impl Solution for Day25 {
+    const DAY: u32 = 25;
+
+    type Input<'a> = Vec<u64>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, Error> {
+        parse(input).map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_1(input: &Self::Input<'_>) -> Result<Answer, Error> {
+        solve_1(input[0], input[1])
+            .map(Answer::from)
+            .map_err(|e| e.in_day(Self::DAY))
+    }
+
+    fn part_2(_input: &Self::Input<'_>) -> Result<Option<Answer>, Error> {
+        Ok(None)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "bench")]
     use test::Bencher;
 
     #[test]
     fn loop_size_5764801_is_8() {
-        assert_eq!(loop_size(5764801), 8);
+        assert_eq!(loop_size(5764801), Ok(8));
     }
 
     #[test]
     fn loop_size_17807724_is_11() {
-        assert_eq!(loop_size(17807724), 11);
+        assert_eq!(loop_size(17807724), Ok(11));
     }
 
     #[test]
@@ -64,9 +117,24 @@ mod tests {
 
     #[test]
     fn same_results_example_1() {
-        assert_eq!(solve_1(17807724, 5764801), 14897079);
+        assert_eq!(solve_1(17807724, 5764801), Ok(14897079));
+    }
+
+    #[test]
+    fn invalid_keys() {
+        assert_eq!(
+            parse("5764801\n17807724\n1").unwrap_err().position,
+            Some((3, 1))
+        );
+        assert_eq!(
+            parse("5764801\n20201227").unwrap_err().position,
+            Some((2, 1))
+        );
+        assert!(parse("5764801").is_err());
+        assert_eq!(loop_size(1), Ok(0));
     }
 
+    #[cfg(feature = "bench")]
     #[bench]
     fn bench_part_1(b: &mut Bencher) {
         b.iter(part_1);
@@ -0,0 +1,7 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    let _ = day02::audit(input, &[Box::new(day02::Count), Box::new(day02::Positions)]);
+});
@@ -0,0 +1,382 @@
+use common::Error;
+
+/// A cell of a grid: column and row, from the top left corner.
+pub type Position = (usize, usize);
+
+/// A step between cells: columns right and rows down.
+pub type Delta = (isize, isize);
+
+/// The four orthogonal neighbours, clockwise from the one above.
+pub const NEIGHBOURS_4: [Delta; 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// The eight neighbours, diagonals included, row by row.
+pub const NEIGHBOURS_8: [Delta; 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// The `3^D - 1` neighbours of a cell in `D` dimensions, diagonals included,
+/// as offsets along each axis in lexicographic order.
+pub fn neighbours_nd<const D: usize>() -> Vec<[isize; D]> {
+    (0..3usize.pow(D as u32))
+        .map(|i| {
+            let mut delta = [0; D];
+            for (axis, d) in delta.iter_mut().enumerate() {
+                *d = (i / 3usize.pow((D - 1 - axis) as u32) % 3) as isize - 1;
+            }
+            delta
+        })
+        .filter(|delta| delta.iter().any(|&d| d != 0))
+        .collect()
+}
+
+/// A rectangular grid of cells, stored row by row.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// A grid of `width` columns from `cells`, row by row.
+    ///
+    /// Panics if `cells` does not fill a whole number of rows.
+    pub fn new(width: usize, cells: Vec<T>) -> Self {
+        assert!(
+            width > 0 && cells.len().is_multiple_of(width),
+            "{} cells do not fill rows of {}",
+            cells.len(),
+            width
+        );
+
+        Self {
+            width,
+            height: cells.len() / width,
+            cells,
+        }
+    }
+
+    /// Parses a character map, one row per line, with `cell` mapping each
+    /// character; characters it rejects are reported with `message`.
+    pub fn parse<F: Fn(char) -> Option<T>>(
+        input: &str,
+        message: &str,
+        cell: F,
+    ) -> Result<Self, Error> {
+        let mut width = None;
+        let mut cells = Vec::new();
+
+        for line in input.lines() {
+            let start = cells.len();
+            for (i, c) in line.char_indices() {
+                cells.push(
+                    cell(c).ok_or_else(|| Error::at(input, &line[i..i + c.len_utf8()], message))?,
+                );
+            }
+
+            let row = cells.len() - start;
+            if *width.get_or_insert(row) != row {
+                return Err(Error::at(
+                    input,
+                    line,
+                    format!("expecting a row of {} cells", width.unwrap_or_default()),
+                ));
+            }
+        }
+
+        match width {
+            Some(width) if width > 0 => Ok(Self::new(width, cells)),
+            _ => Err(Error::new("empty grid")),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The cell at `position`, if within the grid.
+    pub fn get(&self, (x, y): Delta) -> Option<&T> {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            None
+        } else {
+            Some(&self[(x as usize, y as usize)])
+        }
+    }
+
+    /// The cell at `position`, with the grid repeating in every direction.
+    pub fn get_wrapping(&self, (x, y): Delta) -> &T {
+        &self[(
+            x.rem_euclid(self.width as isize) as usize,
+            y.rem_euclid(self.height as isize) as usize,
+        )]
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width)
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = &T> {
+        self.cells.iter()
+    }
+
+    /// Every cell with its position, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = (Position, &T)> {
+        let width = self.width;
+
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(i, cell)| ((i % width, i / width), cell))
+    }
+
+    /// The cells one of `deltas` away from `position`, within the grid.
+    pub fn neighbours<'a>(
+        &'a self,
+        (x, y): Position,
+        deltas: &'a [Delta],
+    ) -> impl Iterator<Item = (Position, &'a T)> + 'a {
+        deltas.iter().filter_map(move |(dx, dy)| {
+            let (x, y) = (x as isize + dx, y as isize + dy);
+            self.get((x, y))
+                .map(|cell| ((x as usize, y as usize), cell))
+        })
+    }
+
+    /// The cells from `position` in steps of `delta`, to the edge of the
+    /// grid, `position` excluded.
+    pub fn ray(&self, (x, y): Position, (dx, dy): Delta) -> impl Iterator<Item = (Position, &T)> {
+        (1..)
+            .map(move |d| (x as isize + dx * d, y as isize + dy * d))
+            .map(move |(x, y)| {
+                self.get((x, y))
+                    .map(|cell| ((x as usize, y as usize), cell))
+            })
+            .take_while(Option::is_some)
+            .flatten()
+    }
+
+    pub fn map<U, F: FnMut(Position, &T) -> U>(&self, mut f: F) -> Grid<U> {
+        Grid::new(
+            self.width,
+            self.positions()
+                .map(|(position, cell)| f(position, cell))
+                .collect(),
+        )
+    }
+
+    /// Mirrors the grid left to right.
+    pub fn flip_h(&mut self) {
+        for row in self.cells.chunks_mut(self.width) {
+            row.reverse();
+        }
+    }
+
+    /// Mirrors the grid top to bottom.
+    pub fn flip_v(&mut self) {
+        let width = self.width;
+        for y in 0..self.height / 2 {
+            let (top, bottom) = self.cells.split_at_mut((self.height - 1 - y) * width);
+            top[y * width..(y + 1) * width].swap_with_slice(&mut bottom[..width]);
+        }
+    }
+
+    /// Draws the grid with `cell` mapping each cell to a character, one
+    /// line per row.
+    pub fn render<F: Fn(&T) -> char>(&self, cell: F) -> String {
+        self.rows()
+            .map(|row| row.iter().map(&cell).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn filled(width: usize, height: usize, value: T) -> Self {
+        Self::new(width, vec![value; width * height])
+    }
+
+    /// Rotates the grid `turns` quarter turns counterclockwise; negative
+    /// turns go clockwise.
+    pub fn rotate(&mut self, turns: isize) {
+        let (width, height) = (self.width, self.height);
+        let cell = |x: usize, y: usize| -> T {
+            match turns.rem_euclid(4) {
+                1 => self[(width - 1 - y, x)].clone(),
+                2 => self[(width - 1 - x, height - 1 - y)].clone(),
+                _ => self[(y, height - 1 - x)].clone(),
+            }
+        };
+
+        *self = match turns.rem_euclid(4) {
+            0 => return,
+            2 => Grid::new(
+                width,
+                (0..height)
+                    .flat_map(|y| (0..width).map(move |x| (x, y)))
+                    .map(|(x, y)| cell(x, y))
+                    .collect(),
+            ),
+            _ => Grid::new(
+                height,
+                (0..width)
+                    .flat_map(|y| (0..height).map(move |x| (x, y)))
+                    .map(|(x, y)| cell(x, y))
+                    .collect(),
+            ),
+        };
+    }
+}
+
+impl<T> std::ops::Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): Position) -> &T {
+        assert!(x < self.width, "column {} out of {}", x, self.width);
+        &self.cells[y * self.width + x]
+    }
+}
+
+impl<T> std::ops::IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, (x, y): Position) -> &mut T {
+        assert!(x < self.width, "column {} out of {}", x, self.width);
+        &mut self.cells[y * self.width + x]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(input: &str) -> Grid<char> {
+        Grid::parse(input, "invalid cell", Some).expect("invalid input")
+    }
+
+    #[test]
+    fn parse() {
+        let grid = grid("#..\n.#.\n");
+
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(1, 1)], '#');
+        assert_eq!(grid.render(|&c| c), "#..\n.#.");
+    }
+
+    #[test]
+    fn parse_errors() {
+        let cell = |c| if c == '#' || c == '.' { Some(c) } else { None };
+
+        let error = Grid::parse("#..\n.x.", "expecting '.' or '#'", cell)
+            .map(drop)
+            .expect_err("invalid cell");
+        assert_eq!(error.position, Some((2, 2)));
+        assert_eq!(error.snippet, "x");
+
+        let error = Grid::parse("#..\n.#", "expecting '.' or '#'", cell)
+            .map(drop)
+            .expect_err("ragged grid");
+        assert_eq!(error.position, Some((2, 1)));
+
+        Grid::parse("", "expecting '.' or '#'", cell)
+            .map(drop)
+            .expect_err("empty grid");
+    }
+
+    #[test]
+    fn access() {
+        let grid = grid("abc\ndef");
+
+        assert_eq!(grid.get((2, 1)), Some(&'f'));
+        assert_eq!(grid.get((3, 1)), None);
+        assert_eq!(grid.get((0, -1)), None);
+        assert_eq!(grid.get_wrapping((3, 1)), &'d');
+        assert_eq!(grid.get_wrapping((-1, -1)), &'f');
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = grid("abc\ndef\nghi");
+
+        let cells = |position, deltas| {
+            grid.neighbours(position, deltas)
+                .map(|(_, &c)| c)
+                .collect::<String>()
+        };
+
+        assert_eq!(cells((1, 1), &NEIGHBOURS_4[..]), "bfhd");
+        assert_eq!(cells((1, 1), &NEIGHBOURS_8[..]), "abcdfghi");
+        assert_eq!(cells((0, 0), &NEIGHBOURS_8[..]), "bde");
+    }
+
+    #[test]
+    fn neighbours_in_any_dimension() {
+        assert_eq!(neighbours_nd::<1>(), vec![[-1], [1]]);
+        assert_eq!(neighbours_nd::<3>().len(), 26);
+        assert_eq!(neighbours_nd::<4>().len(), 80);
+
+        let mut plane = neighbours_nd::<2>()
+            .into_iter()
+            .map(|[y, x]| (x, y))
+            .collect::<Vec<_>>();
+        plane.sort_by_key(|&(x, y)| (y, x));
+        assert_eq!(plane, NEIGHBOURS_8);
+    }
+
+    #[test]
+    fn ray() {
+        let grid = grid("abcd\nefgh\nijkl");
+
+        let cells = |position, delta| {
+            grid.ray(position, delta)
+                .map(|(_, &c)| c)
+                .collect::<String>()
+        };
+
+        assert_eq!(cells((0, 0), (1, 0)), "bcd");
+        assert_eq!(cells((0, 0), (1, 1)), "fk");
+        assert_eq!(cells((3, 2), (-1, -1)), "gb");
+        assert_eq!(cells((3, 2), (0, 1)), "");
+    }
+
+    #[test]
+    fn rotate() {
+        let mut grid = grid("abc\ndef");
+
+        grid.rotate(1);
+        assert_eq!(grid.render(|&c| c), "cf\nbe\nad");
+
+        grid.rotate(-1);
+        assert_eq!(grid.render(|&c| c), "abc\ndef");
+
+        grid.rotate(2);
+        assert_eq!(grid.render(|&c| c), "fed\ncba");
+
+        grid.rotate(-2);
+        grid.rotate(3);
+        assert_eq!(grid.render(|&c| c), "da\neb\nfc");
+    }
+
+    #[test]
+    fn flip() {
+        let mut grid = grid("abc\ndef\nghi");
+
+        grid.flip_h();
+        assert_eq!(grid.render(|&c| c), "cba\nfed\nihg");
+
+        grid.flip_v();
+        assert_eq!(grid.render(|&c| c), "ihg\nfed\ncba");
+    }
+}